@@ -14,6 +14,29 @@ if [ -f $ENV_FILE ]; then
 fi
 
 cat > $ENV_FILE << EOL
+# HTTP server bind address (defaults shown)
+# HOST=0.0.0.0
+# PORT=3000
+
+# Comma-separated origins allowed to call the API from a browser
+# (debug builds allow any origin when unset, release builds allow none)
+# ALLOWED_ORIGINS=http://localhost:5173
+
+# Comma-separated keys accepted as "Authorization: Bearer <key>" on mutating routes
+# (POST/PATCH/DELETE are rejected when unset)
+# API_KEYS=change-me
+
+# Requests each client IP may make per window (defaults shown)
+# RATE_LIMIT_REQUESTS=120
+# RATE_LIMIT_WINDOW_SECS=60
+
+# Largest request body in bytes and longest request in seconds (defaults shown)
+# MAX_BODY_BYTES=1048576
+# REQUEST_TIMEOUT_SECS=30
+
+# Log JSON lines to the console instead of using log4rs.yaml
+# LOG_FORMAT=json
+
 # Redis configuration
 REDIS_URL=redis://127.0.0.1:6379
 REDIS_USERNAME=
@@ -22,6 +45,14 @@ REDIS_PASSWORD=
 # Uncomment and set these if using Redis authentication
 # REDIS_USERNAME=default
 # REDIS_PASSWORD=your_password_here
+
+# Uncomment and set these for rediss:// (TLS) URLs; requires the tls feature
+# REDIS_CA_CERT=/path/to/ca.pem
+# REDIS_CLIENT_CERT=/path/to/client.pem
+# REDIS_CLIENT_KEY=/path/to/client.key
+
+# Uncomment to namespace keys when environments share one Redis instance
+# REDIS_KEY_PREFIX=staging
 EOL
 
 chmod 600 $ENV_FILE
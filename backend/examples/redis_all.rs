@@ -6,7 +6,6 @@ use backend::{
 };
 use log::{debug, info, warn};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 
 /// Example struct to demonstrate serialization/deserialization with Redis
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,26 +26,19 @@ async fn user_example(redis: &RedisPool) -> Result<(), Error> {
     email: "john@example.com".to_string(),
   };
 
-  // Serialize user to JSON
-  let user_json = serde_json::to_string(&user)
-    .map_err(|e| Error::RedisParseError(format!("Failed to serialize user: {}", e)))?;
-
-  // Store in Redis with a TTL of 1 hour
+  // Store in Redis as JSON with a TTL of 1 hour
   let key = format!("user:{}", user.id);
-  redis.set_ex(&key, user_json, 3600).await?;
+  redis.set_json_ex(&key, &user, 3600).await?;
   info!("Stored user in Redis with key: {}", key);
 
   // Retrieve from Redis
-  if redis.exists(&key).await? {
-    let user_json: String = redis.get(&key).await?;
-
-    // Deserialize from JSON
-    let retrieved_user: User = serde_json::from_str(&user_json)
-      .map_err(|e| Error::RedisParseError(format!("Failed to deserialize user: {}", e)))?;
-
-    debug!("Retrieved user from Redis: {:?}", retrieved_user);
-  } else {
-    debug!("User not found in Redis");
+  match redis.get_opt::<String>(&key).await? {
+    Some(json) => {
+      let retrieved_user: User = serde_json::from_str(&json)
+        .map_err(|e| Error::RedisParseError(format!("Failed to deserialize user: {}", e)))?;
+      debug!("Retrieved user from Redis: {:?}", retrieved_user);
+    }
+    None => debug!("User not found in Redis"),
   }
 
   // Delete from Redis
@@ -62,10 +54,8 @@ async fn counter_example(redis: &RedisPool) -> Result<(), Error> {
 
   let counter_key = "visitor_count";
 
-  // Increment counter using execute_command for direct command execution
-  let count: i64 = redis
-    .execute_command(&mut redis::cmd("INCR").arg(counter_key))
-    .await?;
+  // Increment counter atomically
+  let count = redis.incr(counter_key).await?;
 
   info!("Visitor count: {}", count);
 
@@ -82,43 +72,25 @@ async fn hash_example(redis: &RedisPool) -> Result<(), Error> {
   let hash_key = "product:12345";
 
   // Store multiple fields in a hash
-  redis
-    .execute_command::<()>(
-      &mut redis::cmd("HSET")
-        .arg(hash_key)
-        .arg("name")
-        .arg("Awesome Product")
-        .arg("price")
-        .arg(99.99)
-        .arg("stock")
-        .arg(42),
-    )
-    .await?;
+  redis.hset(hash_key, "name", "Awesome Product").await?;
+  redis.hset(hash_key, "price", 99.99).await?;
+  redis.hset(hash_key, "stock", 42).await?;
 
   info!("Stored product data in Redis hash");
 
   // Get specific fields
-  let name: String = redis
-    .execute_command(&mut redis::cmd("HGET").arg(hash_key).arg("name"))
-    .await?;
-
-  let stock: i64 = redis
-    .execute_command(&mut redis::cmd("HGET").arg(hash_key).arg("stock"))
-    .await?;
+  let name: String = redis.hget(hash_key, "name").await?;
+  let stock: i64 = redis.hget(hash_key, "stock").await?;
 
   info!("Product '{}' has {} items in stock", name, stock);
 
   // Get all fields
-  let hash_data: HashMap<String, String> = redis
-    .execute_command(&mut redis::cmd("HGETALL").arg(hash_key))
-    .await?;
+  let hash_data = redis.hgetall(hash_key).await?;
 
   debug!("All product data: {:?}", hash_data);
 
   // Clean up
-  redis
-    .execute_command::<()>(&mut redis::cmd("DEL").arg(hash_key))
-    .await?;
+  redis.del(hash_key).await?;
 
   Ok(())
 }
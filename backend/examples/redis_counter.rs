@@ -1,15 +1,17 @@
 use anyhow::{Context, Result};
-use backend::{http::Error, init_env, init_logging, redis::RedisPool};
+use backend::{
+  http::Error,
+  init_env, init_logging,
+  redis::{RedisOperations, RedisPool},
+};
 use log::info;
 
 /// Example of storing a simple counter in Redis
 async fn counter_example(redis: &RedisPool) -> Result<(), Error> {
   let counter_key = "visitor_count";
 
-  // Increment counter using execute_command for direct command execution
-  let count: i64 = redis
-    .execute_command(&mut redis::cmd("INCR").arg(counter_key))
-    .await?;
+  // Increment counter atomically
+  let count = redis.incr(counter_key).await?;
 
   info!("Visitor count: {}", count);
 
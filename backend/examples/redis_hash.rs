@@ -1,50 +1,35 @@
 use anyhow::{Context, Result};
-use backend::{http::Error, init_env, init_logging, redis::RedisPool};
+use backend::{
+  http::Error,
+  init_env, init_logging,
+  redis::{RedisOperations, RedisPool},
+};
 use log::{debug, info};
-use std::collections::HashMap;
 
 /// Example of working with a Redis hash
 async fn hash_example(redis: &RedisPool) -> Result<(), Error> {
   let hash_key = "product:12345";
 
   // Store multiple fields in a hash
-  redis
-    .execute_command::<()>(
-      &mut redis::cmd("HSET")
-        .arg(hash_key)
-        .arg("name")
-        .arg("Awesome Product")
-        .arg("price")
-        .arg(99.99)
-        .arg("stock")
-        .arg(42),
-    )
-    .await?;
+  redis.hset(hash_key, "name", "Awesome Product").await?;
+  redis.hset(hash_key, "price", 99.99).await?;
+  redis.hset(hash_key, "stock", 42).await?;
 
   info!("Stored product data in Redis hash");
 
   // Get specific fields
-  let name: String = redis
-    .execute_command(&mut redis::cmd("HGET").arg(hash_key).arg("name"))
-    .await?;
-
-  let stock: i64 = redis
-    .execute_command(&mut redis::cmd("HGET").arg(hash_key).arg("stock"))
-    .await?;
+  let name: String = redis.hget(hash_key, "name").await?;
+  let stock: i64 = redis.hget(hash_key, "stock").await?;
 
   info!("Product '{}' has {} items in stock", name, stock);
 
   // Get all fields
-  let hash_data: HashMap<String, String> = redis
-    .execute_command(&mut redis::cmd("HGETALL").arg(hash_key))
-    .await?;
+  let hash_data = redis.hgetall(hash_key).await?;
 
   debug!("All product data: {:?}", hash_data);
 
   // Clean up
-  redis
-    .execute_command::<()>(&mut redis::cmd("DEL").arg(hash_key))
-    .await?;
+  redis.del(hash_key).await?;
 
   Ok(())
 }
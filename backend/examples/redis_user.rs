@@ -24,26 +24,19 @@ async fn user_example(redis: &RedisPool) -> Result<(), Error> {
     email: "john@example.com".to_string(),
   };
 
-  // Serialize user to JSON
-  let user_json = serde_json::to_string(&user)
-    .map_err(|e| Error::RedisParseError(format!("Failed to serialize user: {}", e)))?;
-
-  // Store in Redis with a TTL of 1 hour
+  // Store in Redis as JSON with a TTL of 1 hour
   let key = format!("user:{}", user.id);
-  redis.set_ex(&key, user_json, 3600).await?;
+  redis.set_json_ex(&key, &user, 3600).await?;
   info!("Stored user in Redis with key: {}", key);
 
   // Retrieve from Redis
-  if redis.exists(&key).await? {
-    let user_json: String = redis.get(&key).await?;
-
-    // Deserialize from JSON
-    let retrieved_user: User = serde_json::from_str(&user_json)
-      .map_err(|e| Error::RedisParseError(format!("Failed to deserialize user: {}", e)))?;
-
-    debug!("Retrieved user from Redis: {:?}", retrieved_user);
-  } else {
-    debug!("User not found in Redis");
+  match redis.get_opt::<String>(&key).await? {
+    Some(json) => {
+      let retrieved_user: User = serde_json::from_str(&json)
+        .map_err(|e| Error::RedisParseError(format!("Failed to deserialize user: {}", e)))?;
+      debug!("Retrieved user from Redis: {:?}", retrieved_user);
+    }
+    None => debug!("User not found in Redis"),
   }
 
   // Delete from Redis
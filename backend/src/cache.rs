@@ -0,0 +1,71 @@
+//! The small key-value interface behind the status routes.
+//!
+//! `/redis/status` and `/status/last` only need to ping a store and keep a timestamp
+//! and a counter in it, so they take a `Cache` rather than a `RedisPool`. The pool is
+//! the cache in production; tests can put any implementation in the `AppState`.
+
+use crate::http::Error;
+use crate::redis::{RedisOperations, RedisPool};
+
+/// A key-value store holding string values and integer counters
+#[async_trait::async_trait]
+pub trait Cache: Send + Sync {
+  /// Check that the store is answering
+  async fn ping(&self) -> Result<(), Error>;
+
+  /// Get a value, or `None` if the key does not exist
+  async fn get(&self, key: &str) -> Result<Option<String>, Error>;
+
+  /// Set a value, replacing any previous one
+  async fn set(&self, key: &str, value: &str) -> Result<(), Error>;
+
+  /// Atomically increment an integer key, returning the new value
+  async fn incr(&self, key: &str) -> Result<i64, Error>;
+
+  /// Expire a key after `seconds`, returning false if it doesn't exist
+  async fn expire(&self, key: &str, seconds: u64) -> Result<bool, Error>;
+
+  /// Increment an integer key and give it an expiry of `seconds` if it has none,
+  /// together, so a counter can never be left without one
+  async fn incr_with_expiry(&self, key: &str, seconds: u64) -> Result<i64, Error>;
+}
+
+#[async_trait::async_trait]
+impl Cache for RedisPool {
+  async fn ping(&self) -> Result<(), Error> {
+    RedisPool::ping(self).await
+  }
+
+  async fn get(&self, key: &str) -> Result<Option<String>, Error> {
+    self.get_opt(key).await
+  }
+
+  async fn set(&self, key: &str, value: &str) -> Result<(), Error> {
+    RedisOperations::set(self, key, value).await
+  }
+
+  async fn incr(&self, key: &str) -> Result<i64, Error> {
+    RedisOperations::incr(self, key).await
+  }
+
+  async fn expire(&self, key: &str, seconds: u64) -> Result<bool, Error> {
+    RedisOperations::expire(self, key, seconds).await
+  }
+
+  async fn incr_with_expiry(&self, key: &str, seconds: u64) -> Result<i64, Error> {
+    let key = self.prefixed(key);
+    let (hits,): (i64,) = self
+      .execute_pipeline(
+        redis::pipe()
+          .cmd("INCR")
+          .arg(&key)
+          .cmd("EXPIRE")
+          .arg(&key)
+          .arg(seconds)
+          .arg("NX")
+          .ignore(),
+      )
+      .await?;
+    Ok(hits)
+  }
+}
@@ -0,0 +1,40 @@
+use axum::{extract::Request, middleware::Next, response::Response};
+use log::info;
+use std::time::Instant;
+
+/// Log target for access log lines, so log4rs can route them separately
+pub const ACCESS_LOG_TARGET: &str = "backend::http::access";
+
+/// Middleware that logs method, path, status and latency for every request
+///
+/// Each request is also counted in the `http_requests_total` and
+/// `http_request_duration_seconds` metrics.
+pub async fn access_log(request: Request, next: Next) -> Response {
+  let method = request.method().clone();
+  let path = request.uri().path().to_string();
+  let start = Instant::now();
+
+  let response = next.run(request).await;
+  let elapsed = start.elapsed();
+  let status = response.status().as_u16();
+
+  info!(
+    target: ACCESS_LOG_TARGET,
+    "{} {} {} {:.1}ms",
+    method,
+    path,
+    status,
+    elapsed.as_secs_f64() * 1000.0
+  );
+
+  metrics::counter!(
+    "http_requests_total",
+    "method" => method.to_string(),
+    "status" => status.to_string()
+  )
+  .increment(1);
+  metrics::histogram!("http_request_duration_seconds", "method" => method.to_string())
+    .record(elapsed.as_secs_f64());
+
+  response
+}
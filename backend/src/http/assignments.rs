@@ -0,0 +1,882 @@
+use axum::{
+  body::{Body, Bytes},
+  extract::{FromRequestParts, Path},
+  http::{header, request::Parts},
+  response::{IntoResponse, Response},
+  routing::{delete, get, post},
+  Extension, Router,
+};
+use futures_util::{stream, StreamExt};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::http::{AppState, Authenticated, Cursor, CursorKind, Error, Json, Query, Redis};
+use crate::locker::{promote_next_from_waitlist, store, Assignment};
+use crate::redis::{RedisOperations, RedisPool};
+use crate::student::{self, store::student_key, Student, StudentId};
+
+/// Assignments whose students are fetched per chunk of the export
+const EXPORT_BATCH_SIZE: usize = 100;
+
+/// Columns of the assignment CSV export
+const EXPORT_COLUMNS: [&str; 6] = [
+  "student_id",
+  "full_name",
+  "grade",
+  "locker_number",
+  "hallway",
+  "assigned_at",
+];
+
+/// Create a router with the assignment routes
+pub fn router(state: AppState) -> Router {
+  debug!("Setting up assignment routes");
+  Router::new()
+    .route("/assignments/export", get(export_assignments))
+    .route("/assignments/swap", post(swap_assignments))
+    .route("/assignments/{student_id}", delete(release_assignment))
+    .route("/assignments/{student_id}/claim", post(claim_assignment))
+    .route(
+      "/students/{student_id}/assignment",
+      get(get_student_assignment).put(assign_locker),
+    )
+    .with_state(state)
+}
+
+/// Request body for manually assigning a locker
+#[derive(Debug, Deserialize)]
+pub struct AssignLocker {
+  locker_number: String,
+}
+
+/// A student's current assignment, with the locker's details, or 404 if they have none
+pub async fn get_student_assignment(
+  Path(student_id): Path<String>,
+  Redis(redis_pool): Redis,
+) -> Result<Json<Assignment>, Error> {
+  let student_id = StudentId::new(student_id)?;
+
+  let assignment = store::get_assignment(&redis_pool, &student_id)
+    .await?
+    .ok_or(Error::NotFound)?;
+  Ok(Json(assignment))
+}
+
+/// Manually assign a specific locker to a student, overriding the matcher
+///
+/// The assignment is marked claimed, so the sweeper leaves it alone, and any locker the
+/// student held before is freed. Answers 404 if the student or locker doesn't exist and
+/// 409 if another student holds the locker.
+pub async fn assign_locker(
+  Path(student_id): Path<String>,
+  Redis(redis_pool): Redis,
+  Json(request): Json<AssignLocker>,
+) -> Result<Json<Assignment>, Error> {
+  let student_id = StudentId::new(student_id)?;
+  if !student::store::exists(&redis_pool, &student_id).await? {
+    return Err(Error::NotFound);
+  }
+
+  let (mut lockers, _) = store::get_lockers(&redis_pool, &[request.locker_number]).await?;
+  let locker = lockers.pop().ok_or(Error::NotFound)?;
+
+  let assignment = store::claim_locker(&redis_pool, &student_id, &locker).await?;
+  info!(
+    "Manually assigned locker {} to student {}",
+    locker.number,
+    student_id.to_string()
+  );
+
+  Ok(Json(assignment))
+}
+
+/// Mark a student's assigned locker as claimed so it isn't released by the sweeper
+pub async fn claim_assignment(
+  Path(student_id): Path<String>,
+  Redis(redis_pool): Redis,
+) -> Result<Json<Assignment>, Error> {
+  let student_id = StudentId::new(student_id)?;
+
+  let mut assignment = store::get_assignment(&redis_pool, &student_id)
+    .await?
+    .ok_or(Error::NotFound)?;
+
+  assignment.claim();
+  store::save_assignment(&redis_pool, &assignment).await?;
+  info!(
+    "Student {} claimed locker {}",
+    student_id.to_string(),
+    assignment.locker.number
+  );
+
+  Ok(Json(assignment))
+}
+
+/// Header carrying the cursor for the next page of a paged export
+const NEXT_CURSOR_HEADER: &str = "x-next-cursor";
+
+/// Optional paging of the assignment export
+#[derive(Debug, Default, Deserialize)]
+pub struct ExportParams {
+  /// Rows per page; the whole export is sent when absent
+  limit: Option<usize>,
+  /// `X-Next-Cursor` from the previous page
+  cursor: Option<String>,
+}
+
+/// One row of the assignment export, in column order
+///
+/// The student's name and grade are empty if their record is missing.
+#[derive(Debug, Serialize)]
+pub struct ExportRow {
+  pub student_id: String,
+  pub full_name: Option<String>,
+  pub grade: Option<u8>,
+  pub locker_number: String,
+  pub hallway: String,
+  pub assigned_at: String,
+}
+
+/// Representation of the assignment export, chosen from the `Accept` header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+  Csv,
+  Json,
+}
+
+impl ExportFormat {
+  /// Pick the format with the highest `q` value in an `Accept` header, preferring CSV on ties
+  ///
+  /// A missing or empty header, or a wildcard, means CSV. Returns `None` if neither
+  /// format is acceptable.
+  fn negotiate(accept: Option<&str>) -> Option<Self> {
+    let accept = match accept {
+      Some(accept) if !accept.trim().is_empty() => accept,
+      _ => return Some(Self::Csv),
+    };
+
+    let mut best: Option<(f32, Self)> = None;
+    for range in accept.split(',') {
+      let mut params = range.split(';').map(str::trim);
+      let media_type = params.next().unwrap_or_default().to_ascii_lowercase();
+      let q = match params.find_map(|param| param.strip_prefix("q=")) {
+        Some(q) => q.parse().unwrap_or(0.0),
+        None => 1.0,
+      };
+      let format = match media_type.as_str() {
+        "text/csv" | "text/*" | "*/*" => Self::Csv,
+        "application/json" | "application/*" => Self::Json,
+        _ => continue,
+      };
+      if q > 0.0 && best.is_none_or(|(best_q, _)| q > best_q) {
+        best = Some((q, format));
+      }
+    }
+    best.map(|(_, format)| format)
+  }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ExportFormat {
+  type Rejection = Error;
+
+  async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Error> {
+    let not_acceptable =
+      || Error::NotAcceptable("the export is available as text/csv or application/json".into());
+    let accept = match parts.headers.get(header::ACCEPT) {
+      Some(accept) => Some(accept.to_str().map_err(|_| not_acceptable())?),
+      None => None,
+    };
+    Self::negotiate(accept).ok_or_else(not_acceptable)
+  }
+}
+
+/// Export every assignment, sorted by hallway then locker number
+///
+/// Responds with CSV or a JSON array of `ExportRow`s depending on the `Accept` header,
+/// and `406 Not Acceptable` if it asks for anything else. Locker numbers sort by prefix
+/// and then numerically, so "B-3" comes before "B-12".
+///
+/// With a `limit`, one page is sent and the cursor for the next is returned in the
+/// `X-Next-Cursor` header, which is absent on the last page.
+///
+/// CSV rows are written in batches as student records are fetched, so the response
+/// streams instead of building the whole file in memory.
+///
+/// The export lists students' names and lockers, so this requires an API key even
+/// though it's a `GET`.
+pub async fn export_assignments(
+  format: ExportFormat,
+  Query(params): Query<ExportParams>,
+  Redis(redis_pool): Redis,
+  authenticated: Option<Extension<Authenticated>>,
+) -> Result<Response, Error> {
+  if authenticated.is_none() {
+    return Err(Error::Unauthorized);
+  }
+  if params.limit == Some(0) {
+    return Err(Error::unprocessable_entity([(
+      "limit",
+      "must be at least 1",
+    )]));
+  }
+  let start = match &params.cursor {
+    Some(cursor) => usize::try_from(Cursor::decode(cursor, CursorKind::RowOffset, None)?.position)
+      .map_err(|_| Error::BadRequest("invalid cursor".to_string()))?,
+    None => 0,
+  };
+
+  let mut assignments = store::list_assignments(&redis_pool).await?;
+  assignments.sort_by(|a, b| {
+    (&a.locker.hallway, &a.locker.number).cmp(&(&b.locker.hallway, &b.locker.number))
+  });
+  let total = assignments.len();
+  let end = params
+    .limit
+    .map_or(total, |limit| start.saturating_add(limit).min(total));
+  let next_cursor =
+    (end < total).then(|| Cursor::new(CursorKind::RowOffset, end as u64, None).encode());
+  let assignments: Vec<Assignment> = assignments.into_iter().take(end).skip(start).collect();
+  info!(
+    "Exporting {} assignments as {:?}",
+    assignments.len(),
+    format
+  );
+
+  let mut batches = Vec::new();
+  let mut remaining = assignments.into_iter().peekable();
+  while remaining.peek().is_some() {
+    batches.push(
+      remaining
+        .by_ref()
+        .take(EXPORT_BATCH_SIZE)
+        .collect::<Vec<_>>(),
+    );
+  }
+
+  let mut response = if format == ExportFormat::Json {
+    let mut rows = Vec::new();
+    for batch in &batches {
+      rows.extend(export_rows(&redis_pool, batch).await?);
+    }
+    Json(rows).into_response()
+  } else {
+    csv_response(redis_pool, batches)
+  };
+
+  if let Some(next_cursor) = next_cursor {
+    if let Ok(value) = header::HeaderValue::from_str(&next_cursor) {
+      response.headers_mut().insert(NEXT_CURSOR_HEADER, value);
+    }
+  }
+  Ok(response)
+}
+
+/// Stream the CSV export of `batches`, header row first
+fn csv_response(redis_pool: Arc<RedisPool>, batches: Vec<Vec<Assignment>>) -> Response {
+  let header_row = csv_chunk(|writer| writer.write_record(EXPORT_COLUMNS));
+  let rows = stream::iter(batches).then(move |batch| {
+    let redis_pool = redis_pool.clone();
+    async move {
+      let rows = export_rows(&redis_pool, &batch).await?;
+      csv_chunk(|writer| rows.iter().try_for_each(|row| writer.serialize(row)))
+    }
+  });
+  let body = stream::once(async { header_row }).chain(rows);
+
+  (
+    [(header::CONTENT_TYPE, "text/csv")],
+    Body::from_stream(body),
+  )
+    .into_response()
+}
+
+/// Build the export rows for one batch of assignments, fetching their students in one `MGET`
+async fn export_rows(
+  redis_pool: &RedisPool,
+  batch: &[Assignment],
+) -> Result<Vec<ExportRow>, Error> {
+  let keys: Vec<String> = batch.iter().map(|a| student_key(&a.student_id)).collect();
+  let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+  let students: Vec<Option<String>> = redis_pool.mget(&keys).await?;
+
+  Ok(
+    batch
+      .iter()
+      .zip(students)
+      .map(|(assignment, student)| {
+        let student = student.and_then(|json| serde_json::from_str::<Student>(&json).ok());
+        if student.is_none() {
+          warn!(
+            "No student record for assignment of {}",
+            assignment.student_id.to_string()
+          );
+        }
+        ExportRow {
+          student_id: assignment.student_id.to_string(),
+          full_name: student.as_ref().map(Student::full_name),
+          grade: student.as_ref().map(|s| s.grade),
+          locker_number: assignment.locker.number.to_string(),
+          hallway: assignment.locker.hallway.clone(),
+          assigned_at: assignment.assigned_at.to_rfc3339(),
+        }
+      })
+      .collect(),
+  )
+}
+
+/// Run `write` against a headerless CSV writer and return what it wrote
+fn csv_chunk(
+  write: impl FnOnce(&mut csv::Writer<Vec<u8>>) -> csv::Result<()>,
+) -> Result<Bytes, Error> {
+  let mut writer = csv::WriterBuilder::new()
+    .has_headers(false)
+    .from_writer(Vec::new());
+  write(&mut writer).map_err(|e| anyhow::anyhow!("Failed to write CSV: {}", e))?;
+  let bytes = writer
+    .into_inner()
+    .map_err(|e| anyhow::anyhow!("Failed to write CSV: {}", e))?;
+  Ok(Bytes::from(bytes))
+}
+
+/// Request body for trading lockers between two students
+#[derive(Debug, Deserialize)]
+pub struct SwapRequest {
+  student_a: String,
+  student_b: String,
+}
+
+/// The new assignments after a swap
+#[derive(Debug, Serialize)]
+pub struct Swapped {
+  pub student_a: Assignment,
+  pub student_b: Assignment,
+}
+
+/// Trade the lockers of two students, failing with 409 if either has no assignment
+pub async fn swap_assignments(
+  Redis(redis_pool): Redis,
+  Json(request): Json<SwapRequest>,
+) -> Result<Json<Swapped>, Error> {
+  let student_a = StudentId::new(request.student_a)?;
+  let student_b = StudentId::new(request.student_b)?;
+  if student_a.to_string() == student_b.to_string() {
+    return Err(Error::unprocessable_entity([(
+      "student_b",
+      "must be a different student",
+    )]));
+  }
+
+  let (student_a, student_b) = store::swap_assignments(&redis_pool, &student_a, &student_b).await?;
+  info!(
+    "Students {} and {} swapped lockers {} and {}",
+    student_a.student_id.to_string(),
+    student_b.student_id.to_string(),
+    student_b.locker.number,
+    student_a.locker.number
+  );
+
+  Ok(Json(Swapped {
+    student_a,
+    student_b,
+  }))
+}
+
+/// The outcome of releasing a student's locker
+#[derive(Debug, Serialize)]
+pub struct Released {
+  /// The assignment that was removed
+  pub released: Assignment,
+  /// The waitlisted student who got the locker, if anyone was waiting
+  pub promoted: Option<Assignment>,
+}
+
+/// Release a student's locker back to the pool, handing it to the next waitlisted student
+pub async fn release_assignment(
+  Path(student_id): Path<String>,
+  Redis(redis_pool): Redis,
+) -> Result<Json<Released>, Error> {
+  let student_id = StudentId::new(student_id)?;
+
+  let assignment = store::get_assignment(&redis_pool, &student_id)
+    .await?
+    .ok_or(Error::NotFound)?;
+
+  store::release_assignment(&redis_pool, &assignment).await?;
+  info!(
+    "Student {} released locker {}",
+    student_id.to_string(),
+    assignment.locker.number
+  );
+  let promoted = promote_next_from_waitlist(&redis_pool, &assignment.locker).await?;
+
+  Ok(Json(Released {
+    released: assignment,
+    promoted,
+  }))
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  #[cfg(feature = "redis-tests")]
+  use crate::locker::waitlist::WAITLIST_KEY;
+  #[cfg(feature = "redis-tests")]
+  use crate::locker::{Locker, LockerSize};
+  use crate::redis::RedisConfig;
+  #[cfg(feature = "redis-tests")]
+  use crate::{init_env, init_logging};
+  use axum::{body::Body, http::Request};
+  #[cfg(feature = "redis-tests")]
+  use http_body_util::BodyExt;
+  #[cfg(feature = "redis-tests")]
+  use serde_json::Value;
+  use tower::ServiceExt;
+
+  #[cfg(feature = "redis-tests")]
+  async fn setup() -> Arc<RedisPool> {
+    let _ = init_logging(); // Ignore error if already initialized
+    init_env().unwrap();
+    Arc::new(RedisPool::init().await.unwrap())
+  }
+
+  #[cfg(feature = "redis-tests")]
+  fn release(student_id: &str) -> Request<Body> {
+    Request::delete(format!("/assignments/{}", student_id))
+      .body(Body::empty())
+      .unwrap()
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_release_frees_locker_for_waitlist() {
+    let pool = setup().await;
+    let locker = Locker::new(
+      "X-1".to_string(),
+      "X".to_string(),
+      1,
+      LockerSize::Standard,
+      false,
+    )
+    .unwrap();
+    let holder = StudentId::new("940001".to_string()).unwrap();
+    let waiting = StudentId::new("940002".to_string()).unwrap();
+    store::claim_locker(&pool, &holder, &locker).await.unwrap();
+    pool.del(WAITLIST_KEY).await.unwrap();
+    pool.lpush(WAITLIST_KEY, waiting.to_string()).await.unwrap();
+
+    let response = router(pool.clone().into())
+      .oneshot(release("940001"))
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), 200);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["released"]["locker"]["number"], "X-1");
+    assert_eq!(body["promoted"]["student_id"], "940002");
+    assert!(store::get_assignment(&pool, &holder)
+      .await
+      .unwrap()
+      .is_none());
+
+    let promoted = store::get_assignment(&pool, &waiting)
+      .await
+      .unwrap()
+      .unwrap();
+    store::release_assignment(&pool, &promoted).await.unwrap();
+  }
+
+  #[cfg(feature = "redis-tests")]
+  fn export_uri(uri: &str) -> Request<Body> {
+    Request::get(uri)
+      .extension(Authenticated)
+      .body(Body::empty())
+      .unwrap()
+  }
+
+  #[cfg(feature = "redis-tests")]
+  fn export(accept: Option<&str>) -> Request<Body> {
+    let mut request = Request::get("/assignments/export").extension(Authenticated);
+    if let Some(accept) = accept {
+      request = request.header(header::ACCEPT, accept);
+    }
+    request.body(Body::empty()).unwrap()
+  }
+
+  #[tokio::test]
+  async fn test_export_requires_api_key() {
+    // Rejected before Redis is touched, so an unconnected pool is enough
+    let pool = Arc::new(RedisPool::new(RedisConfig::default()).unwrap());
+
+    let response = router(pool.into())
+      .oneshot(
+        Request::get("/assignments/export")
+          .body(Body::empty())
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), 401);
+  }
+
+  #[test]
+  fn test_negotiate_export_format() {
+    for (accept, expected) in [
+      (None, Some(ExportFormat::Csv)),
+      (Some("*/*"), Some(ExportFormat::Csv)),
+      (Some("text/csv"), Some(ExportFormat::Csv)),
+      (Some("application/json"), Some(ExportFormat::Json)),
+      (
+        Some("text/csv;q=0.5, application/json"),
+        Some(ExportFormat::Json),
+      ),
+      (Some("application/json;q=0, */*"), Some(ExportFormat::Csv)),
+      (Some("application/xml"), None),
+    ] {
+      assert_eq!(ExportFormat::negotiate(accept), expected, "{:?}", accept);
+    }
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_export_lists_assignments_as_csv() {
+    let pool = setup().await;
+    let locker = Locker::new(
+      "X-2".to_string(),
+      "X".to_string(),
+      1,
+      LockerSize::Standard,
+      false,
+    )
+    .unwrap();
+    let student_id = StudentId::new("940004".to_string()).unwrap();
+    store::claim_locker(&pool, &student_id, &locker)
+      .await
+      .unwrap();
+
+    let response = router(pool.clone().into())
+      .oneshot(export(Some("text/csv")))
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "text/csv");
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body = String::from_utf8(body.to_vec()).unwrap();
+    let mut lines = body.lines();
+    assert_eq!(
+      lines.next(),
+      Some("student_id,full_name,grade,locker_number,hallway,assigned_at")
+    );
+    assert!(lines.any(|line| line.starts_with("940004,") && line.contains(",X-2,X,")));
+
+    let assignment = store::get_assignment(&pool, &student_id)
+      .await
+      .unwrap()
+      .unwrap();
+    store::release_assignment(&pool, &assignment).await.unwrap();
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_export_lists_assignments_as_json() {
+    let pool = setup().await;
+    let locker = Locker::new(
+      "X-3".to_string(),
+      "X".to_string(),
+      1,
+      LockerSize::Standard,
+      false,
+    )
+    .unwrap();
+    let student_id = StudentId::new("940005".to_string()).unwrap();
+    store::claim_locker(&pool, &student_id, &locker)
+      .await
+      .unwrap();
+
+    let response = router(pool.clone().into())
+      .oneshot(export(Some("application/json")))
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    let row = body
+      .as_array()
+      .unwrap()
+      .iter()
+      .find(|row| row["student_id"] == "940005")
+      .unwrap();
+    assert_eq!(row["locker_number"], "X-3");
+    assert_eq!(row["hallway"], "X");
+
+    let assignment = store::get_assignment(&pool, &student_id)
+      .await
+      .unwrap()
+      .unwrap();
+    store::release_assignment(&pool, &assignment).await.unwrap();
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_export_pages_with_cursor() {
+    let pool = setup().await;
+    let holders = ["940012", "940013", "940014"];
+    for (i, id) in holders.iter().enumerate() {
+      let locker = Locker::new(
+        format!("X-{}", 10 + i),
+        "X".to_string(),
+        1,
+        LockerSize::Standard,
+        false,
+      )
+      .unwrap();
+      store::claim_locker(&pool, &StudentId::new(id.to_string()).unwrap(), &locker)
+        .await
+        .unwrap();
+    }
+    let app = router(pool.clone().into());
+
+    // Page through one row at a time, collecting every student id
+    let mut seen = Vec::new();
+    let mut uri = "/assignments/export?limit=1".to_string();
+    loop {
+      let response = app
+        .clone()
+        .oneshot(
+          Request::get(&uri)
+            .extension(Authenticated)
+            .header(header::ACCEPT, "application/json")
+            .body(Body::empty())
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+      assert_eq!(response.status(), 200);
+      let next = response
+        .headers()
+        .get(NEXT_CURSOR_HEADER)
+        .map(|value| value.to_str().unwrap().to_string());
+      let body = response.into_body().collect().await.unwrap().to_bytes();
+      let rows: Vec<Value> = serde_json::from_slice(&body).unwrap();
+      assert_eq!(rows.len(), 1);
+      seen.push(rows[0]["student_id"].as_str().unwrap().to_string());
+      match next {
+        Some(cursor) => uri = format!("/assignments/export?limit=1&cursor={}", cursor),
+        None => break,
+      }
+    }
+    for id in holders {
+      assert_eq!(seen.iter().filter(|seen| *seen == id).count(), 1, "{}", id);
+    }
+
+    let response = app
+      .oneshot(export_uri("/assignments/export?cursor=garbage"))
+      .await
+      .unwrap();
+    assert_eq!(response.status(), 400);
+
+    for id in holders {
+      let id = StudentId::new(id.to_string()).unwrap();
+      let assignment = store::get_assignment(&pool, &id).await.unwrap().unwrap();
+      store::release_assignment(&pool, &assignment).await.unwrap();
+    }
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_export_with_unsupported_accept_is_not_acceptable() {
+    let pool = setup().await;
+
+    let response = router(pool.into())
+      .oneshot(export(Some("application/xml")))
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), 406);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"]["code"], "not_acceptable");
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_unassigned_student_assignment_is_not_found() {
+    let pool = setup().await;
+
+    let response = router(pool.into())
+      .oneshot(
+        Request::get("/students/940006/assignment")
+          .body(Body::empty())
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), 404);
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_manual_assignment_then_get() {
+    use crate::student::store as student_store;
+    use chrono::{Datelike, Utc};
+
+    let pool = setup().await;
+    let student = Student::new(
+      "940007".to_string(),
+      "Manual".to_string(),
+      "Override".to_string(),
+      "940007@csxlabs.edu".to_string(),
+      10,
+      Utc::now().year() as u16 + 2,
+      None,
+    )
+    .unwrap();
+    student_store::save(&pool, &student).await.unwrap();
+    let locker = Locker::new(
+      "X-4".to_string(),
+      "X".to_string(),
+      1,
+      LockerSize::Standard,
+      false,
+    )
+    .unwrap();
+    store::save_locker(&pool, &locker).await.unwrap();
+    let app = router(pool.clone().into());
+
+    let response = app
+      .clone()
+      .oneshot(
+        Request::put("/students/940007/assignment")
+          .header(header::CONTENT_TYPE, "application/json")
+          .body(Body::from(r#"{"locker_number": "X-4"}"#))
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+    assert_eq!(response.status(), 200);
+
+    let response = app
+      .oneshot(
+        Request::get("/students/940007/assignment")
+          .body(Body::empty())
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+    assert_eq!(response.status(), 200);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["locker"]["number"], "X-4");
+    assert_eq!(body["claimed"], true);
+
+    let assignment = store::get_assignment(&pool, &student.id)
+      .await
+      .unwrap()
+      .unwrap();
+    store::release_assignment(&pool, &assignment).await.unwrap();
+    student_store::delete(&pool, &student.id).await.unwrap();
+    pool.del(&store::locker_key("X-4")).await.unwrap();
+  }
+
+  #[cfg(feature = "redis-tests")]
+  fn swap(student_a: &str, student_b: &str) -> Request<Body> {
+    Request::post("/assignments/swap")
+      .header(header::CONTENT_TYPE, "application/json")
+      .body(Body::from(
+        serde_json::json!({ "student_a": student_a, "student_b": student_b }).to_string(),
+      ))
+      .unwrap()
+  }
+
+  #[cfg(feature = "redis-tests")]
+  fn swap_locker(number: &str) -> Locker {
+    Locker::new(
+      number.to_string(),
+      "S".to_string(),
+      1,
+      LockerSize::Standard,
+      false,
+    )
+    .unwrap()
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_swap_exchanges_lockers() {
+    let pool = setup().await;
+    let student_a = StudentId::new("940008".to_string()).unwrap();
+    let student_b = StudentId::new("940009".to_string()).unwrap();
+    let before = store::claim_locker(&pool, &student_a, &swap_locker("S-1"))
+      .await
+      .unwrap();
+    store::claim_locker(&pool, &student_b, &swap_locker("S-2"))
+      .await
+      .unwrap();
+
+    let response = router(pool.clone().into())
+      .oneshot(swap("940008", "940009"))
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), 200);
+    let after_a = store::get_assignment(&pool, &student_a)
+      .await
+      .unwrap()
+      .unwrap();
+    let after_b = store::get_assignment(&pool, &student_b)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!(after_a.locker.number.as_str(), "S-2");
+    assert_eq!(after_b.locker.number.as_str(), "S-1");
+    assert!(after_a.assigned_at > before.assigned_at);
+    let holder: String = pool.get(&store::holder_key("S-2")).await.unwrap();
+    assert_eq!(holder, "940008");
+
+    store::release_assignment(&pool, &after_a).await.unwrap();
+    store::release_assignment(&pool, &after_b).await.unwrap();
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_swap_with_unassigned_student_conflicts() {
+    let pool = setup().await;
+    let assigned = StudentId::new("940010".to_string()).unwrap();
+    let before = store::claim_locker(&pool, &assigned, &swap_locker("S-3"))
+      .await
+      .unwrap();
+
+    let response = router(pool.clone().into())
+      .oneshot(swap("940010", "940011"))
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), 409);
+    let unchanged = store::get_assignment(&pool, &assigned)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!(unchanged.assigned_at, before.assigned_at);
+    store::release_assignment(&pool, &unchanged).await.unwrap();
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_release_without_assignment_is_not_found() {
+    let pool = setup().await;
+
+    let response = router(pool.into())
+      .oneshot(release("940003"))
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), 404);
+  }
+}
@@ -0,0 +1,185 @@
+//! API-key authentication for mutating routes.
+//!
+//! Clients send `Authorization: Bearer <key>`, checked against the comma-separated
+//! `API_KEYS` env var. Safe methods (GET, HEAD, OPTIONS) pass through unauthenticated
+//! so read-only endpoints stay open; requests with a valid key are marked with the
+//! `Authenticated` extension so those endpoints can show more to them.
+
+use crate::http::Error;
+use axum::{
+  extract::{Request, State},
+  http::{header, Method},
+  middleware::Next,
+  response::Response,
+};
+use log::{debug, warn};
+use std::{env, sync::Arc};
+use subtle::ConstantTimeEq;
+
+/// Request extension present when the request carried a valid API key
+#[derive(Debug, Clone, Copy)]
+pub struct Authenticated;
+
+/// The set of API keys allowed to call mutating routes
+#[derive(Debug, Clone, Default)]
+pub struct ApiKeys(Arc<Vec<String>>);
+
+impl ApiKeys {
+  /// Load keys from the comma-separated `API_KEYS` env var
+  ///
+  /// Without any keys, every mutating request is rejected.
+  pub fn from_env() -> Self {
+    let keys = Self::parse(&env::var("API_KEYS").unwrap_or_default());
+    if keys.0.is_empty() {
+      warn!("API_KEYS not set, mutating requests will be rejected");
+    } else {
+      debug!("Loaded {} API keys", keys.0.len());
+    }
+    keys
+  }
+
+  fn parse(keys: &str) -> Self {
+    ApiKeys(Arc::new(
+      keys
+        .split(',')
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(str::to_string)
+        .collect(),
+    ))
+  }
+
+  /// Returns true if `candidate` matches one of the keys
+  ///
+  /// Every key is compared in constant time, without stopping at the first match, so
+  /// response timing doesn't reveal how much of a key was guessed.
+  pub fn contains(&self, candidate: &str) -> bool {
+    self.0.iter().fold(false, |found, key| {
+      found | bool::from(key.as_bytes().ct_eq(candidate.as_bytes()))
+    })
+  }
+}
+
+/// Middleware rejecting non-GET/HEAD/OPTIONS requests without a valid API key
+pub async fn require_api_key(
+  State(keys): State<ApiKeys>,
+  mut request: Request,
+  next: Next,
+) -> Result<Response, Error> {
+  let key = request
+    .headers()
+    .get(header::AUTHORIZATION)
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| value.strip_prefix("Bearer "))
+    .map(str::trim);
+  let authenticated = key.is_some_and(|key| keys.contains(key));
+  let has_key = key.is_some();
+
+  if authenticated {
+    request.extensions_mut().insert(Authenticated);
+  }
+  if matches!(
+    *request.method(),
+    Method::GET | Method::HEAD | Method::OPTIONS
+  ) {
+    return Ok(next.run(request).await);
+  }
+
+  match (authenticated, has_key) {
+    (true, _) => Ok(next.run(request).await),
+    (false, true) => {
+      debug!(
+        "Rejected {} {} with an unknown API key",
+        request.method(),
+        request.uri().path()
+      );
+      Err(Error::Unauthorized)
+    }
+    (false, false) => Err(Error::Unauthorized),
+  }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+  use axum::{body::Body, middleware, routing::get, Extension, Router};
+  use http_body_util::BodyExt;
+  use tower::ServiceExt;
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  fn app() -> Router {
+    Router::new()
+      .route(
+        "/students",
+        get(|auth: Option<Extension<Authenticated>>| async move { auth.is_some().to_string() })
+          .post(|| async {}),
+      )
+      .route_layer(middleware::from_fn_with_state(
+        ApiKeys::parse("first, second"),
+        require_api_key,
+      ))
+  }
+
+  fn post(authorization: Option<&str>) -> Request {
+    let mut request = Request::post("/students");
+    if let Some(authorization) = authorization {
+      request = request.header(header::AUTHORIZATION, authorization);
+    }
+    request.body(Body::empty()).unwrap()
+  }
+
+  #[tokio::test]
+  async fn test_valid_key_is_accepted() {
+    setup();
+    for key in ["Bearer first", "Bearer second"] {
+      let response = app().oneshot(post(Some(key))).await.unwrap();
+      assert_eq!(response.status(), 200);
+    }
+  }
+
+  #[tokio::test]
+  async fn test_missing_header_is_unauthorized() {
+    setup();
+    let response = app().oneshot(post(None)).await.unwrap();
+
+    assert_eq!(response.status(), 401);
+    assert_eq!(response.headers()[header::WWW_AUTHENTICATE], "Bearer");
+  }
+
+  #[tokio::test]
+  async fn test_wrong_key_is_unauthorized() {
+    setup();
+    for authorization in ["Bearer third", "Bearer firs", "Basic first", "first"] {
+      let response = app().oneshot(post(Some(authorization))).await.unwrap();
+      assert_eq!(response.status(), 401, "{}", authorization);
+    }
+  }
+
+  #[tokio::test]
+  async fn test_reads_do_not_need_a_key() {
+    setup();
+    for (authorization, authenticated) in [
+      (None, "false"),
+      (Some("Bearer wrong"), "false"),
+      (Some("Bearer first"), "true"),
+    ] {
+      let mut request = Request::get("/students");
+      if let Some(authorization) = authorization {
+        request = request.header(header::AUTHORIZATION, authorization);
+      }
+      let response = app()
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+      assert_eq!(response.status(), 200);
+      let body = response.into_body().collect().await.unwrap().to_bytes();
+      assert_eq!(body, authenticated);
+    }
+  }
+}
@@ -0,0 +1,99 @@
+use anyhow::Context;
+use log::{debug, warn};
+use std::env;
+use std::net::{IpAddr, SocketAddr};
+
+/// HTTP server configuration
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+  /// IP address to bind to (default: `0.0.0.0`)
+  pub host: String,
+  /// TCP port to listen on (default: `3000`)
+  pub port: u16,
+}
+
+impl Default for ServerConfig {
+  fn default() -> Self {
+    // Get bind address from environment or use defaults
+    let host = env::var("HOST")
+      .ok()
+      .filter(|s| !s.is_empty())
+      .unwrap_or_else(|| "0.0.0.0".to_string());
+
+    let port = match env::var("PORT") {
+      Ok(port) => port.parse().unwrap_or_else(|_| {
+        warn!("Ignoring invalid PORT {:?}, using 3000", port);
+        3000
+      }),
+      Err(_) => 3000,
+    };
+
+    debug!("Server configured for {}:{}", host, port);
+    Self { host, port }
+  }
+}
+
+impl ServerConfig {
+  /// The socket address to bind, failing if `host` isn't an IP address
+  pub fn socket_addr(&self) -> anyhow::Result<SocketAddr> {
+    let ip: IpAddr = self
+      .host
+      .parse()
+      .with_context(|| format!("HOST must be an IP address, got {:?}", self.host))?;
+    Ok(SocketAddr::new(ip, self.port))
+  }
+}
+
+/// Read a positive integer from env var `name`, warning and using `default` if invalid
+pub(crate) fn positive_env_var(name: &str, default: u64) -> u64 {
+  match env::var(name) {
+    Ok(value) => match value.parse::<u64>() {
+      Ok(value) if value > 0 => value,
+      _ => {
+        warn!("Ignoring invalid {} {:?}, using {}", name, value, default);
+        default
+      }
+    },
+    Err(_) => default,
+  }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  #[test]
+  fn test_socket_addr() {
+    setup();
+    let config = ServerConfig {
+      host: "127.0.0.1".to_string(),
+      port: 8080,
+    };
+    assert_eq!(
+      config.socket_addr().unwrap(),
+      "127.0.0.1:8080".parse::<SocketAddr>().unwrap()
+    );
+
+    let config = ServerConfig {
+      host: "::1".to_string(),
+      port: 3001,
+    };
+    assert_eq!(config.socket_addr().unwrap().to_string(), "[::1]:3001");
+  }
+
+  #[test]
+  fn test_socket_addr_rejects_hostnames() {
+    setup();
+    let config = ServerConfig {
+      host: "localhost".to_string(),
+      port: 3000,
+    };
+    assert!(config.socket_addr().is_err());
+  }
+}
@@ -0,0 +1,133 @@
+use axum::http::{header, HeaderValue, Method};
+use log::{debug, warn};
+use std::env;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+/// Build the CORS layer from the comma-separated `ALLOWED_ORIGINS` env var
+///
+/// Without `ALLOWED_ORIGINS`, debug builds allow any origin so the frontend dev server
+/// works out of the box, while release builds allow none.
+pub fn cors_layer() -> CorsLayer {
+  layer_for(env::var("ALLOWED_ORIGINS").ok().filter(|s| !s.is_empty()))
+}
+
+fn layer_for(allowed_origins: Option<String>) -> CorsLayer {
+  let origins = match allowed_origins {
+    Some(origins) => origins,
+    None if cfg!(debug_assertions) => {
+      debug!("ALLOWED_ORIGINS not set, allowing any origin in a debug build");
+      return CorsLayer::permissive();
+    }
+    None => {
+      warn!("ALLOWED_ORIGINS not set, cross-origin requests will be rejected");
+      String::new()
+    }
+  };
+
+  let origins: Vec<HeaderValue> = origins
+    .split(',')
+    .map(str::trim)
+    .filter(|origin| !origin.is_empty())
+    .filter_map(|origin| match HeaderValue::from_str(origin) {
+      Ok(value) => Some(value),
+      Err(_) => {
+        warn!("Ignoring invalid origin in ALLOWED_ORIGINS: {:?}", origin);
+        None
+      }
+    })
+    .collect();
+  debug!("Allowing cross-origin requests from {:?}", origins);
+
+  CorsLayer::new()
+    .allow_origin(AllowOrigin::list(origins))
+    .allow_methods([
+      Method::GET,
+      Method::POST,
+      Method::PATCH,
+      Method::DELETE,
+      Method::OPTIONS,
+    ])
+    .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE])
+    .expose_headers([header::RETRY_AFTER])
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+  use axum::{body::Body, http::Request, routing::get, Router};
+  use tower::ServiceExt;
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  fn app() -> Router {
+    Router::new()
+      .route("/status", get(|| async { "ok" }))
+      .layer(layer_for(Some(
+        "https://lockers.csxlabs.edu, https://admin.csxlabs.edu".to_string(),
+      )))
+  }
+
+  #[tokio::test]
+  async fn test_allowed_origin_gets_cors_header() {
+    setup();
+    let response = app()
+      .oneshot(
+        Request::get("/status")
+          .header(header::ORIGIN, "https://admin.csxlabs.edu")
+          .body(Body::empty())
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+
+    assert_eq!(
+      response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+      "https://admin.csxlabs.edu"
+    );
+  }
+
+  #[tokio::test]
+  async fn test_unlisted_origin_gets_no_cors_header() {
+    setup();
+    let response = app()
+      .oneshot(
+        Request::get("/status")
+          .header(header::ORIGIN, "https://evil.example.com")
+          .body(Body::empty())
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+
+    assert!(!response
+      .headers()
+      .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+  }
+
+  #[tokio::test]
+  async fn test_preflight_succeeds() {
+    setup();
+    let response = app()
+      .oneshot(
+        Request::builder()
+          .method(Method::OPTIONS)
+          .uri("/status")
+          .header(header::ORIGIN, "https://lockers.csxlabs.edu")
+          .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+          .body(Body::empty())
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+
+    assert!(response.status().is_success());
+    assert_eq!(
+      response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+      "https://lockers.csxlabs.edu"
+    );
+  }
+}
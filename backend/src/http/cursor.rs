@@ -0,0 +1,157 @@
+//! Opaque pagination cursors.
+//!
+//! List endpoints hand out `next_cursor` strings that clients pass back unchanged. A
+//! cursor is URL-safe base64 of a small JSON `Cursor`, so the paging scheme behind it
+//! can change without breaking clients. Each cursor records what kind of position it
+//! holds, so one issued by a listing can't be replayed against another that reads its
+//! position differently. Cursors aren't signed: decoding only checks that one is well
+//! formed and was issued for the same kind of listing and filter, and anything else is
+//! rejected with `400 Bad Request`.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{Deserialize, Serialize};
+
+use crate::http::Error;
+use crate::student::Grade;
+
+/// What a cursor's `position` counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CursorKind {
+  /// The last student id returned, for `/students`
+  AfterStudent,
+  /// The number of rows already sent, for `/assignments/export`
+  RowOffset,
+}
+
+/// Where a paged listing left off
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Cursor {
+  /// How to read `position`
+  pub kind: CursorKind,
+  /// Position to resume from
+  pub position: u64,
+  /// The grade filter the cursor was issued for
+  pub filter: Option<Grade>,
+}
+
+impl Cursor {
+  pub fn new(kind: CursorKind, position: u64, filter: Option<Grade>) -> Self {
+    Cursor {
+      kind,
+      position,
+      filter,
+    }
+  }
+
+  /// The opaque string handed to clients
+  pub fn encode(&self) -> String {
+    let json = serde_json::to_vec(self).expect("a cursor always serializes");
+    URL_SAFE_NO_PAD.encode(json)
+  }
+
+  /// Decode a cursor from a client, which must be of `kind` and issued for `filter`
+  ///
+  /// # Errors
+  /// Returns `Error::BadRequest` if the cursor isn't one we issued, or was issued for a
+  /// different kind of listing or filter.
+  pub fn decode(cursor: &str, kind: CursorKind, filter: Option<Grade>) -> Result<Self, Error> {
+    let invalid = || Error::BadRequest("invalid cursor".to_string());
+    let json = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+    let cursor: Cursor = serde_json::from_slice(&json).map_err(|_| invalid())?;
+
+    if cursor
+      .filter
+      .is_some_and(|grade| !(9..=12).contains(&grade))
+    {
+      return Err(invalid());
+    }
+    if cursor.kind != kind {
+      return Err(Error::BadRequest(
+        "cursor was issued for a different listing".to_string(),
+      ));
+    }
+    if cursor.filter != filter {
+      return Err(Error::BadRequest(
+        "cursor was issued for a different filter".to_string(),
+      ));
+    }
+    Ok(cursor)
+  }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  #[test]
+  fn test_cursor_round_trip() {
+    setup();
+    for cursor in [
+      Cursor::new(CursorKind::RowOffset, 0, None),
+      Cursor::new(CursorKind::AfterStudent, 123456, Some(10)),
+      Cursor::new(CursorKind::AfterStudent, u64::MAX, Some(12)),
+    ] {
+      let encoded = cursor.encode();
+      assert!(encoded
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+      assert_eq!(
+        Cursor::decode(&encoded, cursor.kind, cursor.filter).unwrap(),
+        cursor
+      );
+    }
+  }
+
+  #[test]
+  fn test_invalid_cursors_are_bad_requests() {
+    setup();
+    let tampered = URL_SAFE_NO_PAD.encode(r#"{"kind":"row_offset","position":5,"filter":13}"#);
+    let extra_field =
+      URL_SAFE_NO_PAD.encode(r#"{"kind":"row_offset","position":5,"filter":null,"x":1}"#);
+    let no_kind = URL_SAFE_NO_PAD.encode(r#"{"position":5,"filter":null}"#);
+    for cursor in [
+      "",
+      "abc",
+      "not base64!",
+      tampered.as_str(),
+      extra_field.as_str(),
+      no_kind.as_str(),
+    ] {
+      assert!(
+        matches!(
+          Cursor::decode(cursor, CursorKind::RowOffset, None),
+          Err(Error::BadRequest(_))
+        ),
+        "{}",
+        cursor
+      );
+    }
+
+    // A cursor for grade 9 can't be used to page grade 10
+    let grade_9 = Cursor::new(CursorKind::AfterStudent, 5, Some(9)).encode();
+    assert!(matches!(
+      Cursor::decode(&grade_9, CursorKind::AfterStudent, Some(10)),
+      Err(Error::BadRequest(_))
+    ));
+
+    // An export row offset can't be used as a student id, or the other way round
+    let offset = Cursor::new(CursorKind::RowOffset, 5, None).encode();
+    assert!(matches!(
+      Cursor::decode(&offset, CursorKind::AfterStudent, None),
+      Err(Error::BadRequest(_))
+    ));
+    let after = Cursor::new(CursorKind::AfterStudent, 900001, None).encode();
+    assert!(matches!(
+      Cursor::decode(&after, CursorKind::RowOffset, None),
+      Err(Error::BadRequest(_))
+    ));
+  }
+}
@@ -1,16 +1,23 @@
 // TODO: finish error implementation
 
 use axum::{
-  http::StatusCode,
+  http::{header, HeaderValue, StatusCode},
   response::{IntoResponse, Json},
 };
-use log::{debug, error};
-use serde_json::json;
+use log::{debug, error, warn};
+use serde::Serialize;
 use std::borrow::Cow;
 use std::collections::HashMap;
 
+use crate::http::request_id;
+use utoipa::ToSchema;
+
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
+  /// Return `400 Bad Request` for malformed JSON or query strings
+  #[error("bad request: {0}")]
+  BadRequest(String),
+
   /// Return `401 Unauthorized`
   #[error("authentication required")]
   Unauthorized,
@@ -23,16 +30,44 @@ pub enum Error {
   #[error("request path not found")]
   NotFound,
 
+  /// Return `406 Not Acceptable` when no representation matches the `Accept` header
+  #[error("not acceptable: {0}")]
+  NotAcceptable(String),
+
+  /// Return `408 Request Timeout` when handling a request takes too long
+  #[error("request took too long")]
+  RequestTimeout,
+
+  /// Return `409 Conflict`
+  #[error("conflict: {0}")]
+  Conflict(String),
+
+  /// Return `413 Payload Too Large`
+  #[error("request body is too large")]
+  PayloadTooLarge,
+
   /// Return `422 Unprocessable Entity`
   #[error("error in the request body")]
   UnprocessableEntity {
     errors: HashMap<Cow<'static, str>, Vec<Cow<'static, str>>>,
   },
 
+  /// Return `429 Too Many Requests` with a `Retry-After` header
+  #[error("too many requests, retry after {retry_after_secs} seconds")]
+  TooManyRequests { retry_after_secs: u64 },
+
+  /// Return `503 Service Unavailable` when a dependency like Redis is down
+  #[error("service unavailable: {0}")]
+  ServiceUnavailable(String),
+
   /// Return `500 Internal Server Error` on Redis connection error
   #[error("failed to connect to Redis: {0}")]
   RedisConnection(String),
 
+  /// Return `500 Internal Server Error` when Redis rejects our credentials
+  #[error("Redis authentication failed: {0}")]
+  RedisAuth(String),
+
   /// Return `500 Internal Server Error` on Redis command error
   #[error("Redis command failed: {0}")]
   RedisCommand(String),
@@ -50,6 +85,34 @@ pub enum Error {
   Anyhow(#[from] anyhow::Error),
 }
 
+/// JSON body of every error response
+#[derive(Debug, Serialize, ToSchema)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ErrorBody {
+  pub error: ErrorDetail,
+}
+
+/// What went wrong, as sent under `error` in an error response
+#[derive(Debug, Serialize, ToSchema)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ErrorDetail {
+  /// Stable machine-readable code, see `Error::code`
+  #[schema(value_type = String, example = "validation_failed")]
+  pub code: &'static str,
+  /// Human-readable description
+  pub message: String,
+  /// Problems with each invalid field, for `validation_failed`
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[schema(value_type = Option<HashMap<String, Vec<String>>>)]
+  pub fields: Option<HashMap<Cow<'static, str>, Vec<Cow<'static, str>>>>,
+  /// Seconds to wait before retrying, for `rate_limited`
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub retry_after_secs: Option<u64>,
+  /// The request's `X-Request-Id`
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub request_id: Option<String>,
+}
+
 impl Error {
   /// Convenient constructor for `Error::UnprocessableEntity`.
   ///
@@ -77,6 +140,7 @@ impl Error {
   pub fn from_redis_error(err: redis::RedisError) -> Self {
     match err.kind() {
       redis::ErrorKind::IoError => Self::RedisConnection(err.to_string()),
+      _ if Self::is_redis_auth_error(&err) => Self::RedisAuth(err.to_string()),
       redis::ErrorKind::ResponseError => {
         if err.to_string().contains("nil") {
           Self::RedisKeyNotFound(err.to_string())
@@ -91,13 +155,56 @@ impl Error {
     }
   }
 
+  /// Returns true if Redis refused the connection's credentials
+  ///
+  /// Covers a failed `AUTH` during connect as well as `NOAUTH`/`WRONGPASS` replies,
+  /// which would otherwise look like an ordinary failed command.
+  fn is_redis_auth_error(err: &redis::RedisError) -> bool {
+    err.kind() == redis::ErrorKind::AuthenticationFailed
+      || matches!(err.code(), Some("NOAUTH" | "WRONGPASS"))
+      || ["NOAUTH", "WRONGPASS"]
+        .iter()
+        .any(|code| err.to_string().contains(code))
+  }
+
+  /// Stable machine-readable code for the error, sent as `error.code`
+  pub fn code(&self) -> &'static str {
+    match self {
+      Self::BadRequest(_) => "bad_request",
+      Self::Unauthorized => "unauthorized",
+      Self::Forbidden => "forbidden",
+      Self::NotFound => "not_found",
+      Self::NotAcceptable(_) => "not_acceptable",
+      Self::RequestTimeout => "request_timeout",
+      Self::Conflict(_) => "conflict",
+      Self::PayloadTooLarge => "payload_too_large",
+      Self::UnprocessableEntity { .. } => "validation_failed",
+      Self::TooManyRequests { .. } => "rate_limited",
+      Self::ServiceUnavailable(_) => "service_unavailable",
+      Self::RedisConnection(_) => "redis_unavailable",
+      Self::RedisAuth(_) => "redis_auth_failed",
+      Self::RedisCommand(_) => "redis_command_failed",
+      Self::RedisKeyNotFound(_) => "key_not_found",
+      Self::RedisParseError(_) => "redis_parse_failed",
+      Self::Anyhow(_) => "internal_error",
+    }
+  }
+
   fn status_code(&self) -> StatusCode {
     match self {
+      Self::BadRequest(_) => StatusCode::BAD_REQUEST,
       Self::Unauthorized => StatusCode::UNAUTHORIZED,
       Self::Forbidden => StatusCode::FORBIDDEN,
       Self::NotFound | Self::RedisKeyNotFound(_) => StatusCode::NOT_FOUND,
+      Self::NotAcceptable(_) => StatusCode::NOT_ACCEPTABLE,
+      Self::RequestTimeout => StatusCode::REQUEST_TIMEOUT,
+      Self::Conflict(_) => StatusCode::CONFLICT,
+      Self::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
       Self::UnprocessableEntity { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+      Self::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
+      Self::ServiceUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
       Self::RedisConnection(_)
+      | Self::RedisAuth(_)
       | Self::RedisCommand(_)
       | Self::RedisParseError(_)
       | Self::Anyhow(_) => StatusCode::INTERNAL_SERVER_ERROR,
@@ -108,29 +215,64 @@ impl Error {
 impl IntoResponse for Error {
   fn into_response(self) -> axum::response::Response {
     let status = self.status_code();
+    let request_id = request_id::current();
+    let rid = request_id
+      .as_ref()
+      .map(|id| format!(" [request_id={}]", id))
+      .unwrap_or_default();
 
     match &self {
-      Error::Unauthorized => debug!("Unauthorized request: {}", self),
-      Error::Forbidden => debug!("Forbidden request: {}", self),
-      Error::NotFound => debug!("Not found: {}", self),
-      Error::UnprocessableEntity { errors } => debug!("Validation errors: {:?}", errors),
-      Error::RedisConnection(err) => error!("Redis connection error: {}", err),
-      Error::RedisCommand(err) => error!("Redis command error: {}", err),
-      Error::RedisKeyNotFound(key) => debug!("Redis key not found: {}", key),
-      Error::RedisParseError(err) => error!("Redis parse error: {}", err),
-      Error::Anyhow(e) => error!("Internal server error: {}", e),
+      Error::BadRequest(reason) => debug!("Bad request: {}{}", reason, rid),
+      Error::Unauthorized => debug!("Unauthorized request: {}{}", self, rid),
+      Error::Forbidden => debug!("Forbidden request: {}{}", self, rid),
+      Error::NotFound => debug!("Not found: {}{}", self, rid),
+      Error::NotAcceptable(reason) => debug!("Not acceptable: {}{}", reason, rid),
+      Error::RequestTimeout => warn!("Request timed out{}", rid),
+      Error::Conflict(reason) => debug!("Conflict: {}{}", reason, rid),
+      Error::PayloadTooLarge => debug!("Request body too large{}", rid),
+      Error::UnprocessableEntity { errors } => debug!("Validation errors: {:?}{}", errors, rid),
+      Error::TooManyRequests { .. } => debug!("Rate limited: {}{}", self, rid),
+      Error::ServiceUnavailable(reason) => warn!("Service unavailable: {}{}", reason, rid),
+      Error::RedisConnection(err) => error!("Redis connection error: {}{}", err, rid),
+      Error::RedisAuth(err) => error!("Redis authentication error: {}{}", err, rid),
+      Error::RedisCommand(err) => error!("Redis command error: {}{}", err, rid),
+      Error::RedisKeyNotFound(key) => debug!("Redis key not found: {}{}", key, rid),
+      Error::RedisParseError(err) => error!("Redis parse error: {}{}", err, rid),
+      Error::Anyhow(e) => error!("Internal server error: {}{}", e, rid),
     }
 
-    let body = match self {
-      Error::UnprocessableEntity { errors } => Json(json!({
-          "errors": errors
-      })),
-      _ => Json(json!({
-          "error": self.to_string()
-      })),
+    let retry_after = match &self {
+      Error::TooManyRequests { retry_after_secs } => Some(*retry_after_secs),
+      _ => None,
+    };
+    let code = self.code();
+    let message = self.to_string();
+    let fields = match self {
+      Error::UnprocessableEntity { errors } => Some(errors),
+      _ => None,
+    };
+    let body = ErrorBody {
+      error: ErrorDetail {
+        code,
+        message,
+        fields,
+        retry_after_secs: retry_after,
+        request_id,
+      },
     };
 
-    (status, body).into_response()
+    let mut response = (status, Json(body)).into_response();
+    if status == StatusCode::UNAUTHORIZED {
+      response
+        .headers_mut()
+        .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+    }
+    if let Some(secs) = retry_after {
+      response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(secs));
+    }
+    response
   }
 }
 
@@ -139,3 +281,130 @@ impl From<redis::RedisError> for Error {
     Self::from_redis_error(err)
   }
 }
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+  use http_body_util::BodyExt;
+  use serde_json::{json, Value};
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  async fn body_json(response: axum::response::Response) -> Value {
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&body).unwrap()
+  }
+
+  #[tokio::test]
+  async fn test_conflict_response() {
+    setup();
+    let response = Error::Conflict("locker A-102 is already assigned".to_string()).into_response();
+
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    assert_eq!(
+      body_json(response).await,
+      json!({
+        "error": {
+          "code": "conflict",
+          "message": "conflict: locker A-102 is already assigned"
+        }
+      })
+    );
+  }
+
+  #[tokio::test]
+  async fn test_unauthorized_sets_www_authenticate() {
+    setup();
+    let response = Error::Unauthorized.into_response();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(response.headers()[header::WWW_AUTHENTICATE], "Bearer");
+  }
+
+  #[tokio::test]
+  async fn test_too_many_requests_sets_retry_after() {
+    setup();
+    let response = Error::TooManyRequests {
+      retry_after_secs: 42,
+    }
+    .into_response();
+
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()[header::RETRY_AFTER], "42");
+    assert_eq!(
+      body_json(response).await,
+      json!({
+        "error": {
+          "code": "rate_limited",
+          "message": "too many requests, retry after 42 seconds",
+          "retry_after_secs": 42
+        }
+      })
+    );
+  }
+
+  #[tokio::test]
+  async fn test_validation_envelope() {
+    setup();
+    let response =
+      Error::unprocessable_entity([("grade", "must be between 9 and 12")]).into_response();
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+      body_json(response).await,
+      json!({
+        "error": {
+          "code": "validation_failed",
+          "message": "error in the request body",
+          "fields": { "grade": ["must be between 9 and 12"] }
+        }
+      })
+    );
+  }
+
+  #[tokio::test]
+  async fn test_internal_error_envelope() {
+    setup();
+    let response = Error::from(anyhow::anyhow!("secret detail")).into_response();
+
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(
+      body_json(response).await,
+      json!({
+        "error": {
+          "code": "internal_error",
+          "message": "an internal server error occurred"
+        }
+      })
+    );
+  }
+
+  #[test]
+  fn test_redis_auth_errors_are_mapped() {
+    setup();
+    let noauth = redis::make_extension_error(
+      "NOAUTH".to_string(),
+      Some("Authentication required.".to_string()),
+    );
+    let rejected_auth = redis::RedisError::from((
+      redis::ErrorKind::AuthenticationFailed,
+      "Password authentication failed",
+    ));
+    for err in [noauth, rejected_auth] {
+      let err = Error::from_redis_error(err);
+      assert!(matches!(err, Error::RedisAuth(_)), "{:?}", err);
+      assert_eq!(err.code(), "redis_auth_failed");
+      assert!(err.to_string().starts_with("Redis authentication failed"));
+    }
+
+    let other = redis::make_extension_error("WRONGTYPE".to_string(), None);
+    assert!(!matches!(
+      Error::from_redis_error(other),
+      Error::RedisAuth(_)
+    ));
+  }
+}
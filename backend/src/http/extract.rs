@@ -0,0 +1,216 @@
+//! Drop-in replacements for axum's `Json` and `Query` extractors.
+//!
+//! axum rejects malformed input with a plaintext body; these wrappers turn the rejection
+//! into our `Error` so clients always get a JSON error body. `ValidatedQuery` goes a
+//! step further and reports a bad parameter as a `422` naming it.
+
+use axum::{
+  extract::{rejection::JsonRejection, rejection::QueryRejection, FromRequest, FromRequestParts},
+  http::{request::Parts, StatusCode},
+  response::{IntoResponse, Response},
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::http::Error;
+
+/// JSON request body extractor and response, rejecting with `Error::BadRequest`
+#[derive(Debug, FromRequest)]
+#[from_request(via(axum::Json), rejection(Error))]
+pub struct Json<T>(pub T);
+
+impl<T: Serialize> IntoResponse for Json<T> {
+  fn into_response(self) -> Response {
+    axum::Json(self.0).into_response()
+  }
+}
+
+/// Query string extractor, rejecting with `Error::BadRequest`
+#[derive(Debug, FromRequestParts)]
+#[from_request(via(axum::extract::Query), rejection(Error))]
+pub struct Query<T>(pub T);
+
+/// Query string extractor, rejecting with `Error::UnprocessableEntity` keyed by the
+/// offending parameter
+#[derive(Debug)]
+pub struct ValidatedQuery<T>(pub T);
+
+impl<T, S> FromRequestParts<S> for ValidatedQuery<T>
+where
+  T: DeserializeOwned,
+  S: Send + Sync,
+{
+  type Rejection = Error;
+
+  async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Error> {
+    let query = parts.uri.query().unwrap_or_default();
+    let deserializer =
+      serde_urlencoded::Deserializer::new(form_urlencoded::parse(query.as_bytes()));
+    serde_path_to_error::deserialize(deserializer)
+      .map(ValidatedQuery)
+      .map_err(|e| {
+        let message = e.inner().to_string();
+        let param = match e.path().to_string() {
+          // Errors about the query as a whole, such as a missing parameter, have no path
+          path if path == "." => missing_field(&message).unwrap_or("query").to_string(),
+          path => path,
+        };
+        Error::unprocessable_entity([(param, message)])
+      })
+  }
+}
+
+/// The field named in serde's "missing field `name`" message
+fn missing_field(message: &str) -> Option<&str> {
+  message.strip_prefix("missing field `")?.split('`').next()
+}
+
+impl From<JsonRejection> for Error {
+  fn from(rejection: JsonRejection) -> Self {
+    // A body cut off by the size limit isn't malformed, just too large
+    if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE {
+      return Self::PayloadTooLarge;
+    }
+    Self::BadRequest(rejection.body_text())
+  }
+}
+
+impl From<QueryRejection> for Error {
+  fn from(rejection: QueryRejection) -> Self {
+    Self::BadRequest(rejection.body_text())
+  }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+  use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::{get, post},
+    Router,
+  };
+  use http_body_util::BodyExt;
+  use serde::Deserialize;
+  use serde_json::{json, Value};
+  use tower::ServiceExt;
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  #[derive(Debug, Deserialize, Serialize)]
+  struct Payload {
+    count: u32,
+  }
+
+  fn app() -> Router {
+    Router::new()
+      .route("/echo", post(|Json(p): Json<Payload>| async { Json(p) }))
+      .route("/query", get(|Query(p): Query<Payload>| async { Json(p) }))
+      .route(
+        "/validated",
+        get(|ValidatedQuery(p): ValidatedQuery<Payload>| async { Json(p) }),
+      )
+  }
+
+  async fn body_json(response: Response) -> Value {
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&body).unwrap()
+  }
+
+  #[tokio::test]
+  async fn test_invalid_json_is_400_with_json_body() {
+    setup();
+    let response = app()
+      .oneshot(
+        Request::post("/echo")
+          .header("content-type", "application/json")
+          .body(Body::from("{\"count\": "))
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = body_json(response).await;
+    assert_eq!(body["error"]["code"], "bad_request");
+    assert!(body["error"]["message"]
+      .as_str()
+      .unwrap()
+      .starts_with("bad request: "));
+  }
+
+  #[tokio::test]
+  async fn test_invalid_query_is_400_with_json_body() {
+    setup();
+    let response = app()
+      .oneshot(
+        Request::get("/query?count=abc")
+          .body(Body::empty())
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(body_json(response).await["error"]["code"], "bad_request");
+  }
+
+  #[tokio::test]
+  async fn test_validated_query_names_bad_param() {
+    setup();
+    for (uri, message) in [
+      ("/validated?count=abc", "invalid digit found in string"),
+      ("/validated", "missing field `count`"),
+    ] {
+      let response = app()
+        .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+      assert_eq!(
+        response.status(),
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "{}",
+        uri
+      );
+      let body = body_json(response).await;
+      assert_eq!(body["error"]["code"], "validation_failed");
+      assert_eq!(
+        body["error"]["fields"]["count"],
+        json!([message]),
+        "{}",
+        uri
+      );
+    }
+
+    let response = app()
+      .oneshot(
+        Request::get("/validated?count=7")
+          .body(Body::empty())
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+    assert_eq!(body_json(response).await, json!({ "count": 7 }));
+  }
+
+  #[tokio::test]
+  async fn test_valid_json_round_trips() {
+    setup();
+    let response = app()
+      .oneshot(
+        Request::post("/echo")
+          .header("content-type", "application/json")
+          .body(Body::from("{\"count\": 3}"))
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body_json(response).await, json!({ "count": 3 }));
+  }
+}
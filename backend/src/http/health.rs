@@ -0,0 +1,255 @@
+//! Liveness and readiness checks for load balancers and orchestrators.
+//!
+//! `/health_check` and `/livez` only report that the process is up and serving requests:
+//! they never touch Redis and have no side effects, so they are safe to poll frequently.
+//! `/readyz` additionally requires Redis to answer a `PING`, so a pod can stay alive
+//! through a Redis outage without being sent traffic. Use `/status` and `/redis/status`
+//! for diagnostics.
+//!
+//! `spawn_health_monitor` also pings Redis in the background, so an outage shows up in
+//! the state's health flag within one interval even when no request touches Redis.
+
+use axum::{extract::State, http::StatusCode, routing::get, Router};
+use log::{debug, info, warn};
+use serde_json::{json, Value};
+use std::env;
+use std::time::Duration;
+
+use crate::http::{metrics, AppState, Json};
+
+/// Seconds between background health checks when `REDIS_HEALTH_INTERVAL` is unset
+const DEFAULT_HEALTH_INTERVAL_SECS: u64 = 10;
+
+/// Time between background health checks, from `REDIS_HEALTH_INTERVAL` in seconds
+pub fn health_interval() -> Duration {
+  let secs = env::var("REDIS_HEALTH_INTERVAL")
+    .ok()
+    .and_then(|secs| secs.parse::<u64>().ok())
+    .filter(|secs| *secs > 0)
+    .unwrap_or(DEFAULT_HEALTH_INTERVAL_SECS);
+  Duration::from_secs(secs)
+}
+
+/// Record whether Redis answered in the state's health flag, logging changes
+fn record_health(state: &AppState, healthy: bool) {
+  metrics::set_redis_up(healthy);
+  match (state.set_redis_healthy(healthy), healthy) {
+    (true, false) => warn!("Redis stopped answering health checks"),
+    (false, true) => info!("Redis is answering health checks again"),
+    _ => {}
+  }
+}
+
+/// Ping the state's Redis pool every `interval`, keeping its health flag up to date
+///
+/// Checks are skipped while the state has no pool; the reconnect task sets the flag
+/// when a pool appears.
+pub fn spawn_health_monitor(state: AppState, interval: Duration) -> tokio::task::JoinHandle<()> {
+  tokio::spawn(async move {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+      ticker.tick().await;
+      let Some(redis_pool) = state.redis() else {
+        continue;
+      };
+      match redis_pool.ping().await {
+        Ok(()) => record_health(&state, true),
+        Err(e) => {
+          debug!("Background health check failed: {}", e);
+          record_health(&state, false);
+        }
+      }
+    }
+  })
+}
+
+/// Create a router with the health check routes
+pub fn router(state: AppState) -> Router {
+  debug!("Setting up health check routes");
+  Router::new()
+    .route("/health_check", get(health_check))
+    .route("/livez", get(livez))
+    .route("/readyz", get(readyz))
+    .with_state(state)
+}
+
+/// Always returns `200 OK` with an empty body
+#[utoipa::path(
+  get,
+  path = "/health_check",
+  tag = "status",
+  responses((status = 200, description = "The server is up"))
+)]
+pub async fn health_check() -> StatusCode {
+  StatusCode::OK
+}
+
+/// Liveness probe: always returns `200 OK` with an empty body
+#[utoipa::path(
+  get,
+  path = "/livez",
+  tag = "status",
+  responses((status = 200, description = "The process is up"))
+)]
+pub async fn livez() -> StatusCode {
+  StatusCode::OK
+}
+
+/// Readiness probe: `200 OK` only when Redis answers a `PING`
+#[utoipa::path(
+  get,
+  path = "/readyz",
+  tag = "status",
+  responses(
+    (status = 200, description = "Ready to serve traffic", body = Value),
+    (status = 503, description = "Redis is down or not configured", body = Value),
+  )
+)]
+pub async fn readyz(State(state): State<AppState>) -> (StatusCode, Json<Value>) {
+  let Some(redis_pool) = state.redis() else {
+    return (
+      StatusCode::SERVICE_UNAVAILABLE,
+      Json(json!({"status": "not_ready", "redis_status": "not_configured"})),
+    );
+  };
+
+  match redis_pool.ping().await {
+    Ok(_) => {
+      record_health(&state, true);
+      (StatusCode::OK, Json(json!({"status": "ready"})))
+    }
+    Err(e) => {
+      warn!("Readiness check failed: {}", e);
+      record_health(&state, false);
+      (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(json!({"status": "not_ready", "redis_status": "disconnected"})),
+      )
+    }
+  }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+  use crate::redis::fake::{fake_pool, FakeRedis};
+  use crate::redis::{RedisConfig, RedisPool};
+  use axum::{body::Body, http::Request};
+  use http_body_util::BodyExt;
+  use serde_json::Value;
+  use std::sync::Arc;
+  use tower::ServiceExt;
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  async fn get_status(app: Router, uri: &str) -> StatusCode {
+    get_json(app, uri).await.0
+  }
+
+  async fn get_json(app: Router, uri: &str) -> (StatusCode, Value) {
+    let response = app
+      .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+      .await
+      .unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+  }
+
+  #[tokio::test]
+  async fn test_livez_does_not_need_redis() {
+    setup();
+    assert_eq!(
+      get_status(router(AppState::default()), "/livez").await,
+      StatusCode::OK
+    );
+  }
+
+  #[tokio::test]
+  async fn test_readyz_without_redis_is_unavailable() {
+    setup();
+    let config = RedisConfig {
+      url: "redis://127.0.0.1:1".to_string(),
+      ..RedisConfig::default()
+    };
+    let down = Some(Arc::new(RedisPool::new(config).unwrap()));
+
+    for pool in [None, down] {
+      assert_eq!(
+        get_status(router(AppState::new(pool)), "/readyz").await,
+        StatusCode::SERVICE_UNAVAILABLE
+      );
+    }
+  }
+
+  #[tokio::test]
+  async fn test_readyz_follows_live_state() {
+    setup();
+    let state = AppState::default();
+    let app = router(state.clone());
+
+    let (status, body) = get_json(app.clone(), "/readyz").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["redis_status"], "not_configured");
+
+    // The same router sees a pool added after it was built
+    let config = RedisConfig {
+      url: "redis://127.0.0.1:1".to_string(),
+      ..RedisConfig::default()
+    };
+    state.set_redis(Some(Arc::new(RedisPool::new(config).unwrap())));
+    let (_, body) = get_json(app.clone(), "/readyz").await;
+    assert_eq!(body["redis_status"], "disconnected");
+
+    state.set_redis(None);
+    let (_, body) = get_json(app, "/readyz").await;
+    assert_eq!(body["redis_status"], "not_configured");
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_readyz_with_redis_is_ready() {
+    setup();
+    crate::init_env().unwrap();
+    let pool = Arc::new(RedisPool::init().await.unwrap());
+
+    assert_eq!(
+      get_status(router(pool.into()), "/readyz").await,
+      StatusCode::OK
+    );
+  }
+
+  /// Wait up to a second for the state's health flag to read `healthy`
+  async fn wait_for_health(state: &AppState, healthy: bool) {
+    for _ in 0..100 {
+      if state.redis_healthy() == healthy {
+        return;
+      }
+      tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("health flag never became {}", healthy);
+  }
+
+  #[tokio::test]
+  async fn test_health_monitor_pings_redis() {
+    setup();
+    let down = RedisConfig {
+      url: "redis://127.0.0.1:1".to_string(),
+      ..RedisConfig::default()
+    };
+    let state = AppState::new(Some(Arc::new(RedisPool::new(down).unwrap())));
+    let monitor = spawn_health_monitor(state.clone(), Duration::from_millis(10));
+    wait_for_health(&state, false).await;
+
+    let server = FakeRedis::start(None).await;
+    state.set_redis(Some(Arc::new(fake_pool(&server.url, None))));
+    state.set_redis_healthy(false);
+    wait_for_health(&state, true).await;
+    assert!(server.count("PING") > 0);
+    monitor.abort();
+  }
+}
@@ -0,0 +1,151 @@
+//! Request body size and duration limits.
+//!
+//! Bodies larger than the limit get `413 Payload Too Large` and requests that take
+//! longer than the timeout get `408 Request Timeout`. Both are produced by tower-http
+//! layers with bare bodies, so they're rewritten into the usual `Error` envelope.
+
+use crate::http::config::positive_env_var;
+use crate::http::Error;
+use axum::{
+  extract::DefaultBodyLimit,
+  http::{header, StatusCode},
+  middleware,
+  response::{IntoResponse, Response},
+  Router,
+};
+use std::time::Duration;
+use tower_http::{limit::RequestBodyLimitLayer, timeout::TimeoutLayer};
+
+const DEFAULT_BODY_LIMIT_BYTES: usize = 1024 * 1024;
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Largest accepted request body and longest time allowed to handle a request
+#[derive(Debug, Clone, Copy)]
+pub struct LimitConfig {
+  pub body_limit_bytes: usize,
+  pub timeout: Duration,
+}
+
+impl Default for LimitConfig {
+  /// Read `MAX_BODY_BYTES` (default 1 MiB) and `REQUEST_TIMEOUT_SECS` (default 30)
+  fn default() -> Self {
+    LimitConfig {
+      body_limit_bytes: positive_env_var("MAX_BODY_BYTES", DEFAULT_BODY_LIMIT_BYTES as u64)
+        as usize,
+      timeout: Duration::from_secs(positive_env_var(
+        "REQUEST_TIMEOUT_SECS",
+        DEFAULT_TIMEOUT_SECS,
+      )),
+    }
+  }
+}
+
+/// Apply the body size limit and request timeout to every route of `router`
+pub fn with_limits(router: Router, config: LimitConfig) -> Router {
+  router
+    // Our limit replaces axum's built-in 2 MB limit on body extractors
+    .layer(DefaultBodyLimit::disable())
+    .layer(RequestBodyLimitLayer::new(config.body_limit_bytes))
+    .layer(TimeoutLayer::with_status_code(
+      StatusCode::REQUEST_TIMEOUT,
+      config.timeout,
+    ))
+    .layer(middleware::map_response(envelope_limit_errors))
+}
+
+/// Replace the bare 413 and 408 responses from the limit layers with our error body
+async fn envelope_limit_errors(response: Response) -> Response {
+  let is_json = response
+    .headers()
+    .get(header::CONTENT_TYPE)
+    .is_some_and(|value| value.as_bytes().starts_with(b"application/json"));
+  if is_json {
+    return response;
+  }
+
+  match response.status() {
+    StatusCode::PAYLOAD_TOO_LARGE => Error::PayloadTooLarge.into_response(),
+    StatusCode::REQUEST_TIMEOUT => Error::RequestTimeout.into_response(),
+    _ => response,
+  }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+  use axum::{body::Body, http::Request, routing::post};
+  use http_body_util::BodyExt;
+  use serde_json::Value;
+  use tower::ServiceExt;
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  fn app() -> Router {
+    let router = Router::new()
+      .route("/import", post(|body: String| async move { body }))
+      .route(
+        "/slow",
+        post(|| async { tokio::time::sleep(Duration::from_secs(5)).await }),
+      );
+    with_limits(
+      router,
+      LimitConfig {
+        body_limit_bytes: 16,
+        timeout: Duration::from_millis(50),
+      },
+    )
+  }
+
+  async fn error_code(response: Response) -> Value {
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    body["error"]["code"].clone()
+  }
+
+  #[tokio::test]
+  async fn test_oversized_body_is_rejected() {
+    setup();
+    let response = app()
+      .oneshot(
+        Request::post("/import")
+          .body(Body::from("x".repeat(1024)))
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(error_code(response).await, "payload_too_large");
+  }
+
+  #[tokio::test]
+  async fn test_small_body_is_accepted() {
+    setup();
+    let response = app()
+      .oneshot(
+        Request::post("/import")
+          .body(Body::from("id,grade"))
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+  }
+
+  #[tokio::test]
+  async fn test_slow_request_times_out() {
+    setup();
+    let response = app()
+      .oneshot(Request::post("/slow").body(Body::empty()).unwrap())
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+    assert_eq!(error_code(response).await, "request_timeout");
+  }
+}
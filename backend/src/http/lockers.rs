@@ -0,0 +1,278 @@
+use axum::{
+  extract::Path,
+  routing::{get, post},
+  Router,
+};
+use log::{debug, info};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::env;
+
+use crate::http::{AppState, Error, Json, Redis};
+use crate::locker::{availability, store, Assignment, HallwayAvailability, Locker};
+use crate::student::StudentId;
+
+/// Most locker numbers a batch lookup may ask for when `LOCKER_BATCH_MAX` is unset
+const DEFAULT_BATCH_MAX: usize = 500;
+
+/// Most locker numbers a batch lookup may ask for, from `LOCKER_BATCH_MAX`
+fn batch_max() -> usize {
+  env::var("LOCKER_BATCH_MAX")
+    .ok()
+    .and_then(|max| max.parse::<usize>().ok())
+    .filter(|max| *max > 0)
+    .unwrap_or(DEFAULT_BATCH_MAX)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchRequest {
+  numbers: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClaimRequest {
+  student_id: String,
+}
+
+/// Create a router with the locker routes
+pub fn router(state: AppState) -> Router {
+  debug!("Setting up locker routes");
+  Router::new()
+    .route("/lockers/availability", get(locker_availability))
+    .route("/lockers/batch", post(batch_lockers))
+    .route("/lockers/{number}/claim", post(claim_locker))
+    .with_state(state)
+}
+
+/// Split requested locker numbers into well-formed and invalid ones, dropping duplicates
+fn partition_numbers(numbers: Vec<String>) -> (Vec<String>, Vec<String>) {
+  let mut valid: Vec<String> = Vec::new();
+  let mut invalid: Vec<String> = Vec::new();
+
+  for number in numbers {
+    let number = number.trim().to_string();
+    let bucket = if Locker::is_valid_number(&number) {
+      &mut valid
+    } else {
+      &mut invalid
+    };
+    if !bucket.contains(&number) {
+      bucket.push(number);
+    }
+  }
+
+  (valid, invalid)
+}
+
+/// Fetch many lockers by number for the locker map
+///
+/// Batches of more than `LOCKER_BATCH_MAX` numbers are rejected with 400.
+pub async fn batch_lockers(
+  Redis(redis_pool): Redis,
+  Json(request): Json<BatchRequest>,
+) -> Result<Json<Value>, Error> {
+  debug!("Batch locker lookup for {} numbers", request.numbers.len());
+
+  let max = batch_max();
+  if request.numbers.len() > max {
+    return Err(Error::BadRequest(format!(
+      "at most {} locker numbers per batch",
+      max
+    )));
+  }
+
+  let (valid, invalid) = partition_numbers(request.numbers);
+  let (lockers, missing) = store::get_lockers(&redis_pool, &valid).await?;
+
+  let response = json!({
+      "lockers": lockers,
+      "missing": missing,
+      "invalid": invalid
+  });
+
+  Ok(Json(response))
+}
+
+/// Count total, free, assigned and free ADA lockers in each hallway
+pub async fn locker_availability(
+  Redis(redis_pool): Redis,
+) -> Result<Json<BTreeMap<String, HallwayAvailability>>, Error> {
+  debug!("Locker availability requested");
+  Ok(Json(availability(&redis_pool).await?))
+}
+
+/// Claim a specific locker for a student, failing with 409 if someone else holds it
+pub async fn claim_locker(
+  Path(number): Path<String>,
+  Redis(redis_pool): Redis,
+  Json(request): Json<ClaimRequest>,
+) -> Result<Json<Assignment>, Error> {
+  let student_id = StudentId::new(request.student_id)?;
+
+  let (mut lockers, _) = store::get_lockers(&redis_pool, &[number]).await?;
+  let locker = lockers.pop().ok_or(Error::NotFound)?;
+
+  let assignment = store::claim_locker(&redis_pool, &student_id, &locker).await?;
+  info!(
+    "Student {} claimed locker {} directly",
+    student_id.to_string(),
+    locker.number
+  );
+
+  Ok(Json(assignment))
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  #[test]
+  fn test_partition_numbers() {
+    setup();
+    let (valid, invalid) = partition_numbers(vec![
+      "A-102".to_string(),
+      "215".to_string(),
+      "".to_string(),
+      "A 102!".to_string(),
+      "A-102".to_string(),
+    ]);
+
+    assert_eq!(valid, vec!["A-102", "215"]);
+    assert_eq!(invalid, vec!["", "A 102!"]);
+  }
+
+  #[tokio::test]
+  async fn test_oversized_batch_is_rejected() {
+    use crate::redis::{RedisConfig, RedisPool};
+    use axum::{body::Body, http::Request};
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    setup();
+    // Rejected before Redis is touched, so a pool that can't connect will do
+    let pool = RedisPool::new(RedisConfig {
+      url: "redis://127.0.0.1:1".to_string(),
+      ..RedisConfig::default()
+    })
+    .unwrap();
+    let numbers: Vec<String> = (0..=DEFAULT_BATCH_MAX)
+      .map(|i| format!("Z-{}", i))
+      .collect();
+
+    let response = router(Arc::new(pool).into())
+      .oneshot(
+        Request::post("/lockers/batch")
+          .header("content-type", "application/json")
+          .body(Body::from(json!({ "numbers": numbers }).to_string()))
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), 400);
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_batch_lockers_found_missing_invalid() {
+    use crate::locker::LockerSize;
+    use crate::redis::{RedisOperations, RedisPool};
+    use axum::{body::Body, http::Request};
+    use http_body_util::BodyExt;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    setup();
+    crate::init_env().unwrap();
+    let pool = Arc::new(RedisPool::init().await.unwrap());
+
+    let locker = Locker::new(
+      "T-1".to_string(),
+      "T".to_string(),
+      1,
+      LockerSize::Standard,
+      false,
+    )
+    .unwrap();
+    store::save_locker(&pool, &locker).await.unwrap();
+
+    let response = router(pool.clone().into())
+      .oneshot(
+        Request::post("/lockers/batch")
+          .header("content-type", "application/json")
+          .body(Body::from(r#"{"numbers": ["T-1", "T-404", "bad number"]}"#))
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), 200);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["lockers"][0]["number"], "T-1");
+    assert_eq!(body["missing"], json!(["T-404"]));
+    assert_eq!(body["invalid"], json!(["bad number"]));
+
+    pool.del(&store::locker_key("T-1")).await.unwrap();
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_claim_taken_locker_returns_409() {
+    use crate::locker::LockerSize;
+    use crate::redis::{RedisOperations, RedisPool};
+    use axum::{body::Body, http::Request};
+    use http_body_util::BodyExt;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    setup();
+    crate::init_env().unwrap();
+    let pool = Arc::new(RedisPool::init().await.unwrap());
+
+    let locker = Locker::new(
+      "T-2".to_string(),
+      "T".to_string(),
+      1,
+      LockerSize::Standard,
+      false,
+    )
+    .unwrap();
+    store::save_locker(&pool, &locker).await.unwrap();
+
+    let claim = |student_id: &str| {
+      Request::post("/lockers/T-2/claim")
+        .header("content-type", "application/json")
+        .body(Body::from(json!({ "student_id": student_id }).to_string()))
+        .unwrap()
+    };
+    let app = router(pool.clone().into());
+
+    let response = app.clone().oneshot(claim("900011")).await.unwrap();
+    assert_eq!(response.status(), 200);
+
+    let response = app.oneshot(claim("900012")).await.unwrap();
+    assert_eq!(response.status(), 409);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"]["code"], "conflict");
+    assert_eq!(
+      body["error"]["message"],
+      "conflict: locker T-2 is already assigned"
+    );
+
+    let winner = store::get_assignment(&pool, &StudentId::new("900011".to_string()).unwrap())
+      .await
+      .unwrap()
+      .unwrap();
+    store::release_assignment(&pool, &winner).await.unwrap();
+    pool.del(&store::locker_key("T-2")).await.unwrap();
+  }
+}
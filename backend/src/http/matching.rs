@@ -0,0 +1,203 @@
+//! Running the matcher over HTTP.
+//!
+//! `POST /match/run` matches every active student without a locker to the free
+//! lockers, the same run a CLI job would do, and records it as a match session.
+
+use axum::{routing::post, Router};
+use chrono::Utc;
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::http::{AppState, Error, Json, Redis};
+use crate::locker::{run_session, store, MatchStats, ZonePolicy};
+use crate::student::{self, Student};
+
+/// Longest accepted session name, in bytes
+const SESSION_NAME_MAX: usize = 100;
+
+/// Create a router with the matching routes
+pub fn router(state: AppState) -> Router {
+  debug!("Setting up matching routes");
+  Router::new()
+    .route("/match/run", post(run_match))
+    .with_state(state)
+}
+
+/// Request body for a match run
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MatchRunRequest {
+  /// Session name; defaults to one derived from the current time
+  #[serde(default)]
+  name: Option<String>,
+  /// Hallways each grade may be placed in; unrestricted when omitted
+  #[serde(default)]
+  policy: ZonePolicy,
+  /// Compute the session without writing anything
+  #[serde(default)]
+  dry_run: bool,
+}
+
+/// What a match run did, or would do for a dry run
+#[derive(Debug, Serialize)]
+pub struct MatchRunSummary {
+  pub name: String,
+  pub dry_run: bool,
+  pub assigned: usize,
+  pub unassigned: usize,
+  /// Free lockers left over after the run
+  pub lockers_remaining: usize,
+  pub stats: MatchStats,
+}
+
+/// Match every active student without a locker to the free lockers
+///
+/// Answers `409 Conflict` if a session with the requested name already exists.
+pub async fn run_match(
+  Redis(redis_pool): Redis,
+  Json(request): Json<MatchRunRequest>,
+) -> Result<Json<MatchRunSummary>, Error> {
+  let name = match request.name {
+    Some(name) => {
+      let name = name.trim().to_string();
+      if name.is_empty() || name.len() > SESSION_NAME_MAX {
+        return Err(Error::unprocessable_entity([(
+          "name",
+          "must be between 1 and 100 bytes",
+        )]));
+      }
+      name
+    }
+    None => format!("run-{}", Utc::now().format("%Y%m%dT%H%M%S")),
+  };
+
+  let assigned: HashSet<String> = store::list_assignments(&redis_pool)
+    .await?
+    .into_iter()
+    .map(|assignment| assignment.student_id.to_string())
+    .collect();
+  let students: Vec<Student> = student::store::load_all(&redis_pool)
+    .await?
+    .into_iter()
+    .filter(|s| s.active && !assigned.contains(&s.id.to_string()))
+    .collect();
+  let lockers = store::list_free_lockers(&redis_pool).await?;
+  debug!(
+    "Match run {}: {} students, {} free lockers",
+    name,
+    students.len(),
+    lockers.len()
+  );
+
+  let session = run_session(
+    &redis_pool,
+    &name,
+    &students,
+    &lockers,
+    &request.policy,
+    None,
+    request.dry_run,
+  )
+  .await?;
+  if !request.dry_run {
+    info!("Match run {} started over HTTP", name);
+  }
+
+  let stats = session.stats();
+  Ok(Json(MatchRunSummary {
+    name: session.name,
+    dry_run: request.dry_run,
+    assigned: session.assignments.len(),
+    unassigned: session.unassigned.len(),
+    lockers_remaining: lockers.len() - session.assignments.len(),
+    stats,
+  }))
+}
+
+// Tests
+#[cfg(all(test, feature = "redis-tests"))]
+mod tests {
+  use super::*;
+  use crate::locker::session::get_session;
+  use crate::locker::store::save_locker;
+  use crate::locker::{Locker, LockerSize};
+  use crate::redis::{RedisConfig, RedisPool};
+  use crate::{init_env, init_logging};
+  use axum::{body::Body, http::header, http::Request};
+  use chrono::Datelike;
+  use http_body_util::BodyExt;
+  use serde_json::Value;
+  use std::sync::Arc;
+  use tower::ServiceExt;
+
+  async fn setup() -> Arc<RedisPool> {
+    let _ = init_logging(); // Ignore error if already initialized
+    init_env().unwrap();
+    // A private namespace, since a run reads every student and locker
+    let pool = RedisPool::new(RedisConfig {
+      key_prefix: Some("test-match-run".to_string()),
+      ..RedisConfig::default()
+    })
+    .unwrap();
+    pool.delete_prefix("").await.unwrap();
+    Arc::new(pool)
+  }
+
+  fn student(id: &str) -> Student {
+    Student::new(
+      id.to_string(),
+      "Match".to_string(),
+      "Run".to_string(),
+      format!("{}@csxlabs.edu", id),
+      11,
+      Utc::now().year() as u16 + 1,
+      None,
+    )
+    .unwrap()
+  }
+
+  #[tokio::test]
+  async fn test_dry_run_returns_counts_without_persisting() {
+    let pool = setup().await;
+    for id in ["950001", "950002", "950003"] {
+      student::store::save(&pool, &student(id)).await.unwrap();
+    }
+    for number in ["R-1", "R-2"] {
+      let locker = Locker::new(
+        number.to_string(),
+        "R".to_string(),
+        1,
+        LockerSize::Standard,
+        false,
+      )
+      .unwrap();
+      save_locker(&pool, &locker).await.unwrap();
+    }
+
+    let response = router(pool.clone().into())
+      .oneshot(
+        Request::post("/match/run")
+          .header(header::CONTENT_TYPE, "application/json")
+          .body(Body::from(r#"{"name":"test-dry","dry_run":true}"#))
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), 200);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["name"], "test-dry");
+    assert_eq!(body["dry_run"], true);
+    assert_eq!(body["assigned"], 2);
+    assert_eq!(body["unassigned"], 1);
+    assert_eq!(body["lockers_remaining"], 0);
+    assert_eq!(body["stats"]["hallways"]["R"]["fill_rate"], 1.0);
+
+    assert!(get_session(&pool, "test-dry").await.unwrap().is_none());
+    assert!(store::list_assignments(&pool).await.unwrap().is_empty());
+    assert_eq!(store::list_free_lockers(&pool).await.unwrap().len(), 2);
+    pool.delete_prefix("").await.unwrap();
+  }
+}
@@ -0,0 +1,98 @@
+//! Prometheus metrics.
+//!
+//! A process-wide recorder collects the counters emitted by the access log middleware
+//! and by `RedisPool`, and `/metrics` renders them in the Prometheus text format:
+//! - `http_requests_total{method, status}` and `http_request_duration_seconds{method}`
+//! - `redis_commands_total`, `redis_command_errors_total` and `redis_reconnects_total`
+//! - `redis_up`: 1 if the last `PING` succeeded, 0 otherwise
+
+use axum::{
+  http::{header, HeaderValue},
+  response::IntoResponse,
+  routing::get,
+  Router,
+};
+use log::debug;
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+use std::sync::OnceLock;
+
+/// Content type of the Prometheus text exposition format
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+static HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+
+/// Install the global Prometheus recorder on first use and return its handle
+pub fn handle() -> &'static PrometheusHandle {
+  HANDLE.get_or_init(|| {
+    debug!("Installing Prometheus metrics recorder");
+    PrometheusBuilder::new()
+      .install_recorder()
+      .expect("Failed to install metrics recorder")
+  })
+}
+
+/// Record the result of a Redis `PING` in the `redis_up` gauge
+pub fn set_redis_up(up: bool) {
+  metrics::gauge!("redis_up").set(if up { 1.0 } else { 0.0 });
+}
+
+/// Create a router with the metrics route
+pub fn router() -> Router {
+  debug!("Setting up metrics route");
+  // Install the recorder before any request is counted
+  handle();
+  Router::new().route("/metrics", get(render))
+}
+
+/// Render every metric in the Prometheus text format
+pub async fn render() -> impl IntoResponse {
+  (
+    [(
+      header::CONTENT_TYPE,
+      HeaderValue::from_static(PROMETHEUS_CONTENT_TYPE),
+    )],
+    handle().render(),
+  )
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use crate::http::AppState;
+  use crate::init_logging;
+  use axum::{body::Body, http::Request};
+  use http_body_util::BodyExt;
+  use tower::ServiceExt;
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  #[tokio::test]
+  async fn test_metrics_counts_requests() {
+    setup();
+    let app = crate::http::router(AppState::default());
+    let response = app
+      .clone()
+      .oneshot(Request::get("/health_check").body(Body::empty()).unwrap())
+      .await
+      .unwrap();
+    assert_eq!(response.status(), 200);
+
+    let response = app
+      .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+      response.headers()["content-type"],
+      "text/plain; version=0.0.4"
+    );
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body = String::from_utf8(body.to_vec()).unwrap();
+    assert!(body
+      .lines()
+      .any(|line| line.starts_with("http_requests_total{")));
+  }
+}
@@ -1,33 +1,199 @@
 use anyhow::Context;
-use log::{debug, info};
-use std::sync::Arc;
+use axum::{middleware, Router};
+use log::{debug, info, warn};
 
+mod access_log;
+mod assignments;
+mod auth;
+mod config;
+mod cors;
+mod cursor;
 mod error;
+mod extract;
+mod health;
+mod limits;
+mod lockers;
+mod matching;
+mod metrics;
+mod openapi;
+mod rate_limit;
+mod request_id;
+mod state;
 mod status;
+mod students;
 
 // Re-export our custom Error type
-pub use error::Error;
-
-pub async fn serve(redis_pool: Option<Arc<crate::redis::RedisPool>>) -> anyhow::Result<()> {
-  let app = if let Some(pool) = redis_pool {
-    debug!("Initializing router with Redis support");
-    status::with_redis_router(pool)
-  } else {
-    debug!("Initializing router without Redis support");
-    status::base_router()
+pub use access_log::ACCESS_LOG_TARGET;
+pub use auth::{ApiKeys, Authenticated};
+pub use config::ServerConfig;
+pub use cursor::{Cursor, CursorKind};
+pub use error::{Error, ErrorBody, ErrorDetail};
+pub use extract::{Json, Query, ValidatedQuery};
+pub use health::{health_interval, spawn_health_monitor};
+pub use limits::LimitConfig;
+pub use rate_limit::RateLimitConfig;
+pub use request_id::{current as current_request_id, RequestId};
+pub use state::{AppState, Redis, Students};
+
+#[cfg(test)]
+pub(crate) use request_id::with_request_id;
+
+/// Build the application router
+///
+/// Every route is registered up front; the Redis-backed ones answer `503` while `state`
+/// has no pool.
+pub fn router(state: AppState) -> Router {
+  debug!("Initializing API router");
+  let protected = assignments::router(state.clone())
+    .merge(lockers::router(state.clone()))
+    .merge(matching::router(state.clone()))
+    .merge(students::router(state.clone()))
+    .route_layer(middleware::from_fn_with_state(
+      ApiKeys::from_env(),
+      auth::require_api_key,
+    ));
+  let limiter = rate_limit::RateLimiter {
+    state: state.clone(),
+    config: RateLimitConfig::default(),
   };
+  let app =
+    status::router(state.clone())
+      .merge(protected)
+      .route_layer(middleware::from_fn_with_state(
+        limiter,
+        rate_limit::rate_limit,
+      ));
+
+  let app = app
+    .merge(health::router(state))
+    .merge(metrics::router())
+    .merge(openapi::router())
+    .fallback(not_found);
+
+  limits::with_limits(app, LimitConfig::default())
+    .layer(cors::cors_layer())
+    .layer(middleware::from_fn(access_log::access_log))
+    .layer(middleware::from_fn(request_id::request_id))
+}
+
+/// Answer unknown paths with the usual JSON error body instead of an empty `404`
+async fn not_found() -> Error {
+  Error::NotFound
+}
+
+pub async fn serve(state: AppState, config: ServerConfig) -> anyhow::Result<()> {
+  let app = router(state);
+  let addr = config.socket_addr()?;
 
-  info!("Starting HTTP server on 0.0.0.0:3000");
+  info!("Starting HTTP server on {}", addr);
   debug!("Initializing API router");
 
-  let listener = tokio::net::TcpListener::bind("0.0.0.0:3000")
+  let listener = tokio::net::TcpListener::bind(addr)
     .await
-    .context("Failed to bind to port 3000")?;
+    .with_context(|| format!("Failed to bind to {}", addr))?;
 
-  info!("Server is listening on 0.0.0.0:3000");
+  info!("Server is listening on {}", addr);
   info!("Press Ctrl+C to stop the server");
 
-  axum::serve(listener, app)
-    .await
-    .context("Failed to start server")
+  axum::serve(
+    listener,
+    app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+  )
+  .with_graceful_shutdown(shutdown_signal())
+  .await
+  .context("Failed to start server")
+}
+
+/// Resolves on Ctrl+C (SIGINT) or SIGTERM so in-flight requests can finish before exit
+pub async fn shutdown_signal() {
+  let ctrl_c = async {
+    if let Err(e) = tokio::signal::ctrl_c().await {
+      warn!("Failed to listen for Ctrl+C: {}", e);
+      std::future::pending::<()>().await;
+    }
+  };
+
+  #[cfg(unix)]
+  let terminate = async {
+    match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+      Ok(mut signal) => {
+        signal.recv().await;
+      }
+      Err(e) => {
+        warn!("Failed to listen for SIGTERM: {}", e);
+        std::future::pending::<()>().await;
+      }
+    }
+  };
+
+  #[cfg(not(unix))]
+  let terminate = std::future::pending::<()>();
+
+  tokio::select! {
+    _ = ctrl_c => {},
+    _ = terminate => {},
+  }
+
+  info!("Shutting down gracefully");
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+  use axum::{body::Body, http::Request};
+  use http_body_util::BodyExt;
+  use tower::ServiceExt;
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  #[tokio::test]
+  async fn test_health_check_is_served() {
+    setup();
+    let response = router(AppState::default())
+      .oneshot(Request::get("/health_check").body(Body::empty()).unwrap())
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), 200);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert!(body.is_empty());
+  }
+
+  #[tokio::test]
+  async fn test_unknown_path_returns_json_404() {
+    setup();
+    let response = router(AppState::default())
+      .oneshot(Request::get("/nope").body(Body::empty()).unwrap())
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), 404);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"]["code"], "not_found");
+  }
+
+  #[cfg(unix)]
+  #[tokio::test]
+  async fn test_shutdown_signal_resolves_on_sigterm() {
+    setup();
+    let shutdown = tokio::spawn(shutdown_signal());
+    // Let the task register its signal handlers before the signal is sent
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    let status = std::process::Command::new("kill")
+      .args(["-TERM", &std::process::id().to_string()])
+      .status()
+      .unwrap();
+    assert!(status.success());
+
+    tokio::time::timeout(std::time::Duration::from_secs(5), shutdown)
+      .await
+      .expect("shutdown signal did not resolve")
+      .unwrap();
+  }
 }
@@ -0,0 +1,106 @@
+//! OpenAPI description of the HTTP API, served at `/openapi.json`.
+//!
+//! Routes are documented with `#[utoipa::path]` next to their handlers; add new ones to
+//! `ApiDoc` so they show up in the spec.
+
+use axum::{routing::get, Router};
+use log::debug;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use crate::http::{health, status, students, Json};
+
+#[derive(OpenApi)]
+#[openapi(
+  info(title = "Lockermatch API"),
+  paths(
+    status::status,
+    status::last_status_check,
+    health::health_check,
+    health::livez,
+    health::readyz,
+    students::list_students,
+    students::create_student,
+    students::import_students,
+    students::search_students,
+    students::changed_since,
+    students::get_student,
+    students::update_student,
+    students::delete_student,
+  ),
+  modifiers(&ApiKeyAuth),
+  tags(
+    (name = "status", description = "Liveness and diagnostics"),
+    (name = "students", description = "The student roster"),
+  )
+)]
+pub struct ApiDoc;
+
+/// Registers the `Authorization: Bearer <key>` scheme used by mutating routes
+struct ApiKeyAuth;
+
+impl Modify for ApiKeyAuth {
+  fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+    let components = openapi.components.get_or_insert_with(Default::default);
+    components.add_security_scheme(
+      "api_key",
+      SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+    );
+  }
+}
+
+/// Create a router serving the spec
+pub fn router() -> Router {
+  debug!("Setting up OpenAPI route");
+  Router::new().route("/openapi.json", get(openapi_json))
+}
+
+/// The OpenAPI document as JSON
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+  Json(ApiDoc::openapi())
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use crate::http::AppState;
+  use crate::init_logging;
+  use axum::{body::Body, http::Request};
+  use http_body_util::BodyExt;
+  use serde_json::Value;
+  use tower::ServiceExt;
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  #[tokio::test]
+  async fn test_openapi_json_lists_paths() {
+    setup();
+    let response = crate::http::router(AppState::default())
+      .oneshot(Request::get("/openapi.json").body(Body::empty()).unwrap())
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), 200);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let spec: Value = serde_json::from_slice(&body).unwrap();
+
+    for path in [
+      "/status",
+      "/status/last",
+      "/health_check",
+      "/livez",
+      "/readyz",
+      "/students",
+      "/students/{id}",
+      "/students/import",
+    ] {
+      assert!(spec["paths"].get(path).is_some(), "missing {}", path);
+    }
+    let schemas = &spec["components"]["schemas"];
+    assert_eq!(schemas["StudentId"]["pattern"], "^[0-9]{6}$");
+    assert!(schemas.get("Student").is_some());
+    assert!(schemas.get("ErrorBody").is_some());
+  }
+}
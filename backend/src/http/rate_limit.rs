@@ -0,0 +1,164 @@
+//! Per-client rate limiting backed by Redis.
+//!
+//! Each client IP gets a fixed-window counter at `ratelimit:{ip}:{window}`, where
+//! `window` is the index of the current window since the epoch. The counter is bumped
+//! with `INCR` and expires with the window, so no cleanup is needed. Requests past the
+//! limit get `429 Too Many Requests` with `Retry-After` set to the end of the window.
+
+use crate::http::config::positive_env_var;
+use crate::http::{AppState, Error};
+use axum::{
+  extract::{ConnectInfo, Request, State},
+  middleware::Next,
+  response::Response,
+};
+use chrono::Utc;
+use log::{debug, warn};
+use std::net::SocketAddr;
+
+const DEFAULT_LIMIT: u64 = 120;
+const DEFAULT_WINDOW_SECS: u64 = 60;
+
+/// How many requests a client may make per window
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitConfig {
+  pub limit: u64,
+  pub window_secs: u64,
+}
+
+impl Default for RateLimitConfig {
+  /// Read `RATE_LIMIT_REQUESTS` (default 120) and `RATE_LIMIT_WINDOW_SECS` (default 60)
+  fn default() -> Self {
+    RateLimitConfig {
+      limit: positive_env_var("RATE_LIMIT_REQUESTS", DEFAULT_LIMIT),
+      window_secs: positive_env_var("RATE_LIMIT_WINDOW_SECS", DEFAULT_WINDOW_SECS),
+    }
+  }
+}
+
+/// Middleware state: the app state holding the counters' pool and the limit to enforce
+#[derive(Clone)]
+pub struct RateLimiter {
+  pub state: AppState,
+  pub config: RateLimitConfig,
+}
+
+/// Redis key of a client's counter for one window
+pub fn rate_limit_key(client: &str, window: u64) -> String {
+  format!("ratelimit:{}:{}", client, window)
+}
+
+/// Middleware rejecting clients that exceed the configured request rate
+///
+/// The client is identified by the peer address, so the server must be run with
+/// `into_make_service_with_connect_info`. If Redis is missing or can't be reached the
+/// request is let through rather than failing the whole API.
+pub async fn rate_limit(
+  State(limiter): State<RateLimiter>,
+  request: Request,
+  next: Next,
+) -> Result<Response, Error> {
+  let client = request
+    .extensions()
+    .get::<ConnectInfo<SocketAddr>>()
+    .map(|ConnectInfo(addr)| addr.ip().to_string())
+    .unwrap_or_else(|| "unknown".to_string());
+
+  let Some(redis_pool) = limiter.state.redis() else {
+    return Ok(next.run(request).await);
+  };
+
+  let window_secs = limiter.config.window_secs;
+  let now = Utc::now().timestamp().max(0) as u64;
+  let window = now / window_secs;
+  let key = redis_pool.prefixed(&rate_limit_key(&client, window));
+
+  let mut pipe = redis::pipe();
+  pipe.incr(&key, 1).expire(&key, window_secs as i64).ignore();
+  let count = match redis_pool.execute_pipeline::<(u64,)>(&pipe).await {
+    Ok((count,)) => count,
+    Err(e) => {
+      warn!("Rate limiter unavailable, allowing request: {}", e);
+      return Ok(next.run(request).await);
+    }
+  };
+
+  if count > limiter.config.limit {
+    debug!(
+      "Client {} exceeded {} requests in window {}",
+      client, limiter.config.limit, window
+    );
+    return Err(Error::TooManyRequests {
+      retry_after_secs: (window + 1) * window_secs - now,
+    });
+  }
+
+  Ok(next.run(request).await)
+}
+
+// Tests
+#[cfg(all(test, feature = "redis-tests"))]
+mod tests {
+  use super::*;
+  use crate::redis::{RedisOperations, RedisPool};
+  use crate::{init_env, init_logging};
+  use axum::{body::Body, http::header, middleware, routing::get, Router};
+  use std::sync::Arc;
+  use tower::ServiceExt;
+
+  async fn setup() -> Arc<RedisPool> {
+    let _ = init_logging(); // Ignore error if already initialized
+    init_env().unwrap();
+    Arc::new(RedisPool::init().await.unwrap())
+  }
+
+  fn request(addr: SocketAddr) -> Request {
+    let mut request = Request::get("/status").body(Body::empty()).unwrap();
+    request.extensions_mut().insert(ConnectInfo(addr));
+    request
+  }
+
+  #[tokio::test]
+  async fn test_request_past_limit_is_rejected() {
+    let pool = setup().await;
+    let limiter = RateLimiter {
+      state: pool.clone().into(),
+      config: RateLimitConfig {
+        limit: 3,
+        window_secs: 3600,
+      },
+    };
+    let app = Router::new()
+      .route("/status", get(|| async {}))
+      .layer(middleware::from_fn_with_state(limiter, rate_limit));
+    let addr: SocketAddr = "203.0.113.49:40000".parse().unwrap();
+    let window = Utc::now().timestamp() as u64 / 3600;
+    pool
+      .del(&rate_limit_key("203.0.113.49", window))
+      .await
+      .unwrap();
+
+    for _ in 0..3 {
+      let response = app.clone().oneshot(request(addr)).await.unwrap();
+      assert_eq!(response.status(), 200);
+    }
+
+    let response = app.clone().oneshot(request(addr)).await.unwrap();
+    assert_eq!(response.status(), 429);
+    let retry_after: u64 = response.headers()[header::RETRY_AFTER]
+      .to_str()
+      .unwrap()
+      .parse()
+      .unwrap();
+    assert!(retry_after > 0 && retry_after <= 3600);
+
+    // Other clients have their own counter
+    let other: SocketAddr = "203.0.113.50:40000".parse().unwrap();
+    let response = app.oneshot(request(other)).await.unwrap();
+    assert_eq!(response.status(), 200);
+
+    for client in ["203.0.113.49", "203.0.113.50"] {
+      pool.del(&rate_limit_key(client, window)).await.unwrap();
+    }
+  }
+}
@@ -0,0 +1,147 @@
+//! Correlation ids for requests.
+//!
+//! Every request gets an `X-Request-Id`, taken from the incoming header when the client
+//! (or a proxy) supplied a sane one, otherwise generated. The id is echoed on the
+//! response, stored in the request extensions, and available to any code running in
+//! the request's task via `current()`, which is how `Error` responses include it.
+
+use axum::{
+  extract::Request,
+  http::{HeaderName, HeaderValue},
+  middleware::Next,
+  response::Response,
+};
+use rand::Rng;
+
+/// Header carrying the request id in both directions
+pub static REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest client-supplied id we accept before generating our own
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+  static CURRENT: String;
+}
+
+/// The id of the request being handled, stored in the request extensions
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// The id of the request handled by the current task, if any
+pub fn current() -> Option<String> {
+  CURRENT.try_with(|id| id.clone()).ok()
+}
+
+/// Run `f` as if handling the request with id `id`
+#[cfg(test)]
+pub fn with_request_id<R>(id: String, f: impl FnOnce() -> R) -> R {
+  CURRENT.sync_scope(id, f)
+}
+
+/// Middleware that assigns, propagates and echoes `X-Request-Id`
+pub async fn request_id(mut request: Request, next: Next) -> Response {
+  let id = request
+    .headers()
+    .get(&REQUEST_ID_HEADER)
+    .and_then(|value| value.to_str().ok())
+    .filter(|id| is_acceptable(id))
+    .map(str::to_string)
+    .unwrap_or_else(generate);
+
+  request.extensions_mut().insert(RequestId(id.clone()));
+  let mut response = CURRENT.scope(id.clone(), next.run(request)).await;
+
+  if let Ok(value) = HeaderValue::from_str(&id) {
+    response
+      .headers_mut()
+      .insert(REQUEST_ID_HEADER.clone(), value);
+  }
+  response
+}
+
+/// Accept client ids that are short and made of printable ASCII
+fn is_acceptable(id: &str) -> bool {
+  !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN && id.chars().all(|c| c.is_ascii_graphic())
+}
+
+fn generate() -> String {
+  format!("{:032x}", rand::rng().random::<u128>())
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+  use axum::{body::Body, middleware, routing::get, Router};
+  use http_body_util::BodyExt;
+  use serde_json::Value;
+  use tower::ServiceExt;
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  fn app() -> Router {
+    Router::new()
+      .route("/health_check", get(|| async {}))
+      .route(
+        "/fail",
+        get(|| async { Err::<(), _>(crate::http::Error::Forbidden) }),
+      )
+      .layer(middleware::from_fn(request_id))
+  }
+
+  #[tokio::test]
+  async fn test_provided_request_id_is_echoed() {
+    setup();
+    let response = app()
+      .oneshot(
+        Request::get("/health_check")
+          .header("x-request-id", "abc-123")
+          .body(Body::empty())
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+
+    assert_eq!(response.headers()["x-request-id"], "abc-123");
+  }
+
+  #[tokio::test]
+  async fn test_missing_or_bogus_request_id_is_generated() {
+    setup();
+    for header in [None, Some("has spaces"), Some("")] {
+      let mut request = Request::get("/health_check");
+      if let Some(header) = header {
+        request = request.header("x-request-id", header);
+      }
+      let response = app()
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+      let id = response.headers()["x-request-id"].to_str().unwrap();
+      assert_eq!(id.len(), 32);
+    }
+  }
+
+  #[tokio::test]
+  async fn test_error_body_includes_request_id() {
+    setup();
+    let response = app()
+      .oneshot(
+        Request::get("/fail")
+          .header("x-request-id", "trace-me")
+          .body(Body::empty())
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), 403);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"]["request_id"], "trace-me");
+  }
+}
@@ -0,0 +1,131 @@
+//! State shared by every route.
+//!
+//! Routes are registered once whether or not Redis is up. The pool lives in an
+//! `ArcSwapOption` so it can appear after a failed start, or be removed, at runtime;
+//! handlers take the `Redis` extractor, which answers `503 Service Unavailable` while
+//! there is no pool.
+//!
+//! Whether Redis last answered is kept in a shared flag, updated by `/readyz` and the
+//! background health monitor.
+//!
+//! Student routes take the `Students` extractor instead, and the status routes the
+//! state's `Cache`; both use the Redis pool unless the state was given another store.
+
+use arc_swap::ArcSwapOption;
+use axum::{extract::FromRequestParts, http::request::Parts};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::cache::Cache;
+use crate::http::Error;
+use crate::redis::RedisPool;
+use crate::student::StudentStore;
+
+/// Application state: the Redis pool, if one is currently available
+#[derive(Clone, Default)]
+pub struct AppState {
+  redis: Arc<ArcSwapOption<RedisPool>>,
+  redis_healthy: Arc<AtomicBool>,
+  students: Option<Arc<dyn StudentStore>>,
+  cache: Option<Arc<dyn Cache>>,
+}
+
+impl AppState {
+  pub fn new(redis_pool: Option<Arc<RedisPool>>) -> Self {
+    AppState {
+      redis_healthy: Arc::new(AtomicBool::new(redis_pool.is_some())),
+      redis: Arc::new(ArcSwapOption::new(redis_pool)),
+      students: None,
+      cache: None,
+    }
+  }
+
+  /// Serve student routes from `store` rather than the Redis pool
+  pub fn with_student_store(mut self, store: Arc<dyn StudentStore>) -> Self {
+    self.students = Some(store);
+    self
+  }
+
+  /// Keep the status routes' timestamp and counter in `cache` rather than the Redis pool
+  pub fn with_cache(mut self, cache: Arc<dyn Cache>) -> Self {
+    self.cache = Some(cache);
+    self
+  }
+
+  /// The cache the status routes use: the one set with `with_cache`, or else the
+  /// current Redis pool
+  pub fn cache(&self) -> Option<Arc<dyn Cache>> {
+    match &self.cache {
+      Some(cache) => Some(cache.clone()),
+      None => self.redis().map(|redis_pool| redis_pool as Arc<dyn Cache>),
+    }
+  }
+
+  /// The store student routes use: the one set with `with_student_store`, or else the
+  /// current Redis pool
+  pub fn student_store(&self) -> Option<Arc<dyn StudentStore>> {
+    match &self.students {
+      Some(store) => Some(store.clone()),
+      None => self
+        .redis()
+        .map(|redis_pool| redis_pool as Arc<dyn StudentStore>),
+    }
+  }
+
+  /// The current Redis pool, or `None` while the server runs without Redis
+  pub fn redis(&self) -> Option<Arc<RedisPool>> {
+    self.redis.load_full()
+  }
+
+  /// Replace the Redis pool seen by every route
+  ///
+  /// A new pool has just connected, so it starts out healthy.
+  pub fn set_redis(&self, redis_pool: Option<Arc<RedisPool>>) {
+    self.set_redis_healthy(redis_pool.is_some());
+    self.redis.store(redis_pool);
+  }
+
+  /// True if Redis answered the last health check
+  pub fn redis_healthy(&self) -> bool {
+    self.redis_healthy.load(Ordering::Relaxed)
+  }
+
+  /// Record the result of a health check, returning the previous one
+  pub fn set_redis_healthy(&self, healthy: bool) -> bool {
+    self.redis_healthy.swap(healthy, Ordering::Relaxed)
+  }
+}
+
+impl From<Arc<RedisPool>> for AppState {
+  fn from(redis_pool: Arc<RedisPool>) -> Self {
+    AppState::new(Some(redis_pool))
+  }
+}
+
+/// Extracts the current Redis pool, rejecting with `Error::ServiceUnavailable` if there is none
+pub struct Redis(pub Arc<RedisPool>);
+
+impl FromRequestParts<AppState> for Redis {
+  type Rejection = Error;
+
+  async fn from_request_parts(_parts: &mut Parts, state: &AppState) -> Result<Self, Error> {
+    state
+      .redis()
+      .map(Redis)
+      .ok_or_else(|| Error::ServiceUnavailable("Redis is not available".to_string()))
+  }
+}
+
+/// Extracts the student store, rejecting with `Error::ServiceUnavailable` if there is none
+pub struct Students(pub Arc<dyn StudentStore>);
+
+impl FromRequestParts<AppState> for Students {
+  type Rejection = Error;
+
+  async fn from_request_parts(_parts: &mut Parts, state: &AppState) -> Result<Self, Error> {
+    state
+      .student_store()
+      .map(Students)
+      .ok_or_else(|| Error::ServiceUnavailable("Redis is not available".to_string()))
+  }
+}
@@ -1,48 +1,80 @@
-use axum::{
-  extract::{Query, State},
-  response::Json,
-  routing::get,
-  Router,
-};
+use axum::{extract::State, http::StatusCode, routing::get, Router};
 use chrono::Utc;
 use log::{debug, info, warn};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::sync::Arc;
+use std::env;
+use utoipa::{IntoParams, ToSchema};
 
-use crate::http::Error;
-use crate::redis::{RedisOperations, RedisPool};
+use crate::http::{metrics, AppState, Error, ErrorBody, Json, ValidatedQuery};
 
-#[derive(Debug, Deserialize)]
+/// Redis key holding the time of the last successful `/redis/status` check
+const LAST_STATUS_CHECK_KEY: &str = "last_status_check";
+
+/// Redis counter of successful `/redis/status` checks
+const STATUS_HITS_KEY: &str = "status_hits";
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct StatusParams {
+  /// Respond with a simulated internal error
   error: Option<bool>,
+  /// Set to false to leave the `/redis/status` hit counter alone, for liveness probes
+  count: Option<bool>,
 }
 
-/// Create the base router without Redis functionality
-pub fn base_router() -> Router {
-  debug!("Setting up base status routes");
-  Router::new().route("/status", get(status))
+/// Seconds the hit counter lives after its first hit, from `STATUS_COUNTER_TTL`
+///
+/// Unset, the counter is never reset.
+fn status_counter_ttl() -> Option<u64> {
+  env::var("STATUS_COUNTER_TTL")
+    .ok()
+    .and_then(|ttl| ttl.parse::<u64>().ok())
+    .filter(|ttl| *ttl > 0)
 }
 
-/// Create a router with Redis state
-pub fn with_redis_router(redis_pool: Arc<RedisPool>) -> Router {
-  debug!("Setting up router with Redis support");
+/// Create a router with the status routes
+pub fn router(state: AppState) -> Router {
+  debug!("Setting up status routes");
   Router::new()
-    .route("/status", get(status))
-    .route("/redis/status", get(redis_status_handler))
-    .with_state(redis_pool)
+    .route("/status", get(status_handler))
+    .route("/redis/status", get(redis_status))
+    .route("/status/last", get(last_status_check))
+    .with_state(state)
 }
 
-/// Handler function with explicit Redis state
-async fn redis_status_handler(
-  query: Query<StatusParams>,
-  state: State<Arc<RedisPool>>,
+/// `/status`, with the pool's command counters and latest health check, or a note that
+/// the server is currently running without Redis
+async fn status_handler(
+  query: ValidatedQuery<StatusParams>,
+  State(state): State<AppState>,
 ) -> Result<Json<Value>, Error> {
-  redis_status(query, state).await
+  let Json(mut response) = status(query).await?;
+  match state.redis() {
+    Some(redis_pool) => {
+      response["redis_metrics"] = json!(redis_pool.metrics());
+      response["redis_healthy"] = json!(state.redis_healthy());
+    }
+    None => response["redis_status"] = json!("not_configured"),
+  }
+  Ok(Json(response))
 }
 
 // Using axum's Result type which works with IntoResponse
-pub async fn status(Query(params): Query<StatusParams>) -> Result<Json<Value>, Error> {
+#[utoipa::path(
+  get,
+  path = "/status",
+  tag = "status",
+  params(StatusParams),
+  responses(
+    (status = 200, description = "Server status and time", body = Value),
+    (status = 422, description = "Malformed query parameter", body = ErrorBody),
+    (status = 500, description = "Simulated error", body = ErrorBody),
+  )
+)]
+pub async fn status(
+  ValidatedQuery(params): ValidatedQuery<StatusParams>,
+) -> Result<Json<Value>, Error> {
   debug!("Status endpoint called with params: {:?}", params);
 
   // Simulate an error if requested via query param
@@ -67,10 +99,13 @@ pub async fn status(Query(params): Query<StatusParams>) -> Result<Json<Value>, E
 }
 
 /// Status endpoint that also checks Redis connection
+///
+/// Responds `503 Service Unavailable` with `"redis_status": "disconnected"` when the
+/// state's cache doesn't answer a ping, or `"not_configured"` while there is none.
 pub async fn redis_status(
-  Query(params): Query<StatusParams>,
-  State(redis_pool): State<Arc<RedisPool>>,
-) -> Result<Json<Value>, Error> {
+  ValidatedQuery(params): ValidatedQuery<StatusParams>,
+  State(state): State<AppState>,
+) -> Result<(StatusCode, Json<Value>), Error> {
   debug!("Redis status endpoint called with params: {:?}", params);
 
   // Simulate an error if requested via query param
@@ -84,14 +119,41 @@ pub async fn redis_status(
   }
 
   let timestamp = Utc::now().to_rfc3339();
+
+  let Some(cache) = state.cache() else {
+    let response = json!({
+        "status": "degraded",
+        "redis_status": "not_configured",
+        "timestamp": timestamp
+    });
+    return Ok((StatusCode::SERVICE_UNAVAILABLE, Json(response)));
+  };
+
+  if let Err(e) = cache.ping().await {
+    warn!("Redis status check failed at {}: {}", timestamp, e);
+    metrics::set_redis_up(false);
+    let response = json!({
+        "status": "degraded",
+        "redis_status": "disconnected",
+        "timestamp": timestamp
+    });
+    return Ok((StatusCode::SERVICE_UNAVAILABLE, Json(response)));
+  }
+
+  metrics::set_redis_up(true);
+
   // Store the current timestamp in Redis
-  let key = "last_status_check";
-  redis_pool.set(key, &timestamp).await?;
+  cache.set(LAST_STATUS_CHECK_KEY, &timestamp).await?;
 
-  // Retrieve and increment the hit counter
-  let hits: i64 = redis_pool
-    .execute_command(&mut redis::cmd("INCR").arg("status_hits"))
-    .await?;
+  // Retrieve and increment the hit counter, starting a new window if it had expired
+  let hits = if params.count.unwrap_or(true) {
+    match status_counter_ttl() {
+      Some(ttl) => cache.incr_with_expiry(STATUS_HITS_KEY, ttl).await?,
+      None => cache.incr(STATUS_HITS_KEY).await?,
+    }
+  } else {
+    parse_hits(cache.get(STATUS_HITS_KEY).await?)?
+  };
 
   info!(
     "Redis status check successful at {} (hit count: {})",
@@ -105,5 +167,310 @@ pub async fn redis_status(
       "hit_count": hits
   });
 
-  Ok(Json(response))
+  Ok((StatusCode::OK, Json(response)))
+}
+
+/// When `/redis/status` last succeeded, and how many times it has
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LastStatusCheck {
+  /// RFC 3339 timestamp of the last successful check
+  pub last_status_check: String,
+  pub status_hits: i64,
+}
+
+/// The last successful Redis self-check, or 404 if there hasn't been one
+#[utoipa::path(
+  get,
+  path = "/status/last",
+  tag = "status",
+  responses(
+    (status = 200, description = "The last successful self-check", body = LastStatusCheck),
+    (status = 404, description = "No self-check has succeeded yet", body = ErrorBody),
+    (status = 503, description = "Redis is not available", body = ErrorBody),
+  )
+)]
+pub async fn last_status_check(
+  State(state): State<AppState>,
+) -> Result<Json<LastStatusCheck>, Error> {
+  let cache = state
+    .cache()
+    .ok_or_else(|| Error::ServiceUnavailable("Redis is not available".to_string()))?;
+  let last_status_check = cache
+    .get(LAST_STATUS_CHECK_KEY)
+    .await?
+    .ok_or(Error::NotFound)?;
+  let status_hits = parse_hits(cache.get(STATUS_HITS_KEY).await?)?;
+
+  Ok(Json(LastStatusCheck {
+    last_status_check,
+    status_hits,
+  }))
+}
+
+/// The hit counter's value, 0 if it isn't set
+fn parse_hits(hits: Option<String>) -> Result<i64, Error> {
+  hits.map_or(Ok(0), |hits| {
+    hits
+      .parse()
+      .map_err(|_| Error::RedisParseError(format!("Invalid status hit count {:?}", hits)))
+  })
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::cache::Cache;
+  use crate::init_logging;
+  use crate::redis::{RedisConfig, RedisPool};
+  use axum::{body::Body, http::Request};
+  use http_body_util::BodyExt;
+  use std::collections::HashMap;
+  use std::sync::{Arc, Mutex};
+  use tower::ServiceExt;
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  /// A cache in a map, which can be switched off to fail like an unreachable Redis
+  #[derive(Default)]
+  struct MockCache {
+    down: bool,
+    values: Mutex<HashMap<String, String>>,
+  }
+
+  #[async_trait::async_trait]
+  impl Cache for MockCache {
+    async fn ping(&self) -> Result<(), Error> {
+      if self.down {
+        return Err(Error::RedisConnection("mock cache is down".to_string()));
+      }
+      Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<String>, Error> {
+      Ok(self.values.lock().unwrap().get(key).cloned())
+    }
+
+    async fn set(&self, key: &str, value: &str) -> Result<(), Error> {
+      self
+        .values
+        .lock()
+        .unwrap()
+        .insert(key.to_string(), value.to_string());
+      Ok(())
+    }
+
+    async fn incr(&self, key: &str) -> Result<i64, Error> {
+      let mut values = self.values.lock().unwrap();
+      let value = values
+        .entry(key.to_string())
+        .or_insert_with(|| "0".to_string());
+      let hits = value.parse::<i64>().unwrap() + 1;
+      *value = hits.to_string();
+      Ok(hits)
+    }
+
+    async fn expire(&self, _key: &str, _seconds: u64) -> Result<bool, Error> {
+      Ok(true)
+    }
+
+    async fn incr_with_expiry(&self, key: &str, _seconds: u64) -> Result<i64, Error> {
+      self.incr(key).await
+    }
+  }
+
+  async fn get_status(app: Router, uri: &str) -> (StatusCode, Value) {
+    let response = app
+      .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+      .await
+      .unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap())
+  }
+
+  #[tokio::test]
+  async fn test_status_without_redis_is_not_configured() {
+    setup();
+    let (status, body) = get_status(router(AppState::default()), "/status").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "ok");
+    assert_eq!(body["redis_status"], "not_configured");
+  }
+
+  #[tokio::test]
+  async fn test_malformed_error_param_is_422() {
+    setup();
+    for uri in ["/status?error=maybe", "/redis/status?error=maybe"] {
+      let (status, body) = get_status(router(AppState::default()), uri).await;
+
+      assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", uri);
+      assert_eq!(body["error"]["code"], "validation_failed");
+      assert!(body["error"]["fields"]["error"].is_array(), "{}", body);
+    }
+  }
+
+  #[tokio::test]
+  async fn test_redis_status_reports_disconnected() {
+    setup();
+    let config = RedisConfig {
+      url: "redis://127.0.0.1:1".to_string(),
+      ..RedisConfig::default()
+    };
+    let pool = Arc::new(RedisPool::new(config).unwrap());
+    let (status, body) = get_status(router(pool.into()), "/redis/status").await;
+
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["status"], "degraded");
+    assert_eq!(body["redis_status"], "disconnected");
+    assert!(body.get("hit_count").is_none());
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_redis_status_reports_connected() {
+    setup();
+    crate::init_env().unwrap();
+    let pool = Arc::new(RedisPool::init().await.unwrap());
+    let (status, body) = get_status(router(pool.into()), "/redis/status").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "ok");
+    assert_eq!(body["redis_status"], "connected");
+    assert!(body["hit_count"].is_i64());
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_last_status_check_after_redis_status() {
+    setup();
+    crate::init_env().unwrap();
+    // A private namespace, so the keys start out unset
+    let pool = Arc::new(
+      RedisPool::new(RedisConfig {
+        key_prefix: Some("test-status-last".to_string()),
+        ..RedisConfig::default()
+      })
+      .unwrap(),
+    );
+    pool.delete_prefix("").await.unwrap();
+    let app = router(pool.clone().into());
+
+    let (status, body) = get_status(app.clone(), "/status/last").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"]["code"], "not_found");
+
+    let (_, checked) = get_status(app.clone(), "/redis/status").await;
+    let (status, body) = get_status(app, "/status/last").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["last_status_check"], checked["timestamp"]);
+    assert_eq!(body["status_hits"], 1);
+    pool.delete_prefix("").await.unwrap();
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_count_false_leaves_counter_unchanged() {
+    setup();
+    crate::init_env().unwrap();
+    let pool = Arc::new(
+      RedisPool::new(RedisConfig {
+        key_prefix: Some("test-status-count".to_string()),
+        ..RedisConfig::default()
+      })
+      .unwrap(),
+    );
+    pool.delete_prefix("").await.unwrap();
+    let app = router(pool.clone().into());
+
+    let (_, body) = get_status(app.clone(), "/redis/status").await;
+    assert_eq!(body["hit_count"], 1);
+    for _ in 0..3 {
+      let (status, body) = get_status(app.clone(), "/redis/status?count=false").await;
+      assert_eq!(status, StatusCode::OK);
+      assert_eq!(body["hit_count"], 1);
+    }
+    let hits: i64 = crate::redis::RedisOperations::get(&*pool, STATUS_HITS_KEY)
+      .await
+      .unwrap();
+    assert_eq!(hits, 1);
+    pool.delete_prefix("").await.unwrap();
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_incr_with_expiry_repairs_counter_without_ttl() {
+    setup();
+    crate::init_env().unwrap();
+    let pool = RedisPool::new(RedisConfig {
+      key_prefix: Some("test-status-ttl".to_string()),
+      ..RedisConfig::default()
+    })
+    .unwrap();
+    pool.delete_prefix("").await.unwrap();
+
+    // A counter left without an expiry, e.g. by a failed EXPIRE after the first INCR
+    Cache::incr(&pool, STATUS_HITS_KEY).await.unwrap();
+    assert_eq!(
+      crate::redis::RedisOperations::ttl(&pool, STATUS_HITS_KEY)
+        .await
+        .unwrap(),
+      -1
+    );
+
+    let hits = pool.incr_with_expiry(STATUS_HITS_KEY, 60).await.unwrap();
+    assert_eq!(hits, 2);
+    let ttl = crate::redis::RedisOperations::ttl(&pool, STATUS_HITS_KEY)
+      .await
+      .unwrap();
+    assert!(ttl > 0 && ttl <= 60);
+
+    // An existing expiry is not pushed back by later hits
+    pool.incr_with_expiry(STATUS_HITS_KEY, 3600).await.unwrap();
+    let ttl = crate::redis::RedisOperations::ttl(&pool, STATUS_HITS_KEY)
+      .await
+      .unwrap();
+    assert!(ttl <= 60);
+    pool.delete_prefix("").await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_last_status_check_without_redis_is_503() {
+    setup();
+    let (status, _) = get_status(router(AppState::default()), "/status/last").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+  }
+
+  #[tokio::test]
+  async fn test_status_routes_use_mock_cache() {
+    setup();
+    let cache = Arc::new(MockCache::default());
+    let app = router(AppState::default().with_cache(cache.clone()));
+
+    let (status, _) = get_status(app.clone(), "/status/last").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, body) = get_status(app.clone(), "/redis/status").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["redis_status"], "connected");
+    assert_eq!(body["hit_count"], 1);
+    let (_, body) = get_status(app.clone(), "/redis/status?count=false").await;
+    assert_eq!(body["hit_count"], 1);
+
+    let (status, last) = get_status(app, "/status/last").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(last["last_status_check"], body["timestamp"]);
+    assert_eq!(last["status_hits"], 1);
+
+    let down = AppState::default().with_cache(Arc::new(MockCache {
+      down: true,
+      ..MockCache::default()
+    }));
+    let (status, body) = get_status(router(down), "/redis/status").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["redis_status"], "disconnected");
+  }
 }
@@ -0,0 +1,1192 @@
+use axum::{
+  extract::{FromRequest, Path, Request},
+  http::{header, HeaderMap, StatusCode},
+  routing::{get, post},
+  Extension, Router,
+};
+use log::{debug, info};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::http::{
+  AppState, Authenticated, Cursor, CursorKind, Error, ErrorBody, Json, Query, Students,
+};
+use crate::student::store::{ChangedSince, StudentPage};
+use crate::student::{
+  parse_csv, Accommodation, AccommodationNeeds, CreateStudentRequest, Grade, ImportRow,
+  PublicStudent, Student, StudentId,
+};
+
+/// Default number of students per page
+const DEFAULT_PAGE_LIMIT: usize = 25;
+
+/// Largest page a client may request
+const MAX_PAGE_LIMIT: usize = 100;
+
+/// Content type of an RFC 6902 JSON Patch body
+const JSON_PATCH_CONTENT_TYPE: &str = "application/json-patch+json";
+
+/// Fields a JSON Patch may replace, by path
+const PATCHABLE_PATHS: [&str; 3] = ["/email", "/grade", "/accommodations"];
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListParams {
+  /// Page size, defaulting to 25 and capped at 100
+  limit: Option<usize>,
+  /// `next_cursor` from the previous page
+  cursor: Option<String>,
+  /// Only list students in this grade
+  grade: Option<Grade>,
+}
+
+impl ListParams {
+  /// Requested page size, clamped to `MAX_PAGE_LIMIT`
+  fn limit(&self) -> usize {
+    self.limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT)
+  }
+}
+
+/// Request body for a partial student update. Absent fields are left unchanged; the
+/// nullable fields are cleared when sent as `null`.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct UpdateStudent {
+  email: Option<String>,
+  grade: Option<Grade>,
+  graduation_year: Option<u16>,
+  #[serde(default, deserialize_with = "present")]
+  special_accommodations: Option<Option<String>>,
+  #[serde(default, deserialize_with = "present")]
+  accommodation: Option<Option<AccommodationNeeds>>,
+  /// Replaces the list and rewrites `special_accommodations` to match
+  accommodations: Option<Vec<Accommodation>>,
+  /// `false` deactivates a student who has left, `true` reactivates them
+  active: Option<bool>,
+}
+
+/// One RFC 6902 JSON Patch operation. Only `replace` on `PATCHABLE_PATHS` is supported.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PatchOperation {
+  op: String,
+  path: String,
+  #[serde(default)]
+  value: Value,
+}
+
+/// Body of `PATCH /students/{id}`: a JSON Patch when sent as
+/// `application/json-patch+json`, otherwise a merge patch
+#[derive(Debug)]
+pub enum StudentPatch {
+  Merge(UpdateStudent),
+  JsonPatch(Vec<PatchOperation>),
+}
+
+impl<S: Send + Sync> FromRequest<S> for StudentPatch {
+  type Rejection = Error;
+
+  async fn from_request(req: Request, state: &S) -> Result<Self, Error> {
+    let json_patch = req
+      .headers()
+      .get(header::CONTENT_TYPE)
+      .and_then(|value| value.to_str().ok())
+      .and_then(|value| value.split(';').next())
+      .is_some_and(|mime| mime.trim().eq_ignore_ascii_case(JSON_PATCH_CONTENT_TYPE));
+
+    if json_patch {
+      let Json(operations) = Json::from_request(req, state).await?;
+      Ok(Self::JsonPatch(operations))
+    } else {
+      let Json(update) = Json::from_request(req, state).await?;
+      Ok(Self::Merge(update))
+    }
+  }
+}
+
+impl StudentPatch {
+  /// The patch as an `UpdateStudent`
+  ///
+  /// JSON Patch operations are applied in order, so a later replace of the same path
+  /// wins. Every unsupported operation or path is reported at once, keyed by its path.
+  fn into_update(self) -> Result<UpdateStudent, Error> {
+    let operations = match self {
+      Self::Merge(update) => return Ok(update),
+      Self::JsonPatch(operations) => operations,
+    };
+
+    let mut errors = Vec::new();
+    let mut fields = Map::new();
+    for operation in operations {
+      if !PATCHABLE_PATHS.contains(&operation.path.as_str()) {
+        errors.push((operation.path, "cannot be patched".to_string()));
+      } else if operation.op != "replace" {
+        errors.push((
+          operation.path,
+          format!("supports only replace, not {}", operation.op),
+        ));
+      } else {
+        fields.insert(operation.path[1..].to_string(), operation.value);
+      }
+    }
+    if !errors.is_empty() {
+      return Err(Error::unprocessable_entity(errors));
+    }
+
+    serde_path_to_error::deserialize(Value::Object(fields))
+      .map_err(|e| Error::unprocessable_entity([(format!("/{}", e.path()), e.inner().to_string())]))
+  }
+}
+
+/// Distinguish an explicit `null` (`Some(None)`) from an absent field (`None`)
+fn present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+  D: Deserializer<'de>,
+  T: Deserialize<'de>,
+{
+  Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// A student as shown to the caller: in full with an API key, otherwise the public view
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum StudentView {
+  Full(Student),
+  Public(PublicStudent),
+}
+
+impl StudentView {
+  fn new(student: Student, authenticated: bool) -> Self {
+    if authenticated {
+      StudentView::Full(student)
+    } else {
+      StudentView::Public(student.to_public())
+    }
+  }
+}
+
+/// One page of the roster as shown to the caller, see `StudentPage`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StudentViewPage {
+  students: Vec<StudentView>,
+  next_cursor: Option<String>,
+  total_estimate: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchParams {
+  /// Part of a first or last name, matched ignoring case
+  q: String,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ChangedSinceParams {
+  /// Epoch millis watermark from the previous poll (0 for a full sync)
+  ts: i64,
+}
+
+/// Create a router with the student routes
+pub fn router(state: AppState) -> Router {
+  debug!("Setting up student routes");
+  Router::new()
+    .route("/students", get(list_students).post(create_student))
+    .route("/students/changed-since", get(changed_since))
+    .route("/students/import", post(import_students))
+    .route("/students/search", get(search_students))
+    .route(
+      "/students/{id}",
+      get(get_student)
+        .patch(update_student)
+        .delete(delete_student),
+    )
+    .with_state(state)
+}
+
+/// Browse the roster a page at a time, showing only public fields without an API key
+#[utoipa::path(
+  get,
+  path = "/students",
+  tag = "students",
+  params(ListParams),
+  responses(
+    (status = 200, description = "One page of students", body = StudentViewPage),
+    (status = 400, description = "The cursor is invalid or for another grade", body = ErrorBody),
+    (status = 422, description = "Invalid paging parameters", body = ErrorBody),
+  )
+)]
+pub async fn list_students(
+  Query(params): Query<ListParams>,
+  Students(students): Students,
+  authenticated: Option<Extension<Authenticated>>,
+) -> Result<Json<StudentViewPage>, Error> {
+  debug!("List students endpoint called with params: {:?}", params);
+
+  let mut errors = Vec::new();
+  if params.limit == Some(0) {
+    errors.push(("limit", "must be at least 1"));
+  }
+  if params.grade.is_some_and(|grade| !(9..=12).contains(&grade)) {
+    errors.push(("grade", "must be between 9 and 12"));
+  }
+  if !errors.is_empty() {
+    return Err(Error::unprocessable_entity(errors));
+  }
+  let cursor = match &params.cursor {
+    Some(cursor) => {
+      let cursor = Cursor::decode(cursor, CursorKind::AfterStudent, params.grade)?;
+      // Student ids are six digits, so the last id returned fits in the cursor
+      let id = StudentId::new(format!("{:06}", cursor.position))
+        .map_err(|_| Error::BadRequest("invalid cursor".to_string()))?;
+      Some(id)
+    }
+    None => None,
+  };
+
+  let StudentPage {
+    students,
+    next_cursor,
+    total_estimate,
+  } = students
+    .list_page(cursor.as_ref(), params.limit(), params.grade)
+    .await?;
+  let next_cursor = next_cursor
+    .and_then(|id| id.parse().ok())
+    .map(|id| Cursor::new(CursorKind::AfterStudent, id, params.grade).encode());
+
+  Ok(Json(StudentViewPage {
+    students: students
+      .into_iter()
+      .map(|student| StudentView::new(student, authenticated.is_some()))
+      .collect(),
+    next_cursor,
+    total_estimate,
+  }))
+}
+
+/// Validate and store a new student
+#[utoipa::path(
+  post,
+  path = "/students",
+  tag = "students",
+  request_body = CreateStudentRequest,
+  security(("api_key" = [])),
+  responses(
+    (status = 201, description = "Student created", body = Student),
+    (status = 409, description = "A student with this id already exists", body = ErrorBody),
+    (status = 422, description = "Invalid student fields", body = ErrorBody),
+  )
+)]
+pub async fn create_student(
+  Students(students): Students,
+  Json(payload): Json<CreateStudentRequest>,
+) -> Result<(StatusCode, Json<Student>), Error> {
+  let student = Student::try_from(payload)?;
+
+  students.create(&student).await?;
+  info!("Created student {}", student.id.to_string());
+
+  Ok((StatusCode::CREATED, Json(student)))
+}
+
+/// Per-row results of a CSV import
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ImportSummary {
+  created: usize,
+  failed: usize,
+  rows: Vec<ImportRow>,
+}
+
+/// Import a roster from a CSV body with columns
+/// `id,first_name,last_name,email,grade,graduation_year,accommodations`
+///
+/// Rows that fail validation or reuse a taken email are reported and skipped; the rest
+/// are stored.
+#[utoipa::path(
+  post,
+  path = "/students/import",
+  tag = "students",
+  request_body(content = String, content_type = "text/csv"),
+  security(("api_key" = [])),
+  responses((status = 200, description = "Per-row import results", body = ImportSummary))
+)]
+pub async fn import_students(
+  Students(store): Students,
+  body: String,
+) -> Result<Json<ImportSummary>, Error> {
+  let (students, mut rows) = parse_csv(&body);
+
+  let mut created = 0;
+  let outcomes = store.save_many(&students).await?;
+  for (student, conflict) in students.iter().zip(outcomes) {
+    match conflict {
+      None => created += 1,
+      // A taken email fails the row, not the whole import
+      Some(reason) => {
+        let id = student.id.to_string();
+        for row in rows.iter_mut() {
+          if let ImportRow::Created {
+            row: number,
+            id: row_id,
+          } = row
+          {
+            if *row_id == id {
+              *row = ImportRow::Invalid {
+                row: *number,
+                errors: HashMap::from([("email".into(), vec![reason.into()])]),
+              };
+              break;
+            }
+          }
+        }
+      }
+    }
+  }
+
+  let summary = ImportSummary {
+    created,
+    failed: rows.len() - created,
+    rows,
+  };
+  info!(
+    "Imported {} students ({} rows failed validation)",
+    summary.created, summary.failed
+  );
+
+  Ok(Json(summary))
+}
+
+/// Fetch a single student by id, showing only public fields without an API key
+#[utoipa::path(
+  get,
+  path = "/students/{id}",
+  tag = "students",
+  params(("id" = StudentId, Path, description = "6-digit student id")),
+  responses(
+    (status = 200, description = "The student", body = StudentView),
+    (status = 404, description = "No such student", body = ErrorBody),
+  )
+)]
+pub async fn get_student(
+  Path(id): Path<String>,
+  Students(students): Students,
+  authenticated: Option<Extension<Authenticated>>,
+) -> Result<Json<StudentView>, Error> {
+  let id = StudentId::new(id)?;
+  let student = students.load(&id).await?.ok_or(Error::NotFound)?;
+
+  Ok(Json(StudentView::new(student, authenticated.is_some())))
+}
+
+/// Apply a partial update to a student, reporting every invalid field at once
+///
+/// The body is a merge patch, or an RFC 6902 JSON Patch of `replace` operations on
+/// `/email`, `/grade` or `/accommodations` when sent as `application/json-patch+json`.
+#[utoipa::path(
+  patch,
+  path = "/students/{id}",
+  tag = "students",
+  params(("id" = StudentId, Path, description = "6-digit student id")),
+  request_body(content(
+    (UpdateStudent = "application/merge-patch+json"),
+    (Vec<PatchOperation> = "application/json-patch+json"),
+  )),
+  security(("api_key" = [])),
+  responses(
+    (status = 200, description = "The updated student", body = Student),
+    (status = 404, description = "No such student", body = ErrorBody),
+    (status = 409, description = "Stale `If-Match` version or taken email", body = ErrorBody),
+    (status = 422, description = "Invalid student fields", body = ErrorBody),
+  )
+)]
+pub async fn update_student(
+  Path(id): Path<String>,
+  Students(students): Students,
+  headers: HeaderMap,
+  patch: StudentPatch,
+) -> Result<Json<Student>, Error> {
+  let id = StudentId::new(id)?;
+  let expected_version = if_match_version(&headers)?;
+  let update = patch.into_update()?;
+  let mut student = students.load(&id).await?.ok_or(Error::NotFound)?;
+
+  if let Some(expected) = expected_version.filter(|expected| *expected != student.version) {
+    return Err(Error::Conflict(format!(
+      "student {} is at version {}, not {}",
+      id.to_string(),
+      student.version,
+      expected
+    )));
+  }
+
+  let original = student.clone();
+  apply_update(&mut student, update)?;
+
+  // A no-op update keeps the stored record and its `updated_at` as they were
+  let changed = student.diff(&original);
+  if changed.is_empty() {
+    debug!("Update of student {} changed nothing", id.to_string());
+    return Ok(Json(original));
+  }
+
+  // Recheck the version as part of the write, in case another update landed since the load
+  match expected_version {
+    Some(expected) => students.save_if_version(&student, expected).await?,
+    None => students.save(&student).await?,
+  }
+  info!(
+    "Updated student {} ({})",
+    id.to_string(),
+    changed.join(", ")
+  );
+
+  Ok(Json(student))
+}
+
+/// The student version a client expects, from an `If-Match` header like `"3"`
+///
+/// Returns `Ok(None)` when there is no header or it is `*`, so the update is unconditional.
+fn if_match_version(headers: &HeaderMap) -> Result<Option<u64>, Error> {
+  let Some(value) = headers.get(header::IF_MATCH) else {
+    return Ok(None);
+  };
+  let value = value.to_str().unwrap_or_default().trim();
+  if value == "*" {
+    return Ok(None);
+  }
+
+  let version = value.strip_prefix("W/").unwrap_or(value).trim_matches('"');
+  version
+    .parse()
+    .map(Some)
+    .map_err(|_| Error::unprocessable_entity([("If-Match", "must be a student version")]))
+}
+
+/// Apply each field of `update` with the matching `Student::update_*` method
+fn apply_update(student: &mut Student, update: UpdateStudent) -> Result<(), Error> {
+  let mut results = Vec::new();
+  if let Some(email) = update.email {
+    results.push(student.update_email(email));
+  }
+  if let Some(grade) = update.grade {
+    results.push(student.update_grade(grade));
+  }
+  if let Some(graduation_year) = update.graduation_year {
+    results.push(student.update_graduation_year(graduation_year));
+  }
+  if let Some(accommodations) = update.special_accommodations {
+    results.push(student.update_special_accommodations(accommodations));
+  }
+  if let Some(accommodation) = update.accommodation {
+    results.push(student.update_accommodation(accommodation));
+  }
+  if let Some(accommodations) = update.accommodations {
+    results.push(student.update_accommodations(accommodations));
+  }
+  match update.active {
+    Some(true) if !student.active => student.reactivate(),
+    Some(false) if student.active => student.deactivate(),
+    _ => {}
+  }
+
+  // Merge the field errors so the client sees every problem in one response
+  let mut errors = HashMap::new();
+  for result in results {
+    match result {
+      Ok(()) => {}
+      Err(Error::UnprocessableEntity {
+        errors: field_errors,
+      }) => errors.extend(field_errors),
+      Err(e) => return Err(e),
+    }
+  }
+  if !errors.is_empty() {
+    return Err(Error::UnprocessableEntity { errors });
+  }
+
+  Ok(())
+}
+
+/// Delete a student
+#[utoipa::path(
+  delete,
+  path = "/students/{id}",
+  tag = "students",
+  params(("id" = StudentId, Path, description = "6-digit student id")),
+  security(("api_key" = [])),
+  responses(
+    (status = 204, description = "Student deleted"),
+    (status = 404, description = "No such student", body = ErrorBody),
+  )
+)]
+pub async fn delete_student(
+  Path(id): Path<String>,
+  Students(students): Students,
+) -> Result<StatusCode, Error> {
+  let id = StudentId::new(id)?;
+  if students.load(&id).await?.is_none() {
+    return Err(Error::NotFound);
+  }
+
+  students.delete(&id).await?;
+  info!("Deleted student {}", id.to_string());
+
+  Ok(StatusCode::NO_CONTENT)
+}
+
+/// Look students up by partial name, best matches first
+#[utoipa::path(
+  get,
+  path = "/students/search",
+  tag = "students",
+  params(SearchParams),
+  responses(
+    (status = 200, description = "Matching students", body = Vec<PublicStudent>),
+    (status = 422, description = "Empty query", body = ErrorBody),
+  )
+)]
+pub async fn search_students(
+  Query(params): Query<SearchParams>,
+  Students(students): Students,
+) -> Result<Json<Vec<PublicStudent>>, Error> {
+  if params.q.trim().is_empty() {
+    return Err(Error::unprocessable_entity([("q", "cannot be empty")]));
+  }
+
+  Ok(Json(students.search(&params.q).await?))
+}
+
+/// Students updated or deleted since the given watermark, for syncing clients
+///
+/// Sync clients need full records, so this requires an API key.
+#[utoipa::path(
+  get,
+  path = "/students/changed-since",
+  tag = "students",
+  params(ChangedSinceParams),
+  security(("api_key" = [])),
+  responses(
+    (status = 200, description = "Changes since the watermark", body = ChangedSince),
+    (status = 422, description = "Invalid watermark", body = ErrorBody),
+  )
+)]
+pub async fn changed_since(
+  Query(params): Query<ChangedSinceParams>,
+  Students(students): Students,
+  authenticated: Option<Extension<Authenticated>>,
+) -> Result<Json<ChangedSince>, Error> {
+  debug!("Changed-since endpoint called with params: {:?}", params);
+
+  if authenticated.is_none() {
+    return Err(Error::Unauthorized);
+  }
+
+  if params.ts < 0 {
+    return Err(Error::unprocessable_entity([("ts", "cannot be negative")]));
+  }
+
+  let changes = students.changed_since(params.ts).await?;
+  debug!(
+    "{} students changed and {} deleted since {}",
+    changes.students.len(),
+    changes.deleted.len(),
+    params.ts
+  );
+
+  Ok(Json(changes))
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+  use crate::redis::{RedisConfig, RedisPool};
+  use crate::student::academic_year::DEFAULT_START_MONTH;
+  #[cfg(feature = "redis-tests")]
+  use crate::student::store;
+  use crate::student::{AcademicYear, InMemoryStore, StudentStore};
+  use axum::{body::Body, http::Request};
+  use chrono::Utc;
+  use http_body_util::BodyExt;
+  use serde_json::{json, Value};
+  use std::sync::Arc;
+  use tower::ServiceExt;
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  fn student_json(id: &str, grade: Grade) -> Value {
+    json!({
+      "id": id,
+      "first_name": "Casey",
+      "last_name": "Jones",
+      "email": format!("{}@csxlabs.edu", id),
+      "grade": grade,
+      "graduation_year": AcademicYear::current(Utc::now(), DEFAULT_START_MONTH).graduation_year_for(grade),
+    })
+  }
+
+  fn json_request(method: &str, uri: &str, body: Value) -> Request<Body> {
+    Request::builder()
+      .method(method)
+      .uri(uri)
+      .header("content-type", "application/json")
+      .body(Body::from(body.to_string()))
+      .unwrap()
+  }
+
+  async fn body_json(response: axum::response::Response) -> Value {
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&body).unwrap()
+  }
+
+  #[tokio::test]
+  async fn test_student_routes_run_on_in_memory_store() {
+    setup();
+    let state =
+      AppState::default().with_student_store(Arc::new(crate::student::InMemoryStore::new()));
+    let app = router(state);
+
+    let response = app
+      .clone()
+      .oneshot(json_request(
+        "POST",
+        "/students",
+        student_json("980010", 11),
+      ))
+      .await
+      .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let response = app
+      .clone()
+      .oneshot(json_request(
+        "POST",
+        "/students",
+        student_json("980010", 11),
+      ))
+      .await
+      .unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let response = app
+      .clone()
+      .oneshot(
+        Request::get("/students/980010")
+          .body(Body::empty())
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body_json(response).await["full_name"], "Casey Jones");
+
+    let response = app
+      .clone()
+      .oneshot(
+        Request::delete("/students/980010")
+          .body(Body::empty())
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = app
+      .oneshot(
+        Request::get("/students/980010")
+          .body(Body::empty())
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+  }
+
+  #[tokio::test]
+  async fn test_create_student_invalid_returns_422() {
+    setup();
+    // Validation fails before Redis is touched, so an unconnected pool is enough
+    let pool = Arc::new(RedisPool::new(RedisConfig::default()).unwrap());
+
+    let response = router(pool.into())
+      .oneshot(json_request("POST", "/students", student_json("12345", 13)))
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), 422);
+    let body = body_json(response).await;
+    assert_eq!(body["error"]["code"], "validation_failed");
+    assert!(body["error"]["fields"]["id"].is_array());
+    assert!(body["error"]["fields"]["grade"].is_array());
+  }
+
+  #[tokio::test]
+  async fn test_create_student_empty_body_lists_required_fields() {
+    setup();
+    let pool = Arc::new(RedisPool::new(RedisConfig::default()).unwrap());
+
+    let response = router(pool.into())
+      .oneshot(json_request("POST", "/students", json!({})))
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), 422);
+    let body = body_json(response).await;
+    for field in [
+      "id",
+      "first_name",
+      "last_name",
+      "email",
+      "grade",
+      "graduation_year",
+    ] {
+      assert_eq!(body["error"]["fields"][field], json!(["is required"]));
+    }
+  }
+
+  #[test]
+  fn test_list_limit_clamping() {
+    setup();
+    let params = |limit| ListParams {
+      limit,
+      ..Default::default()
+    };
+    assert_eq!(params(None).limit(), 25);
+    assert_eq!(params(Some(10)).limit(), 10);
+    assert_eq!(params(Some(500)).limit(), 100);
+  }
+
+  #[tokio::test]
+  async fn test_list_students_invalid_params_returns_422() {
+    setup();
+    let pool = Arc::new(RedisPool::new(RedisConfig::default()).unwrap());
+
+    let response = router(pool.into())
+      .oneshot(
+        Request::get("/students?limit=0&grade=13&cursor=abc")
+          .body(Body::empty())
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), 422);
+    let body = body_json(response).await;
+    for field in ["limit", "grade"] {
+      assert!(
+        body["error"]["fields"][field].is_array(),
+        "missing {} error",
+        field
+      );
+    }
+  }
+
+  #[tokio::test]
+  async fn test_list_students_invalid_cursor_returns_400() {
+    setup();
+    let pool = Arc::new(RedisPool::new(RedisConfig::default()).unwrap());
+    let app = router(pool.into());
+    let grade_9 = Cursor::new(CursorKind::AfterStudent, 900001, Some(9)).encode();
+    let export = Cursor::new(CursorKind::RowOffset, 1, None).encode();
+
+    for uri in [
+      "/students?cursor=abc".to_string(),
+      format!("/students?grade=10&cursor={}", grade_9),
+      format!("/students?cursor={}", export),
+    ] {
+      let response = app
+        .clone()
+        .oneshot(Request::get(&uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+      assert_eq!(response.status(), 400, "{}", uri);
+      assert_eq!(body_json(response).await["error"]["code"], "bad_request");
+    }
+  }
+
+  #[test]
+  fn test_apply_update_collects_errors() {
+    setup();
+    let mut student: Student = serde_json::from_value(json!({
+      "id": "920000",
+      "first_name": "Casey",
+      "last_name": "Jones",
+      "email": "casey@csxlabs.edu",
+      "grade": 10,
+      "graduation_year": 2030,
+      "special_accommodations": "bottom row",
+      "created_at": Utc::now(),
+      "updated_at": Utc::now(),
+    }))
+    .unwrap();
+
+    let update: UpdateStudent =
+      serde_json::from_value(json!({"email": "nope", "grade": 8})).unwrap();
+    let Err(Error::UnprocessableEntity { errors }) = apply_update(&mut student, update) else {
+      panic!("expected validation errors");
+    };
+    assert!(errors.contains_key("email"));
+    assert!(errors.contains_key("grade"));
+
+    // Absent fields are untouched, explicit nulls clear the field
+    let update: UpdateStudent = serde_json::from_value(json!({"grade": 11})).unwrap();
+    apply_update(&mut student, update).unwrap();
+    assert_eq!(student.grade, 11);
+    assert!(student.special_accommodations.is_some());
+
+    let update: UpdateStudent =
+      serde_json::from_value(json!({"special_accommodations": null})).unwrap();
+    apply_update(&mut student, update).unwrap();
+    assert!(student.special_accommodations.is_none());
+  }
+
+  fn json_patch(body: Value) -> Request<Body> {
+    Request::patch("/students/980020")
+      .header("content-type", "application/json-patch+json")
+      .body(Body::from(body.to_string()))
+      .unwrap()
+  }
+
+  async fn app_with_student() -> Router {
+    let students = InMemoryStore::new();
+    let student = serde_json::from_value::<CreateStudentRequest>(student_json("980020", 10));
+    students
+      .save(&Student::try_from(student.unwrap()).unwrap())
+      .await
+      .unwrap();
+    router(AppState::default().with_student_store(Arc::new(students)))
+  }
+
+  #[tokio::test]
+  async fn test_json_patch_replace() {
+    setup();
+    let app = app_with_student().await;
+
+    let response = app
+      .clone()
+      .oneshot(json_patch(json!([
+        { "op": "replace", "path": "/grade", "value": 11 },
+        { "op": "replace", "path": "/email", "value": "new@csxlabs.edu" },
+      ])))
+      .await
+      .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = body_json(response).await;
+    assert_eq!(body["grade"], 11);
+    assert_eq!(body["email"], "new@csxlabs.edu");
+
+    // The result is validated like any other update
+    let response = app
+      .oneshot(json_patch(json!([
+        { "op": "replace", "path": "/grade", "value": 8 },
+      ])))
+      .await
+      .unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(body_json(response).await["error"]["fields"]["grade"].is_array());
+  }
+
+  #[tokio::test]
+  async fn test_json_patch_unsupported_path_is_422() {
+    setup();
+    let app = app_with_student().await;
+
+    let response = app
+      .clone()
+      .oneshot(json_patch(json!([
+        { "op": "replace", "path": "/first_name", "value": "Sam" },
+        { "op": "remove", "path": "/grade" },
+      ])))
+      .await
+      .unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body = body_json(response).await;
+    let fields = &body["error"]["fields"];
+    assert_eq!(fields["/first_name"][0], "cannot be patched");
+    assert_eq!(fields["/grade"][0], "supports only replace, not remove");
+
+    // Merge patches are still accepted
+    let response = app
+      .oneshot(
+        Request::patch("/students/980020")
+          .header("content-type", "application/merge-patch+json")
+          .body(Body::from(json!({ "grade": 12 }).to_string()))
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body_json(response).await["grade"], 12);
+  }
+
+  #[tokio::test]
+  async fn test_list_and_search_run_on_in_memory_store() {
+    setup();
+    let students = InMemoryStore::new();
+    for (id, first_name, grade) in [
+      ("980030", "Avery", 10),
+      ("980031", "Blake", 11),
+      ("980032", "Casey", 10),
+    ] {
+      let mut request = student_json(id, grade);
+      request["first_name"] = first_name.into();
+      let student = serde_json::from_value::<CreateStudentRequest>(request).unwrap();
+      students
+        .save(&Student::try_from(student).unwrap())
+        .await
+        .unwrap();
+    }
+    let app = router(AppState::default().with_student_store(Arc::new(students)));
+    let get = |uri: &str| Request::get(uri).body(Body::empty()).unwrap();
+
+    let response = app.clone().oneshot(get("/students?limit=2")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let page = body_json(response).await;
+    assert_eq!(page["students"].as_array().unwrap().len(), 2);
+    assert_eq!(page["total_estimate"], 3);
+    let cursor = page["next_cursor"].as_str().unwrap().to_string();
+
+    let response = app
+      .clone()
+      .oneshot(get(&format!("/students?limit=2&cursor={}", cursor)))
+      .await
+      .unwrap();
+    let page = body_json(response).await;
+    assert_eq!(page["students"][0]["full_name"], "Casey Jones");
+    assert!(page["next_cursor"].is_null());
+
+    let response = app
+      .clone()
+      .oneshot(get("/students?grade=10"))
+      .await
+      .unwrap();
+    assert_eq!(
+      body_json(response).await["students"]
+        .as_array()
+        .unwrap()
+        .len(),
+      2
+    );
+
+    let response = app.oneshot(get("/students/search?q=bla")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let matches = body_json(response).await;
+    assert_eq!(matches.as_array().unwrap().len(), 1);
+    assert_eq!(matches[0]["full_name"], "Blake Jones");
+  }
+
+  #[tokio::test]
+  async fn test_racing_updates_of_one_version_conflict() {
+    setup();
+    let students = InMemoryStore::new();
+    let request = serde_json::from_value::<CreateStudentRequest>(student_json("980033", 10));
+    let student = Student::try_from(request.unwrap()).unwrap();
+    students.save(&student).await.unwrap();
+
+    // Both editors loaded the same version before either saved
+    let mut first = students.load(&student.id).await.unwrap().unwrap();
+    let mut second = first.clone();
+    let loaded = first.version;
+    first.update_grade(11).unwrap();
+    second.update_grade(12).unwrap();
+
+    students.save_if_version(&first, loaded).await.unwrap();
+    let lost = students.save_if_version(&second, loaded).await;
+    assert!(matches!(lost, Err(Error::Conflict(_))), "{:?}", lost);
+    assert_eq!(students.load(&student.id).await.unwrap().unwrap().grade, 11);
+  }
+
+  #[test]
+  fn test_if_match_version_parsing() {
+    setup();
+    let headers = |value: &str| HeaderMap::from_iter([(header::IF_MATCH, value.parse().unwrap())]);
+
+    assert_eq!(if_match_version(&HeaderMap::new()).unwrap(), None);
+    assert_eq!(if_match_version(&headers("*")).unwrap(), None);
+    assert_eq!(if_match_version(&headers("\"3\"")).unwrap(), Some(3));
+    assert_eq!(if_match_version(&headers("W/\"3\"")).unwrap(), Some(3));
+    assert_eq!(if_match_version(&headers("7")).unwrap(), Some(7));
+    assert!(if_match_version(&headers("\"abc\"")).is_err());
+  }
+
+  #[tokio::test]
+  async fn test_create_student_malformed_json_returns_400() {
+    setup();
+    let pool = Arc::new(RedisPool::new(RedisConfig::default()).unwrap());
+
+    let response = router(pool.into())
+      .oneshot(
+        Request::post("/students")
+          .header("content-type", "application/json")
+          .body(Body::from("{\"id\": \"123456\","))
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), 400);
+    assert_eq!(body_json(response).await["error"]["code"], "bad_request");
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_import_students_csv() {
+    setup();
+    crate::init_env().unwrap();
+    let pool = Arc::new(RedisPool::init().await.unwrap());
+    let year = AcademicYear::current(Utc::now(), DEFAULT_START_MONTH).graduation_year_for(10);
+    let csv = format!(
+      "id,first_name,last_name,email,grade,graduation_year,accommodations\n\
+       920101,Ada,Lovelace,ada@csxlabs.edu,10,{year},\n\
+       920102,Alan,Turing,alan@csxlabs.edu,14,{year},\n\
+       920103,Grace,Hopper,grace@csxlabs.edu,10,{year},\n"
+    );
+
+    let response = router(pool.clone().into())
+      .oneshot(
+        Request::post("/students/import")
+          .header("content-type", "text/csv")
+          .body(Body::from(csv))
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), 200);
+    let body = body_json(response).await;
+    assert_eq!(body["created"], 2);
+    assert_eq!(body["failed"], 1);
+    assert_eq!(body["rows"][1]["status"], "invalid");
+    assert!(body["rows"][1]["errors"]["grade"].is_array());
+
+    for id in ["920101", "920103"] {
+      let id = StudentId::new(id.to_string()).unwrap();
+      assert!(store::load(&pool, &id).await.unwrap().is_some());
+      store::delete(&pool, &id).await.unwrap();
+    }
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_student_crud() {
+    setup();
+    crate::init_env().unwrap();
+    let pool = Arc::new(RedisPool::init().await.unwrap());
+    let app = router(pool.into());
+
+    let response = app
+      .clone()
+      .oneshot(json_request(
+        "POST",
+        "/students",
+        student_json("920001", 10),
+      ))
+      .await
+      .unwrap();
+    assert_eq!(response.status(), 201);
+    assert_eq!(body_json(response).await["id"], "920001");
+
+    let response = app
+      .clone()
+      .oneshot(
+        Request::get("/students/920001")
+          .body(Body::empty())
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+    assert_eq!(response.status(), 200);
+    let body = body_json(response).await;
+    assert_eq!(body["full_name"], "Casey Jones");
+    assert!(body.get("email").is_none());
+
+    let response = app
+      .clone()
+      .oneshot(json_request(
+        "PATCH",
+        "/students/920001",
+        json!({"grade": 11}),
+      ))
+      .await
+      .unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(body_json(response).await["grade"], 11);
+
+    let response = app
+      .clone()
+      .oneshot(
+        Request::delete("/students/920001")
+          .body(Body::empty())
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+    assert_eq!(response.status(), 204);
+
+    let response = app
+      .oneshot(
+        Request::get("/students/920001")
+          .body(Body::empty())
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+    assert_eq!(response.status(), 404);
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_stale_patch_returns_409() {
+    setup();
+    crate::init_env().unwrap();
+    let pool = Arc::new(RedisPool::init().await.unwrap());
+    let request = serde_json::from_value::<CreateStudentRequest>(student_json("920101", 10));
+    let student = Student::try_from(request.unwrap()).unwrap();
+    store::save(&pool, &student).await.unwrap();
+    let app = router(pool.clone().into());
+
+    let patch = |version: u64, grade: Grade| {
+      Request::patch("/students/920101")
+        .header("content-type", "application/json")
+        .header(header::IF_MATCH, format!("\"{}\"", version))
+        .body(Body::from(json!({ "grade": grade }).to_string()))
+        .unwrap()
+    };
+
+    // Both admins loaded the created version; the first edit wins
+    let created = student.version;
+    let response = app.clone().oneshot(patch(created, 11)).await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(body_json(response).await["version"], created + 1);
+
+    let response = app.clone().oneshot(patch(created, 12)).await.unwrap();
+    assert_eq!(response.status(), 409);
+    assert_eq!(body_json(response).await["error"]["code"], "conflict");
+    assert_eq!(
+      store::load(&pool, &student.id)
+        .await
+        .unwrap()
+        .unwrap()
+        .grade,
+      11
+    );
+
+    // Two racing edits of version 1: exactly one is applied
+    let (first, second) = tokio::join!(app.clone().oneshot(patch(1, 9)), app.oneshot(patch(1, 12)));
+    let mut statuses = [first.unwrap().status(), second.unwrap().status()];
+    statuses.sort();
+    assert_eq!(statuses, [StatusCode::OK, StatusCode::CONFLICT]);
+    assert_eq!(
+      store::load(&pool, &student.id)
+        .await
+        .unwrap()
+        .unwrap()
+        .version,
+      created + 2
+    );
+
+    store::delete(&pool, &student.id).await.unwrap();
+  }
+}
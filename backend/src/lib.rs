@@ -1,25 +1,78 @@
 use anyhow::{Context, Result};
-use log::{debug, warn};
+use log::{debug, warn, LevelFilter};
+use log4rs::append::console::ConsoleAppender;
+use log4rs::config::{Appender, Config, Root};
+use log4rs::encode::{pattern::PatternEncoder, Encode};
+use std::path::Path;
 use std::sync::atomic::AtomicBool;
+pub mod cache;
 pub mod http;
+pub mod locker;
+pub mod logging;
+pub mod preflight;
 pub mod redis;
+#[cfg(feature = "schema")]
+pub mod schema;
 pub mod student;
 
 static LOGGING_INITIALIZED: AtomicBool = AtomicBool::new(false);
 
+/// log4rs configuration file, relative to the working directory
+const LOG_CONFIG_FILE: &str = "log4rs.yaml";
+
+/// Line layout shared with the console appender in `log4rs.yaml`
+const LOG_PATTERN: &str = "{d(%Y-%m-%d %H:%M:%S %Z)(utc)} - {h({l})} - {t} - {m}{n}";
+
 /// Initialize the logging system using log4rs
+///
+/// With `LOG_FORMAT=json`, logs JSON lines to the console. Otherwise uses `log4rs.yaml`
+/// when present, falling back to `default_logging_config`.
 pub fn init_logging() -> Result<()> {
   if logging_initialized() {
     return Ok(());
   }
-  let result =
-    log4rs::init_file("log4rs.yaml", Default::default()).context("Failed to initialize logging");
+  let json = std::env::var("LOG_FORMAT").is_ok_and(|format| format.eq_ignore_ascii_case("json"));
+  let result = if json {
+    console_logging_config(Box::new(logging::JsonLineEncoder)).and_then(|config| {
+      log4rs::init_config(config)
+        .map(|_| ())
+        .context("Failed to initialize logging")
+    })
+  } else if Path::new(LOG_CONFIG_FILE).exists() {
+    log4rs::init_file(LOG_CONFIG_FILE, Default::default()).context("Failed to initialize logging")
+  } else {
+    default_logging_config().and_then(|config| {
+      log4rs::init_config(config)
+        .map(|_| ())
+        .context("Failed to initialize logging")
+    })
+  };
   if result.is_ok() {
     set_logging_initialized();
   }
   result
 }
 
+/// Console-only logging at the level in `RUST_LOG` (default `info`)
+pub fn default_logging_config() -> Result<Config> {
+  console_logging_config(Box::new(PatternEncoder::new(LOG_PATTERN)))
+}
+
+/// Console-only logging with `encoder` at the level in `RUST_LOG` (default `info`)
+fn console_logging_config(encoder: Box<dyn Encode>) -> Result<Config> {
+  let level = std::env::var("RUST_LOG")
+    .ok()
+    .and_then(|level| level.parse::<LevelFilter>().ok())
+    .unwrap_or(LevelFilter::Info);
+
+  let stdout = ConsoleAppender::builder().encoder(encoder).build();
+
+  Config::builder()
+    .appender(Appender::builder().build("stdout", Box::new(stdout)))
+    .build(Root::builder().appender("stdout").build(level))
+    .context("Invalid default logging configuration")
+}
+
 /// Check if the logging system is initialized
 pub fn logging_initialized() -> bool {
   LOGGING_INITIALIZED.load(std::sync::atomic::Ordering::Relaxed)
@@ -0,0 +1,146 @@
+//! Per-hallway locker counts for planning a match run.
+
+use crate::http::Error;
+use crate::locker::store::{get_lockers, list_assignments};
+use crate::redis::{RedisOperations, RedisPool};
+use log::debug;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+
+/// How many lockers a hallway has and how many are still free
+#[derive(Debug, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HallwayAvailability {
+  pub total: usize,
+  pub free: usize,
+  pub assigned: usize,
+  pub ada_free: usize,
+}
+
+/// Count the lockers in every indexed hallway, keyed by hallway name
+///
+/// `total` comes from the `hallway:{name}` index sets and `assigned` from the stored
+/// assignments, so a locker indexed without a record still counts toward `total`.
+pub async fn availability(
+  pool: &RedisPool,
+) -> Result<BTreeMap<String, HallwayAvailability>, Error> {
+  let assigned: HashSet<String> = list_assignments(pool)
+    .await?
+    .into_iter()
+    .map(|assignment| assignment.locker.number.to_string())
+    .collect();
+
+  let mut hallways = BTreeMap::new();
+  for key in pool.scan_collect("hallway:*").await? {
+    let Some(hallway) = key.strip_prefix("hallway:") else {
+      continue;
+    };
+    let numbers: Vec<String> = pool.smembers(&key).await?;
+    let (lockers, _) = get_lockers(pool, &numbers).await?;
+
+    let taken = numbers.iter().filter(|n| assigned.contains(*n)).count();
+    let ada_free = lockers
+      .iter()
+      .filter(|l| l.ada_accessible && !assigned.contains(l.number.as_str()))
+      .count();
+    hallways.insert(
+      hallway.to_string(),
+      HallwayAvailability {
+        total: numbers.len(),
+        free: numbers.len() - taken,
+        assigned: taken,
+        ada_free,
+      },
+    );
+  }
+
+  debug!("Computed availability for {} hallways", hallways.len());
+  Ok(hallways)
+}
+
+// Tests
+#[cfg(all(test, feature = "redis-tests"))]
+mod tests {
+  use super::*;
+  use crate::locker::store::{
+    hallway_key, locker_key, release_assignment, save_assignment, save_locker,
+  };
+  use crate::locker::{Assignment, Locker, LockerSize};
+  use crate::student::StudentId;
+  use crate::{init_env, init_logging};
+
+  async fn setup() -> RedisPool {
+    let _ = init_logging(); // Ignore error if already initialized
+    init_env().unwrap();
+    RedisPool::init().await.unwrap()
+  }
+
+  fn locker(number: &str, hallway: &str, ada_accessible: bool) -> Locker {
+    Locker::new(
+      number.to_string(),
+      hallway.to_string(),
+      1,
+      LockerSize::Standard,
+      ada_accessible,
+    )
+    .unwrap()
+  }
+
+  #[tokio::test]
+  async fn test_availability_counts_per_hallway() {
+    let pool = setup().await;
+    let lockers = [
+      locker("AVA-1", "AVA", true),
+      locker("AVA-2", "AVA", true),
+      locker("AVA-3", "AVA", false),
+      locker("AVB-1", "AVB", false),
+    ];
+    for locker in &lockers {
+      save_locker(&pool, locker).await.unwrap();
+    }
+    let assignments = [
+      Assignment::new(
+        StudentId::new("940001".to_string()).unwrap(),
+        lockers[0].clone(),
+        false,
+      ),
+      Assignment::new(
+        StudentId::new("940002".to_string()).unwrap(),
+        lockers[3].clone(),
+        false,
+      ),
+    ];
+    for assignment in &assignments {
+      save_assignment(&pool, assignment).await.unwrap();
+    }
+
+    let hallways = availability(&pool).await.unwrap();
+    assert_eq!(
+      hallways["AVA"],
+      HallwayAvailability {
+        total: 3,
+        free: 2,
+        assigned: 1,
+        ada_free: 1,
+      }
+    );
+    assert_eq!(
+      hallways["AVB"],
+      HallwayAvailability {
+        total: 1,
+        free: 0,
+        assigned: 1,
+        ada_free: 0,
+      }
+    );
+
+    for assignment in &assignments {
+      release_assignment(&pool, assignment).await.unwrap();
+    }
+    for locker in &lockers {
+      pool.del(&locker_key(locker.number.as_str())).await.unwrap();
+    }
+    for hallway in ["AVA", "AVB"] {
+      pool.del(&hallway_key(hallway)).await.unwrap();
+    }
+  }
+}
@@ -0,0 +1,250 @@
+use crate::http::Error;
+use crate::locker::store::{self, assignment_key, holder_key};
+use crate::locker::waitlist::WAITLIST_KEY;
+use crate::locker::Assignment;
+use crate::redis::RedisPool;
+use crate::student::StudentId;
+use chrono::{DateTime, Duration, Utc};
+use log::{debug, info, warn};
+use std::env;
+use std::sync::Arc;
+
+/// How long a student has to claim an assigned locker before it returns to the pool.
+#[derive(Debug, Clone)]
+pub struct ClaimPolicy {
+  /// Time after `assigned_at` by which the assignment must be claimed
+  pub window: Duration,
+}
+
+impl Default for ClaimPolicy {
+  fn default() -> Self {
+    // Get the claim window from environment or default to 72 hours
+    let hours = env::var("UNCLAIMED_EXPIRY_HOURS")
+      .ok()
+      .and_then(|h| h.parse::<i64>().ok())
+      .filter(|h| *h > 0)
+      .unwrap_or(72);
+
+    Self {
+      window: Duration::hours(hours),
+    }
+  }
+}
+
+impl ClaimPolicy {
+  /// Returns true if the assignment is unclaimed and past its claim deadline at `now`.
+  pub fn is_expired(&self, assignment: &Assignment, now: DateTime<Utc>) -> bool {
+    !assignment.claimed && assignment.assigned_at + self.window < now
+  }
+}
+
+/// Release every unclaimed assignment past its claim deadline.
+///
+/// Each release rechecks the stored assignment in a transaction, so one claimed or
+/// replaced since the listing is left alone. Freed students go back on the waitlist
+/// behind everyone already waiting. Returns the ids of the students whose assignments
+/// were freed.
+pub async fn sweep_unclaimed(
+  pool: &RedisPool,
+  policy: &ClaimPolicy,
+) -> Result<Vec<StudentId>, Error> {
+  let now = Utc::now();
+  let mut freed = Vec::new();
+
+  for assignment in store::list_assignments(pool).await? {
+    if policy.is_expired(&assignment, now)
+      && release_if_expired(pool, &assignment, policy, now).await?
+    {
+      info!(
+        "Released unclaimed locker {} assigned to student {}, back on the waitlist",
+        assignment.locker.number,
+        assignment.student_id.to_string()
+      );
+      freed.push(assignment.student_id);
+    }
+  }
+
+  debug!("Unclaimed sweep freed {} assignments", freed.len());
+  Ok(freed)
+}
+
+/// Release `assignment` and waitlist its student if it is still stored, unclaimed and
+/// expired at `now`, returning whether it was released
+async fn release_if_expired(
+  pool: &RedisPool,
+  assignment: &Assignment,
+  policy: &ClaimPolicy,
+  now: DateTime<Utc>,
+) -> Result<bool, Error> {
+  let id = assignment.student_id.to_string();
+  let student_assignment = assignment_key(&assignment.student_id);
+  let holder = holder_key(assignment.locker.number.as_str());
+
+  let released: Vec<i64> = pool
+    .transaction(&[&student_assignment, &holder], |mut conn| {
+      let id = id.clone();
+      let student_assignment = pool.prefixed(&student_assignment);
+      let holder = pool.prefixed(&holder);
+
+      async move {
+        let current: Option<String> = redis::cmd("GET")
+          .arg(&student_assignment)
+          .query_async(&mut conn)
+          .await?;
+        let current: Option<Assignment> = current
+          .map(|json| serde_json::from_str(&json))
+          .transpose()
+          .map_err(|e| {
+            Error::RedisParseError(format!("Failed to deserialize assignment: {}", e))
+          })?;
+
+        // An empty transaction leaves everything as it is
+        let mut pipe = redis::pipe();
+        let still_expired = current.is_some_and(|current| {
+          current.locker.number == assignment.locker.number && policy.is_expired(&current, now)
+        });
+        if still_expired {
+          let holder_id: Option<String> = redis::cmd("GET")
+            .arg(&holder)
+            .query_async(&mut conn)
+            .await?;
+          pipe.del(&student_assignment);
+          if holder_id.as_deref() == Some(id.as_str()) {
+            pipe.del(&holder).ignore();
+          }
+          // LPUSH puts the student at the low-priority end of the waitlist
+          pipe.lpush(pool.prefixed(WAITLIST_KEY), &id).ignore();
+        }
+        Ok(pipe)
+      }
+    })
+    .await?;
+
+  Ok(!released.is_empty())
+}
+
+/// Spawn a background task that runs `sweep_unclaimed` every `interval`
+pub fn spawn_sweeper(
+  pool: Arc<RedisPool>,
+  policy: ClaimPolicy,
+  interval: std::time::Duration,
+) -> tokio::task::JoinHandle<()> {
+  tokio::spawn(async move {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+      ticker.tick().await;
+      if let Err(e) = sweep_unclaimed(&pool, &policy).await {
+        warn!("Unclaimed assignment sweep failed: {}", e);
+      }
+    }
+  })
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+  #[cfg(feature = "redis-tests")]
+  use crate::locker::LockerNumber;
+  use crate::locker::{Locker, LockerSize};
+  #[cfg(feature = "redis-tests")]
+  use crate::redis::{RedisConfig, RedisOperations};
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  fn assignment(assigned_hours_ago: i64) -> Assignment {
+    let locker = Locker::new(
+      "A-1".to_string(),
+      "A".to_string(),
+      1,
+      LockerSize::Standard,
+      false,
+    )
+    .unwrap();
+    let mut assignment =
+      Assignment::new(StudentId::new("123456".to_string()).unwrap(), locker, false);
+    assignment.assigned_at = Utc::now() - Duration::hours(assigned_hours_ago);
+    assignment
+  }
+
+  #[test]
+  fn test_unclaimed_past_deadline_is_expired() {
+    setup();
+    let policy = ClaimPolicy {
+      window: Duration::hours(24),
+    };
+
+    assert!(policy.is_expired(&assignment(25), Utc::now()));
+    assert!(!policy.is_expired(&assignment(23), Utc::now()));
+  }
+
+  #[test]
+  fn test_claimed_is_kept() {
+    setup();
+    let policy = ClaimPolicy {
+      window: Duration::hours(24),
+    };
+    let mut claimed = assignment(48);
+    claimed.claim();
+
+    assert!(claimed.claimed);
+    assert!(!policy.is_expired(&claimed, Utc::now()));
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_sweep_releases_expired_and_waitlists_student() {
+    setup();
+    crate::init_env().unwrap();
+    let pool = RedisPool::new(RedisConfig {
+      key_prefix: Some("test-expiry".to_string()),
+      ..RedisConfig::default()
+    })
+    .unwrap();
+    pool.delete_prefix("").await.unwrap();
+    let policy = ClaimPolicy {
+      window: Duration::hours(24),
+    };
+
+    let expired = assignment(48);
+    store::save_assignment(&pool, &expired).await.unwrap();
+    let mut claimed = assignment(48);
+    claimed.student_id = StudentId::new("123457".to_string()).unwrap();
+    claimed.locker.number = LockerNumber::new("A-2".to_string()).unwrap();
+    claimed.claim();
+    store::save_assignment(&pool, &claimed).await.unwrap();
+
+    let freed = sweep_unclaimed(&pool, &policy).await.unwrap();
+    let freed: Vec<String> = freed.iter().map(StudentId::to_string).collect();
+    assert_eq!(freed, vec!["123456"]);
+    assert!(store::get_assignment(&pool, &expired.student_id)
+      .await
+      .unwrap()
+      .is_none());
+    assert!(!pool.exists(&holder_key("A-1")).await.unwrap());
+    assert!(store::get_assignment(&pool, &claimed.student_id)
+      .await
+      .unwrap()
+      .is_some());
+    let waiting: Vec<String> = pool.lrange(WAITLIST_KEY, 0, -1).await.unwrap();
+    assert_eq!(waiting, vec!["123456"]);
+
+    // An assignment claimed after the listing is kept
+    let mut late = assignment(48);
+    store::save_assignment(&pool, &late).await.unwrap();
+    late.claim();
+    store::save_assignment(&pool, &late).await.unwrap();
+    let stale = assignment(48);
+    assert!(!release_if_expired(&pool, &stale, &policy, Utc::now())
+      .await
+      .unwrap());
+    assert!(store::get_assignment(&pool, &late.student_id)
+      .await
+      .unwrap()
+      .is_some());
+    pool.delete_prefix("").await.unwrap();
+  }
+}
@@ -0,0 +1,340 @@
+//! Bulk locker import from the facilities spreadsheet.
+//!
+//! Columns: `number,hallway,row,tier,size,ada_accessible`. `row` may be left blank,
+//! `size` is `standard` or `wide`, and `ada_accessible` accepts `true`/`false`,
+//! `yes`/`no` or `1`/`0`.
+//!
+//! Lockers are stored by number, so a number that appears twice in the file, or that
+//! is already stored, is reported as a conflict instead of overwriting the earlier
+//! locker. Pass `overwrite` to `import_csv` to replace stored lockers on purpose.
+
+use crate::http::Error;
+use crate::locker::store::{get_lockers, save_locker};
+use crate::locker::{Locker, LockerSize};
+use crate::redis::RedisPool;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::Read;
+
+/// One row of a locker CSV, before validation
+#[derive(Debug, Deserialize)]
+struct CsvRow {
+  number: String,
+  hallway: String,
+  #[serde(default)]
+  row: String,
+  tier: String,
+  #[serde(default)]
+  size: String,
+  #[serde(default)]
+  ada_accessible: String,
+}
+
+/// The outcome of importing one locker CSV row
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum LockerImportRow {
+  /// The row produced a valid locker
+  Created { row: usize, number: String },
+  /// The row failed validation; `errors` is keyed by field like `UnprocessableEntity`
+  Invalid {
+    row: usize,
+    errors: HashMap<Cow<'static, str>, Vec<Cow<'static, str>>>,
+  },
+  /// The locker's number is taken by an earlier row or a stored locker
+  Conflict {
+    row: usize,
+    number: String,
+    reason: String,
+  },
+}
+
+/// Counts and per-row results of a locker import
+#[derive(Debug, Serialize)]
+pub struct LockerImportSummary {
+  pub created: usize,
+  pub failed: usize,
+  pub conflicts: usize,
+  pub rows: Vec<LockerImportRow>,
+}
+
+/// Parse and validate a locker CSV with a header row.
+///
+/// Each data row (numbered from 1) is validated independently through `Locker::new`,
+/// so one bad row never prevents the others from importing. A row reusing the number
+/// of an earlier valid row is a conflict. Returns the lockers to store alongside a
+/// per-row result in input order.
+pub fn parse_csv(reader: impl Read) -> (Vec<Locker>, Vec<LockerImportRow>) {
+  let mut reader = csv::ReaderBuilder::new()
+    .trim(csv::Trim::All)
+    .from_reader(reader);
+
+  let mut lockers = Vec::new();
+  let mut rows = Vec::new();
+  let mut seen: HashMap<String, (String, usize)> = HashMap::new();
+
+  for (index, record) in reader.deserialize::<CsvRow>().enumerate() {
+    let row = index + 1;
+    let result = record
+      .map_err(|e| Error::unprocessable_entity([("row", e.to_string())]))
+      .and_then(validate_row);
+
+    match result {
+      Ok(locker) => {
+        if let Some((hallway, first)) = seen.get(locker.number.as_str()) {
+          rows.push(LockerImportRow::Conflict {
+            row,
+            number: locker.number.to_string(),
+            reason: format!(
+              "duplicates the locker in hallway {} on row {}",
+              hallway, first
+            ),
+          });
+          continue;
+        }
+        seen.insert(locker.number.to_string(), (locker.hallway.clone(), row));
+        rows.push(LockerImportRow::Created {
+          row,
+          number: locker.number.to_string(),
+        });
+        lockers.push(locker);
+      }
+      Err(Error::UnprocessableEntity { errors }) => {
+        rows.push(LockerImportRow::Invalid { row, errors })
+      }
+      Err(e) => rows.push(LockerImportRow::Invalid {
+        row,
+        errors: HashMap::from([("row".into(), vec![e.to_string().into()])]),
+      }),
+    }
+  }
+
+  (lockers, rows)
+}
+
+/// Parse the typed columns and build a `Locker`, collecting every field error
+fn validate_row(row: CsvRow) -> Result<Locker, Error> {
+  let bank = match row.row.as_str() {
+    "" => Ok(None),
+    bank => bank.parse::<u16>().map(Some),
+  };
+  let tier = row.tier.parse::<u8>();
+  let size = match row.size.to_lowercase().as_str() {
+    "" | "standard" => Some(LockerSize::Standard),
+    "wide" => Some(LockerSize::Wide),
+    _ => None,
+  };
+  let ada_accessible = match row.ada_accessible.to_lowercase().as_str() {
+    "" | "false" | "no" | "0" => Some(false),
+    "true" | "yes" | "1" => Some(true),
+    _ => None,
+  };
+
+  let mut errors = Vec::new();
+  if bank.is_err() {
+    errors.push(("row", "must be a number"));
+  }
+  if tier.is_err() {
+    errors.push(("tier", "must be a number"));
+  }
+  if size.is_none() {
+    errors.push(("size", "must be standard or wide"));
+  }
+  if ada_accessible.is_none() {
+    errors.push(("ada_accessible", "must be true or false"));
+  }
+  if !errors.is_empty() {
+    return Err(Error::unprocessable_entity(errors));
+  }
+
+  let mut locker = Locker::new(
+    row.number,
+    row.hallway,
+    tier.unwrap(),
+    size.unwrap(),
+    ada_accessible.unwrap(),
+  )?;
+  locker.row = bank.unwrap();
+  Ok(locker)
+}
+
+/// Import a locker CSV, storing every valid row and its hallway index entry
+///
+/// Invalid rows are reported and skipped, as are rows whose locker is already stored
+/// unless `overwrite` is set. A Redis failure aborts the import.
+pub async fn import_csv(
+  pool: &RedisPool,
+  reader: impl Read,
+  overwrite: bool,
+) -> Result<LockerImportSummary, Error> {
+  let (mut lockers, mut rows) = parse_csv(reader);
+
+  if !overwrite {
+    let numbers: Vec<String> = lockers.iter().map(|l| l.number.to_string()).collect();
+    let (stored, _) = get_lockers(pool, &numbers).await?;
+    let stored: HashMap<String, String> = stored
+      .into_iter()
+      .map(|locker| (locker.number.to_string(), locker.hallway))
+      .collect();
+
+    if !stored.is_empty() {
+      for result in rows.iter_mut() {
+        if let LockerImportRow::Created { row, number } = result {
+          if let Some(hallway) = stored.get(number.as_str()) {
+            *result = LockerImportRow::Conflict {
+              row: *row,
+              number: number.clone(),
+              reason: format!("is already stored in hallway {}", hallway),
+            };
+          }
+        }
+      }
+      lockers.retain(|locker| !stored.contains_key(locker.number.as_str()));
+    }
+  }
+
+  for locker in &lockers {
+    save_locker(pool, locker).await?;
+  }
+
+  let conflicts = rows
+    .iter()
+    .filter(|row| matches!(row, LockerImportRow::Conflict { .. }))
+    .count();
+  let summary = LockerImportSummary {
+    created: lockers.len(),
+    failed: rows.len() - lockers.len() - conflicts,
+    conflicts,
+    rows,
+  };
+  info!(
+    "Imported {} lockers ({} rows failed validation, {} conflicted)",
+    summary.created, summary.failed, summary.conflicts
+  );
+  Ok(summary)
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  const CSV: &str = "number,hallway,row,tier,size,ada_accessible\n\
+                     I-1,I,1,1,wide,yes\n\
+                     I-2,I,1,top,standard,no\n\
+                     I-3,I,,2,,\n";
+
+  #[test]
+  fn test_parse_csv_keeps_valid_rows_around_invalid_tier() {
+    setup();
+    let (lockers, rows) = parse_csv(CSV.as_bytes());
+
+    assert_eq!(lockers.len(), 2);
+    assert_eq!(lockers[0].size, LockerSize::Wide);
+    assert!(lockers[0].ada_accessible);
+    assert_eq!(lockers[0].row, Some(1));
+    assert_eq!(lockers[1].row, None);
+    assert_eq!(lockers[1].size, LockerSize::Standard);
+
+    assert!(matches!(&rows[0], LockerImportRow::Created { row: 1, number } if number == "I-1"));
+    match &rows[1] {
+      LockerImportRow::Invalid { row, errors } => {
+        assert_eq!(*row, 2);
+        assert!(errors.contains_key("tier"));
+      }
+      other => panic!("expected row 2 to be invalid, got {:?}", other),
+    }
+  }
+
+  #[test]
+  fn test_parse_csv_reports_duplicate_number_as_conflict() {
+    setup();
+    let csv = "number,hallway,row,tier,size,ada_accessible\n\
+               K-1,K,1,1,,\n\
+               K-1,K,2,2,,\n\
+               K-2,K,2,1,,\n";
+    let (lockers, rows) = parse_csv(csv.as_bytes());
+
+    assert_eq!(lockers.len(), 2);
+    assert_eq!(lockers[0].tier, 1);
+    match &rows[1] {
+      LockerImportRow::Conflict {
+        row,
+        number,
+        reason,
+      } => {
+        assert_eq!((*row, number.as_str()), (2, "K-1"));
+        assert!(reason.contains("row 1"), "{}", reason);
+      }
+      other => panic!("expected row 2 to conflict, got {:?}", other),
+    }
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_import_csv_stores_only_valid_rows() {
+    use crate::locker::store::{get_lockers, hallway_key, locker_key};
+    use crate::redis::RedisOperations;
+
+    setup();
+    crate::init_env().unwrap();
+    let pool = RedisPool::init().await.unwrap();
+
+    for key in [locker_key("I-1"), locker_key("I-3")] {
+      pool.del(&key).await.unwrap();
+    }
+    let summary = import_csv(&pool, CSV.as_bytes(), false).await.unwrap();
+    assert_eq!((summary.created, summary.failed), (2, 1));
+
+    let numbers = ["I-1".to_string(), "I-2".to_string(), "I-3".to_string()];
+    let (found, missing) = get_lockers(&pool, &numbers).await.unwrap();
+    assert_eq!(found.len(), 2);
+    assert_eq!(missing, ["I-2"]);
+
+    for key in [locker_key("I-1"), locker_key("I-3"), hallway_key("I")] {
+      pool.del(&key).await.unwrap();
+    }
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_import_csv_conflicts_with_stored_locker_unless_overwriting() {
+    use crate::locker::store::{get_lockers, hallway_key, locker_key};
+    use crate::redis::RedisOperations;
+
+    setup();
+    crate::init_env().unwrap();
+    let pool = RedisPool::init().await.unwrap();
+    pool.del(&locker_key("K-10")).await.unwrap();
+    let header = "number,hallway,row,tier,size,ada_accessible\n";
+
+    let first = format!("{}K-10,K,1,1,,\n", header);
+    let summary = import_csv(&pool, first.as_bytes(), false).await.unwrap();
+    assert_eq!(summary.created, 1);
+
+    let second = format!("{}K-10,K,1,3,wide,\n", header);
+    let summary = import_csv(&pool, second.as_bytes(), false).await.unwrap();
+    assert_eq!((summary.created, summary.conflicts), (0, 1));
+    assert!(
+      matches!(&summary.rows[0], LockerImportRow::Conflict { number, .. } if number == "K-10")
+    );
+    let (stored, _) = get_lockers(&pool, &["K-10".to_string()]).await.unwrap();
+    assert_eq!(stored[0].tier, 1);
+
+    let summary = import_csv(&pool, second.as_bytes(), true).await.unwrap();
+    assert_eq!((summary.created, summary.conflicts), (1, 0));
+    let (stored, _) = get_lockers(&pool, &["K-10".to_string()]).await.unwrap();
+    assert_eq!(stored[0].tier, 3);
+
+    for key in [locker_key("K-10"), hallway_key("K")] {
+      pool.del(&key).await.unwrap();
+    }
+  }
+}
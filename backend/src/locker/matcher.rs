@@ -0,0 +1,478 @@
+use crate::locker::{Assignment, Locker, ZonePolicy};
+use crate::student::{AccommodationNeeds, Student, StudentId};
+use log::{debug, info};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+
+/// A placement rule deciding whether a locker is acceptable for a student.
+type Pass<'a> = &'a dyn Fn(&Student, &AccommodationNeeds, &Locker) -> bool;
+
+/// The outcome of a matching run.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MatchResult {
+  pub assignments: Vec<Assignment>,
+  pub unassigned: Vec<StudentId>,
+}
+
+/// Assigns each student at most one free locker.
+///
+/// Inactive students are skipped entirely and appear in neither list of the result.
+///
+/// Students are placed in priority order: students with accommodation needs first, then
+/// seniors down to freshmen, keeping input order otherwise. Every student with needs is
+/// placed before anyone else, and only in a locker that meets them, in passes:
+///
+/// 1. For students whose grade has a zone, a locker inside it
+/// 2. For everyone else, a locker their grade permits (unzoned grades may use any
+///    hallway)
+/// 3. A locker in any hallway
+///
+/// The remaining students then prefer lockers that meet no special need (accessible,
+/// bottom tier or wide), keeping those free for students who need them:
+///
+/// 1. For zoned students, such a locker inside their zone
+/// 2. Such a locker their grade permits
+/// 3. For zoned students, any locker inside their zone
+/// 4. Any locker their grade permits
+/// 5. Such a locker in any hallway
+/// 6. Any free locker
+///
+/// Zoned students go first so that unzoned grades can't take the lockers in their
+/// zone. Students with needs that no free locker meets are left `unassigned`.
+///
+/// Assignments outside the hallways permitted by `policy` are flagged `out_of_zone`.
+/// Students left over when lockers run out are returned in `unassigned`, in priority
+/// order.
+pub fn match_students(
+  students: &[Student],
+  lockers: &[Locker],
+  policy: &ZonePolicy,
+) -> MatchResult {
+  match_students_seeded(students, lockers, policy, None)
+}
+
+/// Like `match_students`, breaking priority ties with a seeded shuffle
+///
+/// With `Some(seed)`, students of equal priority are placed in an order shuffled by
+/// `StdRng::seed_from_u64(seed)` instead of input order, so a run can be reproduced
+/// exactly by reusing its seed. `None` keeps input order.
+pub fn match_students_seeded(
+  students: &[Student],
+  lockers: &[Locker],
+  policy: &ZonePolicy,
+  seed: Option<u64>,
+) -> MatchResult {
+  debug!(
+    "Matching {} students to {} lockers (seed: {:?})",
+    students.len(),
+    lockers.len(),
+    seed
+  );
+
+  let mut order: Vec<(&Student, AccommodationNeeds)> = students
+    .iter()
+    .filter(|s| s.active)
+    .map(|s| (s, s.accommodation_needs()))
+    .collect();
+  if let Some(seed) = seed {
+    // The sort below is stable, so this only reorders students of equal priority
+    order.shuffle(&mut StdRng::seed_from_u64(seed));
+  }
+  order.sort_by_key(|(s, needs)| (Reverse(needs.has_locker_needs()), Reverse(s.grade)));
+
+  let mut taken = vec![false; lockers.len()];
+  let mut placed: Vec<Option<usize>> = vec![None; order.len()];
+
+  let zoned = |s: &Student| policy.allowed_hallways(s.grade).is_some();
+  let in_zone = |s: &Student, l: &Locker| policy.permits(s.grade, &l.hallway);
+  let needy_passes: [Pass; 3] = [
+    &|s, needs, l| zoned(s) && in_zone(s, l) && l.satisfies(needs),
+    &|s, needs, l| in_zone(s, l) && l.satisfies(needs),
+    &|_, needs, l| l.satisfies(needs),
+  ];
+  let other_passes: [Pass; 6] = [
+    &|s, _, l| zoned(s) && in_zone(s, l) && !l.meets_special_needs(),
+    &|s, _, l| in_zone(s, l) && !l.meets_special_needs(),
+    &|s, _, l| zoned(s) && in_zone(s, l),
+    &|s, _, l| in_zone(s, l),
+    &|_, _, l| !l.meets_special_needs(),
+    &|_, _, _| true,
+  ];
+  let stages: [(bool, &[Pass]); 2] = [(true, &needy_passes), (false, &other_passes)];
+
+  for (needy, passes) in stages {
+    for accepts in passes {
+      for (slot, (student, needs)) in order.iter().enumerate() {
+        if placed[slot].is_some() || needs.has_locker_needs() != needy {
+          continue;
+        }
+
+        let candidate =
+          (0..lockers.len()).find(|&i| !taken[i] && accepts(student, needs, &lockers[i]));
+        if let Some(i) = candidate {
+          taken[i] = true;
+          placed[slot] = Some(i);
+        }
+      }
+    }
+  }
+
+  let mut result = MatchResult::default();
+  for ((student, _), locker) in order.iter().zip(placed) {
+    match locker {
+      Some(i) => {
+        let locker = &lockers[i];
+        let out_of_zone = !policy.permits(student.grade, &locker.hallway);
+        if out_of_zone {
+          debug!(
+            "Student {} placed out of zone in hallway {}",
+            student.id.to_string(),
+            locker.hallway
+          );
+        }
+        result.assignments.push(Assignment::new(
+          student.id.clone(),
+          locker.clone(),
+          out_of_zone,
+        ));
+      }
+      None => result.unassigned.push(student.id.clone()),
+    }
+  }
+
+  info!(
+    "Matching complete: {} assigned, {} unassigned",
+    result.assignments.len(),
+    result.unassigned.len()
+  );
+
+  result
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+  use crate::locker::LockerSize;
+  use crate::student::academic_year::DEFAULT_START_MONTH;
+  use crate::student::{AcademicYear, Accommodation};
+  use chrono::Utc;
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  fn student(id: &str, grade: u8) -> Student {
+    let graduation_year =
+      AcademicYear::current(Utc::now(), DEFAULT_START_MONTH).graduation_year_for(grade);
+    Student::new(
+      id.to_string(),
+      "Test".to_string(),
+      "Student".to_string(),
+      format!("{}@csxlabs.edu", id),
+      grade,
+      graduation_year,
+      None,
+    )
+    .unwrap()
+  }
+
+  fn locker(number: &str, hallway: &str) -> Locker {
+    Locker::new(
+      number.to_string(),
+      hallway.to_string(),
+      1,
+      LockerSize::Standard,
+      false,
+    )
+    .unwrap()
+  }
+
+  fn top_locker(number: &str, hallway: &str) -> Locker {
+    Locker {
+      tier: 3,
+      ..locker(number, hallway)
+    }
+  }
+
+  #[test]
+  fn test_senior_not_placed_in_freshman_hallway() {
+    setup();
+    let policy = ZonePolicy::new().with_zone(12, ["A"]).with_zone(9, ["C"]);
+    let lockers = vec![locker("C-1", "C"), locker("A-1", "A")];
+    let students = vec![student("100001", 12)];
+
+    let result = match_students(&students, &lockers, &policy);
+
+    assert_eq!(result.assignments.len(), 1);
+    assert_eq!(result.assignments[0].locker.hallway, "A");
+    assert!(!result.assignments[0].out_of_zone);
+  }
+
+  #[test]
+  fn test_out_of_zone_fallback() {
+    setup();
+    let policy = ZonePolicy::new().with_zone(12, ["A"]).with_zone(9, ["C"]);
+    let lockers = vec![locker("A-1", "A"), locker("C-1", "C"), locker("C-2", "C")];
+    let students = vec![
+      student("100001", 12),
+      student("100002", 12),
+      student("100003", 9),
+    ];
+
+    let result = match_students(&students, &lockers, &policy);
+
+    assert_eq!(result.assignments.len(), 3);
+    assert!(result.unassigned.is_empty());
+
+    // The freshman keeps an in-zone locker; the second senior falls back
+    let freshman = result
+      .assignments
+      .iter()
+      .find(|a| a.student_id.to_string() == "100003")
+      .unwrap();
+    assert_eq!(freshman.locker.hallway, "C");
+    assert!(!freshman.out_of_zone);
+
+    let senior = result
+      .assignments
+      .iter()
+      .find(|a| a.student_id.to_string() == "100002")
+      .unwrap();
+    assert_eq!(senior.locker.hallway, "C");
+    assert!(senior.out_of_zone);
+  }
+
+  #[test]
+  fn test_unzoned_grade_does_not_take_zoned_lockers() {
+    setup();
+    let policy = ZonePolicy::new().with_zone(9, ["C"]);
+    let lockers = vec![locker("C-1", "C"), locker("A-1", "A")];
+    let students = vec![student("100001", 12), student("100002", 9)];
+
+    let result = match_students(&students, &lockers, &policy);
+
+    let hallway_of = |id: &str| {
+      result
+        .assignments
+        .iter()
+        .find(|a| a.student_id.to_string() == id)
+        .map(|a| (a.locker.hallway.clone(), a.out_of_zone))
+        .unwrap()
+    };
+    assert_eq!(hallway_of("100002"), ("C".to_string(), false));
+    assert_eq!(hallway_of("100001"), ("A".to_string(), false));
+  }
+
+  fn needs_accessible(id: &str, grade: u8) -> Student {
+    let mut student = student(id, grade);
+    student
+      .update_accommodations(vec![Accommodation::AdaAccessible])
+      .unwrap();
+    student
+  }
+
+  fn ada_locker(number: &str, hallway: &str) -> Locker {
+    Locker {
+      ada_accessible: true,
+      ..top_locker(number, hallway)
+    }
+  }
+
+  #[test]
+  fn test_zoned_student_without_needs_does_not_take_needed_locker() {
+    setup();
+    let policy = ZonePolicy::new().with_zone(10, ["A"]);
+    let lockers = vec![ada_locker("A-1", "A")];
+    let students = vec![student("100001", 10), needs_accessible("100002", 12)];
+
+    let result = match_students(&students, &lockers, &policy);
+
+    assert_eq!(result.assignments.len(), 1);
+    assert_eq!(result.assignments[0].student_id.to_string(), "100002");
+    assert_eq!(result.unassigned[0].to_string(), "100001");
+  }
+
+  #[test]
+  fn test_needy_out_of_zone_fallback_beats_unzoned_students() {
+    setup();
+    let policy = ZonePolicy::new().with_zone(9, ["C"]);
+    let lockers = vec![ada_locker("A-1", "A")];
+    let students = vec![student("100001", 12), needs_accessible("100002", 9)];
+
+    let result = match_students(&students, &lockers, &policy);
+
+    assert_eq!(result.assignments.len(), 1);
+    assert_eq!(result.assignments[0].student_id.to_string(), "100002");
+    assert!(result.assignments[0].out_of_zone);
+    assert_eq!(result.unassigned[0].to_string(), "100001");
+  }
+
+  #[test]
+  fn test_needs_are_never_given_an_unsuitable_locker() {
+    setup();
+    let lockers = vec![top_locker("A-1", "A"), ada_locker("A-2", "A")];
+    let students = vec![
+      needs_accessible("100001", 12),
+      needs_accessible("100002", 11),
+    ];
+
+    let result = match_students(&students, &lockers, &ZonePolicy::new());
+
+    assert_eq!(result.assignments.len(), 1);
+    assert_eq!(result.assignments[0].locker.number.as_str(), "A-2");
+    assert_eq!(result.unassigned[0].to_string(), "100002");
+  }
+
+  #[test]
+  fn test_students_without_needs_prefer_plain_lockers() {
+    setup();
+    let lockers = vec![
+      ada_locker("A-1", "A"),
+      locker("A-2", "A"),
+      top_locker("A-3", "A"),
+    ];
+    let students = vec![student("100001", 12), student("100002", 11)];
+
+    let result = match_students(&students, &lockers, &ZonePolicy::new());
+
+    let numbers: Vec<&str> = result
+      .assignments
+      .iter()
+      .map(|a| a.locker.number.as_str())
+      .collect();
+    assert_eq!(numbers, ["A-3", "A-1"]);
+  }
+
+  #[test]
+  fn test_unassigned_when_out_of_lockers() {
+    setup();
+    let lockers = vec![locker("A-1", "A")];
+    let students = vec![student("100001", 11), student("100002", 12)];
+
+    let result = match_students(&students, &lockers, &ZonePolicy::new());
+
+    assert_eq!(result.assignments.len(), 1);
+    assert_eq!(result.assignments[0].student_id.to_string(), "100002");
+    assert_eq!(result.unassigned.len(), 1);
+    assert_eq!(result.unassigned[0].to_string(), "100001");
+  }
+
+  #[test]
+  fn test_needs_lower_row_forces_bottom_tier() {
+    setup();
+    let mut needs_lower = student("100001", 10);
+    needs_lower
+      .update_accommodation(Some(AccommodationNeeds {
+        needs_lower_row: true,
+        ..Default::default()
+      }))
+      .unwrap();
+    let students = vec![student("100002", 12), needs_lower];
+    let lockers = vec![top_locker("A-1", "A"), locker("A-2", "A")];
+
+    let result = match_students(&students, &lockers, &ZonePolicy::new());
+
+    let assignment = result
+      .assignments
+      .iter()
+      .find(|a| a.student_id.to_string() == "100001")
+      .unwrap();
+    assert!(assignment.locker.is_bottom_tier());
+  }
+
+  #[test]
+  fn test_structured_accommodations_force_bottom_tier() {
+    setup();
+    let mut needs_lower = student("100001", 10);
+    needs_lower
+      .update_accommodations(vec![Accommodation::BottomTier])
+      .unwrap();
+    let students = vec![student("100002", 12), needs_lower];
+    let lockers = vec![top_locker("A-1", "A"), locker("A-2", "A")];
+
+    let result = match_students(&students, &lockers, &ZonePolicy::new());
+
+    let assignment = result
+      .assignments
+      .iter()
+      .find(|a| a.student_id.to_string() == "100001")
+      .unwrap();
+    assert!(assignment.locker.is_bottom_tier());
+  }
+
+  #[test]
+  fn test_legacy_accommodation_text_still_used() {
+    setup();
+    let mut legacy = student("100001", 10);
+    legacy
+      .update_special_accommodations(Some("Bottom row locker for mobility aid".to_string()))
+      .unwrap();
+    assert!(legacy.accommodation.is_none());
+
+    let students = vec![student("100002", 12), legacy];
+    let lockers = vec![top_locker("A-1", "A"), locker("A-2", "A")];
+
+    let result = match_students(&students, &lockers, &ZonePolicy::new());
+
+    let assignment = result
+      .assignments
+      .iter()
+      .find(|a| a.student_id.to_string() == "100001")
+      .unwrap();
+    assert!(assignment.locker.is_bottom_tier());
+  }
+
+  #[test]
+  fn test_inactive_students_are_skipped() {
+    setup();
+    let mut transferred = student("100001", 12);
+    transferred.deactivate();
+    let students = vec![transferred, student("100002", 9)];
+    let lockers = vec![locker("A-1", "A")];
+
+    let result = match_students(&students, &lockers, &ZonePolicy::new());
+
+    assert_eq!(result.assignments.len(), 1);
+    assert_eq!(result.assignments[0].student_id.to_string(), "100002");
+    assert!(result.unassigned.is_empty());
+  }
+
+  #[test]
+  fn test_same_seed_gives_identical_assignments() {
+    setup();
+    let students: Vec<Student> = (0..20)
+      .map(|i| student(&format!("1001{:02}", i), 9 + (i % 4) as u8))
+      .collect();
+    let lockers: Vec<Locker> = (1..=12).map(|i| locker(&format!("S-{}", i), "S")).collect();
+    let placements = |seed| {
+      let result = match_students_seeded(&students, &lockers, &ZonePolicy::new(), seed);
+      let assigned: Vec<(String, String)> = result
+        .assignments
+        .iter()
+        .map(|a| (a.student_id.to_string(), a.locker.number.to_string()))
+        .collect();
+      let unassigned: Vec<String> = result.unassigned.iter().map(StudentId::to_string).collect();
+      (assigned, unassigned)
+    };
+
+    assert_eq!(placements(Some(42)), placements(Some(42)));
+    assert_eq!(placements(None), placements(None));
+
+    // Shuffling only breaks ties: every senior still gets a locker before any freshman
+    let (assigned, _) = placements(Some(7));
+    let grade_of = |id: &str| {
+      students
+        .iter()
+        .find(|s| s.id.to_string() == id)
+        .unwrap()
+        .grade
+    };
+    let grades: Vec<u8> = assigned.iter().map(|(id, _)| grade_of(id)).collect();
+    assert!(grades.windows(2).all(|pair| pair[0] >= pair[1]));
+  }
+}
@@ -0,0 +1,25 @@
+pub mod availability;
+pub mod expiry;
+pub mod import;
+pub mod matcher;
+pub mod model;
+pub mod number;
+pub mod reservation;
+pub mod session;
+pub mod stats;
+pub mod store;
+pub mod waitlist;
+pub mod zone;
+
+// Re-export the main types for easier access
+pub use availability::{availability, HallwayAvailability};
+pub use expiry::ClaimPolicy;
+pub use import::{import_csv, LockerImportRow, LockerImportSummary};
+pub use matcher::{match_students, match_students_seeded, MatchResult};
+pub use model::{Assignment, Locker, LockerSize};
+pub use number::LockerNumber;
+pub use reservation::{confirm_reservation, reserve_locker};
+pub use session::{rollback_session, run_session, run_session_for, MatchSession};
+pub use stats::{HallwayFill, MatchStats};
+pub use waitlist::promote_next_from_waitlist;
+pub use zone::ZonePolicy;
@@ -0,0 +1,165 @@
+use crate::http::Error;
+use crate::locker::number::FORMAT_HINT;
+use crate::locker::LockerNumber;
+use crate::student::{AccommodationNeeds, StudentId};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A physical locker that can be assigned to a student.
+///
+/// # Field Specifications
+/// - `number`: Locker number as printed on the door (e.g., "A-102"), see `LockerNumber`
+/// - `hallway`: Name of the hallway the locker is in (e.g., "A")
+/// - `row`: Position of the locker's bank along the hallway, if known
+/// - `tier`: Vertical position in the bank, where 1 is the bottom tier
+/// - `size`: Standard or wide locker
+/// - `ada_accessible`: Whether the locker meets ADA accessibility requirements
+///
+/// # Examples
+/// ```
+/// use backend::locker::{Locker, LockerSize};
+///
+/// let locker = Locker::new(
+///   "A-102".to_string(),
+///   "A".to_string(),
+///   1,
+///   LockerSize::Standard,
+///   true,
+/// ).unwrap();
+/// assert!(locker.is_bottom_tier());
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Locker {
+  pub number: LockerNumber,
+  pub hallway: String,
+  #[serde(default)]
+  pub row: Option<u16>,
+  pub tier: u8, // 1 = bottom tier
+  #[serde(default)]
+  pub size: LockerSize,
+  pub ada_accessible: bool,
+}
+
+/// Physical width of a locker.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LockerSize {
+  #[default]
+  Standard,
+  Wide,
+}
+
+impl Locker {
+  /// Creates a new Locker with validation.
+  ///
+  /// # Errors
+  /// Returns `Error::UnprocessableEntity` if the number is malformed, the hallway is empty,
+  /// or the tier is 0.
+  pub fn new(
+    number: String,
+    hallway: String,
+    tier: u8,
+    size: LockerSize,
+    ada_accessible: bool,
+  ) -> Result<Self, Error> {
+    let mut errors = HashMap::new();
+
+    let number = LockerNumber::new(number.trim().to_string());
+    if number.is_err() {
+      errors
+        .entry("number".into())
+        .or_insert_with(Vec::new)
+        .push(FORMAT_HINT.into());
+    }
+
+    if hallway.trim().is_empty() {
+      errors
+        .entry("hallway".into())
+        .or_insert_with(Vec::new)
+        .push("cannot be empty".into());
+    }
+
+    if tier == 0 {
+      errors
+        .entry("tier".into())
+        .or_insert_with(Vec::new)
+        .push("must be 1 or greater".into());
+    }
+
+    if !errors.is_empty() {
+      return Err(Error::UnprocessableEntity { errors });
+    }
+
+    Ok(Locker {
+      number: number?,
+      hallway: hallway.trim().to_string(),
+      row: None,
+      tier,
+      size,
+      ada_accessible,
+    })
+  }
+
+  /// Returns true if `number` is a well-formed locker number (e.g., "A-102" or "215").
+  pub fn is_valid_number(number: &str) -> bool {
+    LockerNumber::is_valid(number)
+  }
+
+  pub fn is_bottom_tier(&self) -> bool {
+    self.tier == 1
+  }
+
+  /// Returns true if this locker meets any accommodation need: it is accessible, bottom
+  /// tier or wide.
+  pub fn meets_special_needs(&self) -> bool {
+    self.ada_accessible || self.is_bottom_tier() || self.size == LockerSize::Wide
+  }
+
+  /// Returns true if this locker satisfies every need in `needs`.
+  pub fn satisfies(&self, needs: &AccommodationNeeds) -> bool {
+    (!needs.needs_accessible || self.ada_accessible)
+      && (!needs.needs_lower_row || self.is_bottom_tier())
+      && (!needs.needs_wide || self.size == LockerSize::Wide)
+  }
+}
+
+/// A locker assigned to a student by the matcher.
+///
+/// `out_of_zone` is set when the student's grade has a zone in the `ZonePolicy`
+/// but no permitted locker was free, so the matcher fell back to another hallway.
+///
+/// `claimed` is set once the student confirms the locker; unclaimed assignments are
+/// released by the expiry sweeper after the configured window.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Assignment {
+  pub student_id: StudentId,
+  pub locker: Locker,
+  pub assigned_at: DateTime<Utc>,
+  pub out_of_zone: bool,
+  #[serde(default)]
+  pub claimed: bool,
+  #[serde(default)]
+  pub claimed_at: Option<DateTime<Utc>>,
+}
+
+impl Assignment {
+  pub fn new(student_id: StudentId, locker: Locker, out_of_zone: bool) -> Self {
+    Assignment {
+      student_id,
+      locker,
+      assigned_at: Utc::now(),
+      out_of_zone,
+      claimed: false,
+      claimed_at: None,
+    }
+  }
+
+  /// Mark the assignment as claimed by the student. Claiming twice keeps the first time.
+  pub fn claim(&mut self) {
+    if !self.claimed {
+      self.claimed = true;
+      self.claimed_at = Some(Utc::now());
+    }
+  }
+}
@@ -0,0 +1,197 @@
+//! Locker numbers and their ordering.
+//!
+//! A locker number is an optional alphabetic prefix, an optional dash, then digits:
+//! "A-102", "B3" or "215". Numbers sort by prefix, then by numeric value, so "B-3"
+//! comes before "B-12" and unprefixed numbers come before any prefixed ones.
+
+use crate::http::Error;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// Longest locker number accepted, including the prefix and dash
+pub const MAX_LOCKER_NUMBER_LEN: usize = 16;
+
+/// Validation message for malformed locker numbers
+pub(crate) const FORMAT_HINT: &str = "must be a letter prefix and a number, like A-102 or 215";
+
+/// A validated locker number, ordered by prefix and then numerically
+///
+/// # Examples
+/// ```
+/// use backend::locker::LockerNumber;
+///
+/// let b3 = LockerNumber::new("B-3".to_string()).unwrap();
+/// let b12 = LockerNumber::new("B-12".to_string()).unwrap();
+/// assert!(b3 < b12);
+/// assert_eq!(b12.prefix(), "B");
+/// assert_eq!(b12.numeric(), 12);
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct LockerNumber {
+  raw: String,
+  prefix: String,
+  numeric: u64,
+}
+
+impl LockerNumber {
+  /// Parses a locker number such as "A-102" or "215".
+  ///
+  /// # Errors
+  /// Returns `Error::UnprocessableEntity` if the number isn't a letter prefix, an optional
+  /// dash and digits, or is longer than `MAX_LOCKER_NUMBER_LEN`.
+  pub fn new(number: String) -> Result<Self, Error> {
+    let Some((prefix, numeric)) = Self::parse(&number) else {
+      return Err(Error::UnprocessableEntity {
+        errors: HashMap::from([("number".into(), vec![FORMAT_HINT.into()])]),
+      });
+    };
+    let prefix = prefix.to_string();
+
+    Ok(LockerNumber {
+      raw: number,
+      prefix,
+      numeric,
+    })
+  }
+
+  /// Split `number` into its prefix and numeric part, or `None` if it's malformed
+  fn parse(number: &str) -> Option<(&str, u64)> {
+    if number.len() > MAX_LOCKER_NUMBER_LEN {
+      return None;
+    }
+    let digits_at = number
+      .find(|c: char| !c.is_ascii_alphabetic())
+      .unwrap_or(number.len());
+    let (prefix, rest) = number.split_at(digits_at);
+    let digits = match rest.strip_prefix('-') {
+      Some(digits) if !prefix.is_empty() => digits,
+      _ => rest,
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+      return None;
+    }
+    Some((prefix, digits.parse().ok()?))
+  }
+
+  /// Returns true if `number` is a well-formed locker number
+  pub fn is_valid(number: &str) -> bool {
+    Self::parse(number).is_some()
+  }
+
+  /// The number as printed on the door
+  pub fn as_str(&self) -> &str {
+    &self.raw
+  }
+
+  /// The alphabetic prefix, empty for purely numeric schemes
+  pub fn prefix(&self) -> &str {
+    &self.prefix
+  }
+
+  /// The value of the numeric part
+  pub fn numeric(&self) -> u64 {
+    self.numeric
+  }
+}
+
+impl fmt::Display for LockerNumber {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.raw)
+  }
+}
+
+impl TryFrom<String> for LockerNumber {
+  type Error = Error;
+
+  fn try_from(number: String) -> Result<Self, Error> {
+    Self::new(number)
+  }
+}
+
+impl From<LockerNumber> for String {
+  fn from(number: LockerNumber) -> String {
+    number.raw
+  }
+}
+
+impl PartialEq for LockerNumber {
+  fn eq(&self, other: &Self) -> bool {
+    self.raw == other.raw
+  }
+}
+
+impl Eq for LockerNumber {}
+
+impl Hash for LockerNumber {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.raw.hash(state);
+  }
+}
+
+impl Ord for LockerNumber {
+  fn cmp(&self, other: &Self) -> Ordering {
+    // The raw text breaks ties such as "A1" and "A-01"
+    (&self.prefix, self.numeric, &self.raw).cmp(&(&other.prefix, other.numeric, &other.raw))
+  }
+}
+
+impl PartialOrd for LockerNumber {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  fn sorted(numbers: &[&str]) -> Vec<String> {
+    let mut numbers: Vec<LockerNumber> = numbers
+      .iter()
+      .map(|n| LockerNumber::new(n.to_string()).unwrap())
+      .collect();
+    numbers.sort();
+    numbers.into_iter().map(String::from).collect()
+  }
+
+  #[test]
+  fn test_mixed_prefixes_sort_by_prefix_then_number() {
+    setup();
+    assert_eq!(
+      sorted(&["B-12", "A-102", "B-3", "A-2", "AB-1", "B3"]),
+      ["A-2", "A-102", "AB-1", "B-3", "B3", "B-12"]
+    );
+  }
+
+  #[test]
+  fn test_numeric_scheme_sorts_numerically() {
+    setup();
+    assert_eq!(
+      sorted(&["215", "9", "1000", "30"]),
+      ["9", "30", "215", "1000"]
+    );
+
+    let number = LockerNumber::new("215".to_string()).unwrap();
+    assert_eq!(number.prefix(), "");
+    assert_eq!(number.numeric(), 215);
+  }
+
+  #[test]
+  fn test_malformed_numbers_are_rejected() {
+    setup();
+    for number in ["", "A", "A-", "-12", "A-1B", "A 102", "12345678901234567"] {
+      assert!(!LockerNumber::is_valid(number), "{}", number);
+      assert!(LockerNumber::new(number.to_string()).is_err());
+    }
+  }
+}
@@ -0,0 +1,206 @@
+//! Short holds on a locker while a student confirms it.
+//!
+//! In the self-service flow a student picks a locker and gets a few minutes to confirm
+//! it before anyone else can. A hold is a `reservation:{number}` key holding the
+//! student's id, set with `SET NX PX` so only one student can hold a locker and the
+//! hold frees itself when it expires.
+
+use crate::http::Error;
+use crate::locker::store::{claim_reserved_locker, holder_key};
+use crate::locker::{Assignment, Locker};
+use crate::redis::{RedisOperations, RedisPool};
+use crate::student::StudentId;
+use log::{debug, info};
+use std::time::Duration;
+
+/// Redis key holding the id of the student a locker is reserved for
+pub fn reservation_key(number: &str) -> String {
+  format!("reservation:{}", number)
+}
+
+/// Hold `locker` for `student_id` for `ttl`
+///
+/// Reserving a locker the student already holds fails like any other conflict; the
+/// hold isn't extended.
+///
+/// # Errors
+/// Returns `Error::Conflict` if the locker is already reserved or assigned.
+pub async fn reserve_locker(
+  pool: &RedisPool,
+  student_id: &StudentId,
+  locker: &Locker,
+  ttl: Duration,
+) -> Result<(), Error> {
+  let number = locker.number.as_str();
+  if pool.exists(&holder_key(number)).await? {
+    return Err(Error::Conflict(format!(
+      "locker {} is already assigned",
+      number
+    )));
+  }
+
+  let set: Option<String> = pool
+    .execute_command(
+      redis::cmd("SET")
+        .arg(pool.prefixed(&reservation_key(number)))
+        .arg(student_id.to_string())
+        .arg("NX")
+        .arg("PX")
+        .arg(ttl.as_millis().max(1) as u64),
+    )
+    .await?;
+  if set.is_none() {
+    return Err(Error::Conflict(format!(
+      "locker {} is already reserved",
+      number
+    )));
+  }
+
+  debug!(
+    "Reserved locker {} for student {} for {:?}",
+    number,
+    student_id.to_string(),
+    ttl
+  );
+  Ok(())
+}
+
+/// Turn `student_id`'s reservation of `locker` into a claimed assignment
+///
+/// The reservation is checked and removed in the same transaction that makes the
+/// assignment, as in `claim_locker`, so a student holding another locker gives it up.
+///
+/// # Errors
+/// Returns `Error::NotFound` if the locker has no reservation, for example because it
+/// expired, and `Error::Conflict` if it's reserved for another student or was assigned
+/// in the meantime.
+pub async fn confirm_reservation(
+  pool: &RedisPool,
+  student_id: &StudentId,
+  locker: &Locker,
+) -> Result<Assignment, Error> {
+  let assignment = claim_reserved_locker(pool, student_id, locker).await?;
+
+  info!(
+    "Student {} confirmed their reservation of locker {}",
+    student_id.to_string(),
+    locker.number
+  );
+  Ok(assignment)
+}
+
+// Tests
+#[cfg(all(test, feature = "redis-tests"))]
+mod tests {
+  use super::*;
+  use crate::locker::store::{claim_locker, get_assignment, release_assignment};
+  use crate::locker::LockerSize;
+  use crate::{init_env, init_logging};
+
+  async fn setup() -> RedisPool {
+    let _ = init_logging(); // Ignore error if already initialized
+    init_env().unwrap();
+    RedisPool::init().await.unwrap()
+  }
+
+  fn locker(number: &str) -> Locker {
+    Locker::new(
+      number.to_string(),
+      "V".to_string(),
+      1,
+      LockerSize::Standard,
+      false,
+    )
+    .unwrap()
+  }
+
+  fn student(id: &str) -> StudentId {
+    StudentId::new(id.to_string()).unwrap()
+  }
+
+  #[tokio::test]
+  async fn test_second_reservation_conflicts_until_expiry() {
+    let pool = setup().await;
+    let locker = locker("V-1");
+    pool.del(&reservation_key("V-1")).await.unwrap();
+
+    reserve_locker(
+      &pool,
+      &student("970001"),
+      &locker,
+      Duration::from_millis(200),
+    )
+    .await
+    .unwrap();
+    let second = reserve_locker(&pool, &student("970002"), &locker, Duration::from_secs(60)).await;
+    assert!(matches!(second, Err(Error::Conflict(_))));
+    let ttl = pool.ttl(&reservation_key("V-1")).await.unwrap();
+    assert!((0..=1).contains(&ttl), "{}", ttl);
+
+    // The hold frees itself
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    reserve_locker(&pool, &student("970002"), &locker, Duration::from_secs(60))
+      .await
+      .unwrap();
+    pool.del(&reservation_key("V-1")).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_confirm_promotes_reservation_to_assignment() {
+    let pool = setup().await;
+    let locker = locker("V-2");
+    let holder = student("970003");
+    pool.del(&reservation_key("V-2")).await.unwrap();
+
+    // Nothing to confirm yet
+    let missing = confirm_reservation(&pool, &holder, &locker).await;
+    assert!(matches!(missing, Err(Error::NotFound)));
+
+    reserve_locker(&pool, &holder, &locker, Duration::from_secs(60))
+      .await
+      .unwrap();
+    let other = confirm_reservation(&pool, &student("970004"), &locker).await;
+    assert!(matches!(other, Err(Error::Conflict(_))));
+
+    let assignment = confirm_reservation(&pool, &holder, &locker).await.unwrap();
+    assert!(assignment.claimed);
+    assert_eq!(
+      get_assignment(&pool, &holder)
+        .await
+        .unwrap()
+        .unwrap()
+        .locker
+        .number
+        .as_str(),
+      "V-2"
+    );
+    assert!(!pool.exists(&reservation_key("V-2")).await.unwrap());
+
+    // An assigned locker can't be reserved
+    let reserved =
+      reserve_locker(&pool, &student("970004"), &locker, Duration::from_secs(60)).await;
+    assert!(matches!(reserved, Err(Error::Conflict(_))));
+    release_assignment(&pool, &assignment).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_reserved_locker_cannot_be_claimed_by_another_student() {
+    let pool = setup().await;
+    let locker = locker("V-3");
+    let holder = student("970005");
+    let other = student("970006");
+    pool.del(&reservation_key("V-3")).await.unwrap();
+
+    reserve_locker(&pool, &holder, &locker, Duration::from_secs(60))
+      .await
+      .unwrap();
+    let taken = claim_locker(&pool, &other, &locker).await;
+    assert!(matches!(taken, Err(Error::Conflict(_))), "{:?}", taken);
+    assert!(get_assignment(&pool, &other).await.unwrap().is_none());
+
+    // The hold still lets its student confirm
+    let assignment = confirm_reservation(&pool, &holder, &locker).await.unwrap();
+    assert!(!pool.exists(&reservation_key("V-3")).await.unwrap());
+    release_assignment(&pool, &assignment).await.unwrap();
+  }
+}
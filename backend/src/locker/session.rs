@@ -0,0 +1,505 @@
+//! Named matching runs.
+//!
+//! A match session records everything one run of the matcher did, so admins can
+//! inspect the result for a term and roll it back.
+//!
+//! Key scheme:
+//! - `match_session:{name}`: JSON `MatchSession` record
+//! - `match_stats:{name}`: JSON `MatchStats` computed when the session ran
+//!
+//! Running a session also replaces the waitlist with its unassigned students.
+
+use crate::http::Error;
+use crate::locker::store::{assignment_key, get_assignment, holder_key};
+use crate::locker::waitlist::{replace_waitlist, WAITLIST_KEY};
+use crate::locker::{match_students_seeded, Assignment, Locker, MatchStats, ZonePolicy};
+use crate::redis::{to_json, RedisOperations, RedisPool};
+use crate::student::{load_many, AccommodationNeeds, Student, StudentId};
+use chrono::{DateTime, Utc};
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+/// The assignments and leftovers of one named matching run
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MatchSession {
+  pub name: String,
+  pub assignments: Vec<Assignment>,
+  pub unassigned: Vec<StudentId>,
+  /// Seed that broke priority ties, if any; rerunning with it reproduces the session
+  #[serde(default)]
+  pub seed: Option<u64>,
+  pub created_at: DateTime<Utc>,
+  /// Lockers offered to the run in each hallway
+  #[serde(default)]
+  pub hallway_capacity: BTreeMap<String, usize>,
+  /// Locker needs of the students who had any, keyed by student id; notes are dropped
+  #[serde(default)]
+  pub needs: BTreeMap<String, AccommodationNeeds>,
+}
+
+/// Redis key holding the JSON record for a match session
+pub fn session_key(name: &str) -> String {
+  format!("match_session:{}", name)
+}
+
+/// Redis key holding the statistics of a match session
+pub fn stats_key(name: &str) -> String {
+  format!("match_stats:{}", name)
+}
+
+/// Match `students` to `lockers`, store the assignments and record them as session `name`
+///
+/// The assignments, locker holders, waitlist and session record are written in one
+/// transaction. A student who already held a locker gives it up, as with `claim_locker`.
+/// The lockers must be free: if one is claimed by another student before the write, the
+/// run fails and nothing is written.
+///
+/// Ties between students of equal priority are broken by input order, or by a shuffle
+/// seeded with `seed` when one is given (see `match_students_seeded`).
+///
+/// With `dry_run` the same session is computed and returned, but nothing is written, so
+/// staff can preview the assignments and unassigned list before committing them.
+///
+/// # Errors
+/// Returns `Error::Conflict` if a session with this name already exists, or if one of
+/// the matched lockers is held by another student.
+pub async fn run_session(
+  pool: &RedisPool,
+  name: &str,
+  students: &[Student],
+  lockers: &[Locker],
+  policy: &ZonePolicy,
+  seed: Option<u64>,
+  dry_run: bool,
+) -> Result<MatchSession, Error> {
+  if pool.exists(&session_key(name)).await? {
+    return Err(Error::Conflict(format!(
+      "match session {} already exists",
+      name
+    )));
+  }
+
+  let result = match_students_seeded(students, lockers, policy, seed);
+  let mut hallway_capacity = BTreeMap::new();
+  for locker in lockers {
+    *hallway_capacity.entry(locker.hallway.clone()).or_insert(0) += 1;
+  }
+  let needs = students
+    .iter()
+    .filter(|s| s.active)
+    .map(|s| (s.id.to_string(), s.accommodation_needs()))
+    .filter(|(_, needs)| needs.has_locker_needs())
+    .map(|(id, needs)| {
+      (
+        id,
+        AccommodationNeeds {
+          notes: None,
+          ..needs
+        },
+      )
+    })
+    .collect();
+  let session = MatchSession {
+    name: name.to_string(),
+    assignments: result.assignments,
+    unassigned: result.unassigned,
+    seed,
+    created_at: Utc::now(),
+    hallway_capacity,
+    needs,
+  };
+
+  if dry_run {
+    info!(
+      "Dry run of match session {} would assign {} students, {} unassigned",
+      name,
+      session.assignments.len(),
+      session.unassigned.len()
+    );
+    return Ok(session);
+  }
+
+  // Watch everything the run reads or overwrites, so a `claim_locker` landing between
+  // the match and the write makes the transaction retry and see it
+  let mut watched = vec![session_key(name), WAITLIST_KEY.to_string()];
+  for assignment in &session.assignments {
+    watched.push(assignment_key(&assignment.student_id));
+    watched.push(holder_key(assignment.locker.number.as_str()));
+  }
+  let watched: Vec<&str> = watched.iter().map(String::as_str).collect();
+  let offered: HashSet<&str> = session
+    .assignments
+    .iter()
+    .map(|assignment| assignment.locker.number.as_str())
+    .collect();
+
+  pool
+    .transaction::<(), _, _>(&watched, |mut conn| {
+      let session = &session;
+      let offered = &offered;
+
+      async move {
+        let exists: bool = redis::cmd("EXISTS")
+          .arg(pool.prefixed(&session_key(name)))
+          .query_async(&mut conn)
+          .await?;
+        if exists {
+          return Err(Error::Conflict(format!(
+            "match session {} already exists",
+            name
+          )));
+        }
+
+        let mut pipe = redis::pipe();
+        for assignment in &session.assignments {
+          let id = assignment.student_id.to_string();
+          let number = assignment.locker.number.as_str();
+          let holder = pool.prefixed(&holder_key(number));
+          let student_assignment = pool.prefixed(&assignment_key(&assignment.student_id));
+
+          let current: Option<String> = redis::cmd("GET")
+            .arg(&holder)
+            .query_async(&mut conn)
+            .await?;
+          if current.is_some_and(|current| current != id) {
+            return Err(Error::Conflict(format!(
+              "locker {} was assigned to another student during the run",
+              number
+            )));
+          }
+
+          // Free the locker the student held before, unless this run hands it to someone
+          let previous: Option<String> = redis::cmd("GET")
+            .arg(&student_assignment)
+            .query_async(&mut conn)
+            .await?;
+          if let Some(previous) =
+            previous.and_then(|json| serde_json::from_str::<Assignment>(&json).ok())
+          {
+            let previous = previous.locker.number.as_str();
+            if !offered.contains(previous) {
+              pipe.del(pool.prefixed(&holder_key(previous))).ignore();
+            }
+          }
+
+          pipe
+            .set(&student_assignment, to_json(assignment)?)
+            .ignore()
+            .set(&holder, &id)
+            .ignore();
+        }
+        replace_waitlist(pool, &mut pipe, &session.unassigned);
+        pipe
+          .set(pool.prefixed(&session_key(name)), to_json(session)?)
+          .ignore()
+          .set(pool.prefixed(&stats_key(name)), to_json(&session.stats())?)
+          .ignore();
+        Ok(pipe)
+      }
+    })
+    .await?;
+
+  info!(
+    "Match session {} assigned {} students, {} unassigned",
+    name,
+    session.assignments.len(),
+    session.unassigned.len()
+  );
+  Ok(session)
+}
+
+/// Like `run_session`, loading the roster from Redis by id
+///
+/// The students are fetched with `load_many` in one round-trip; ids with no stored
+/// record are skipped.
+pub async fn run_session_for(
+  pool: &RedisPool,
+  name: &str,
+  student_ids: &[StudentId],
+  lockers: &[Locker],
+  policy: &ZonePolicy,
+  seed: Option<u64>,
+  dry_run: bool,
+) -> Result<MatchSession, Error> {
+  let students = load_many(pool, student_ids).await?;
+  if students.len() < student_ids.len() {
+    debug!(
+      "Match session {}: {} of {} students have no record",
+      name,
+      student_ids.len() - students.len(),
+      student_ids.len()
+    );
+  }
+  run_session(pool, name, &students, lockers, policy, seed, dry_run).await
+}
+
+/// Load a match session, returning `Ok(None)` if there is none
+pub async fn get_session(pool: &RedisPool, name: &str) -> Result<Option<MatchSession>, Error> {
+  match pool.get_json(&session_key(name)).await {
+    Ok(session) => Ok(Some(session)),
+    Err(Error::RedisKeyNotFound(_)) => Ok(None),
+    Err(e) => Err(e),
+  }
+}
+
+/// Load the statistics stored when session `name` ran, returning `Ok(None)` if there are none
+pub async fn get_stats(pool: &RedisPool, name: &str) -> Result<Option<MatchStats>, Error> {
+  match pool.get_json(&stats_key(name)).await {
+    Ok(stats) => Ok(Some(stats)),
+    Err(Error::RedisKeyNotFound(_)) => Ok(None),
+    Err(e) => Err(e),
+  }
+}
+
+/// Delete the assignments created by session `name`, then the session itself
+///
+/// Assignments that have been replaced since the session ran are left alone. The
+/// waitlist is cleared. Returns
+/// the number of assignments removed.
+///
+/// # Errors
+/// Returns `Error::NotFound` if there is no session with this name.
+pub async fn rollback_session(pool: &RedisPool, name: &str) -> Result<usize, Error> {
+  let session = get_session(pool, name).await?.ok_or(Error::NotFound)?;
+
+  let mut pipe = redis::pipe();
+  let mut removed = 0;
+  for assignment in &session.assignments {
+    let current = get_assignment(pool, &assignment.student_id).await?;
+    let unchanged = current.is_some_and(|current| {
+      current.locker.number == assignment.locker.number
+        && current.assigned_at == assignment.assigned_at
+    });
+    if !unchanged {
+      debug!(
+        "Keeping assignment of student {}, changed since session {}",
+        assignment.student_id.to_string(),
+        name
+      );
+      continue;
+    }
+
+    pipe
+      .del(pool.prefixed(&assignment_key(&assignment.student_id)))
+      .ignore()
+      .del(pool.prefixed(&holder_key(assignment.locker.number.as_str())))
+      .ignore();
+    removed += 1;
+  }
+  pipe
+    .del(pool.prefixed(&session_key(name)))
+    .ignore()
+    .del(pool.prefixed(&stats_key(name)))
+    .ignore()
+    .del(pool.prefixed(WAITLIST_KEY))
+    .ignore();
+  pool.execute_pipeline::<()>(&pipe).await?;
+
+  info!(
+    "Rolled back match session {}, removing {} assignments",
+    name, removed
+  );
+  Ok(removed)
+}
+
+// Tests
+#[cfg(all(test, feature = "redis-tests"))]
+mod tests {
+  use super::*;
+  use crate::locker::LockerSize;
+  use crate::{init_env, init_logging};
+  use chrono::Datelike;
+
+  async fn setup() -> RedisPool {
+    let _ = init_logging(); // Ignore error if already initialized
+    init_env().unwrap();
+    RedisPool::init().await.unwrap()
+  }
+
+  fn student(id: &str) -> Student {
+    Student::new(
+      id.to_string(),
+      "Session".to_string(),
+      "Student".to_string(),
+      format!("{}@csxlabs.edu", id),
+      10,
+      Utc::now().year() as u16 + 2,
+      None,
+    )
+    .unwrap()
+  }
+
+  fn locker(number: &str) -> Locker {
+    Locker::new(
+      number.to_string(),
+      "M".to_string(),
+      1,
+      LockerSize::Standard,
+      false,
+    )
+    .unwrap()
+  }
+
+  #[tokio::test]
+  async fn test_session_records_and_rolls_back_assignments() {
+    let pool = setup().await;
+    let name = "test-session";
+    pool.del(&session_key(name)).await.unwrap();
+    let students = [student("920001"), student("920002"), student("920003")];
+    let lockers = [locker("M-1"), locker("M-2")];
+
+    let session = run_session(
+      &pool,
+      name,
+      &students,
+      &lockers,
+      &ZonePolicy::new(),
+      None,
+      false,
+    )
+    .await
+    .unwrap();
+    assert_eq!(session.assignments.len(), 2);
+    assert_eq!(session.unassigned.len(), 1);
+    for assignment in &session.assignments {
+      assert!(get_assignment(&pool, &assignment.student_id)
+        .await
+        .unwrap()
+        .is_some());
+    }
+    assert_eq!(get_stats(&pool, name).await.unwrap(), Some(session.stats()));
+
+    // Names are not reused
+    let rerun = run_session(
+      &pool,
+      name,
+      &students,
+      &lockers,
+      &ZonePolicy::new(),
+      None,
+      false,
+    )
+    .await;
+    assert!(matches!(rerun, Err(Error::Conflict(_))));
+
+    assert_eq!(rollback_session(&pool, name).await.unwrap(), 2);
+    for assignment in &session.assignments {
+      assert!(get_assignment(&pool, &assignment.student_id)
+        .await
+        .unwrap()
+        .is_none());
+    }
+    assert!(get_session(&pool, name).await.unwrap().is_none());
+    assert!(get_stats(&pool, name).await.unwrap().is_none());
+  }
+
+  #[tokio::test]
+  async fn test_session_frees_previous_lockers_and_respects_holders() {
+    use crate::locker::store::claim_locker;
+
+    let pool = setup().await;
+    for name in ["test-session-moves", "test-session-taken"] {
+      pool.del(&session_key(name)).await.unwrap();
+    }
+    let moving = student("920008");
+    claim_locker(&pool, &moving.id, &locker("M-5"))
+      .await
+      .unwrap();
+
+    // The student's old locker is released when the run moves them
+    run_session(
+      &pool,
+      "test-session-moves",
+      std::slice::from_ref(&moving),
+      &[locker("M-6")],
+      &ZonePolicy::new(),
+      None,
+      false,
+    )
+    .await
+    .unwrap();
+    assert!(!pool.exists(&holder_key("M-5")).await.unwrap());
+    let holder: Option<String> = pool.get_opt(&holder_key("M-6")).await.unwrap();
+    assert_eq!(holder.as_deref(), Some("920008"));
+
+    // A locker someone else holds isn't taken from them
+    let other = student("920009");
+    let run = run_session(
+      &pool,
+      "test-session-taken",
+      std::slice::from_ref(&other),
+      &[locker("M-6")],
+      &ZonePolicy::new(),
+      None,
+      false,
+    )
+    .await;
+    assert!(matches!(run, Err(Error::Conflict(_))), "{:?}", run);
+    assert!(get_assignment(&pool, &other.id).await.unwrap().is_none());
+    assert!(get_session(&pool, "test-session-taken")
+      .await
+      .unwrap()
+      .is_none());
+
+    rollback_session(&pool, "test-session-moves").await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_run_session_for_loads_students_by_id() {
+    let pool = setup().await;
+    let students = [student("920006"), student("920007")];
+    for student in &students {
+      crate::student::store::save(&pool, student).await.unwrap();
+    }
+    let ids: Vec<StudentId> = students.iter().map(|s| s.id.clone()).collect();
+
+    let session = run_session_for(
+      &pool,
+      "test-run-for",
+      &ids,
+      &[locker("M-4")],
+      &ZonePolicy::new(),
+      None,
+      true,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(session.assignments.len(), 1);
+    assert_eq!(session.unassigned.len(), 1);
+    for student in &students {
+      crate::student::store::delete(&pool, &student.id)
+        .await
+        .unwrap();
+    }
+  }
+
+  #[tokio::test]
+  async fn test_dry_run_writes_nothing() {
+    let pool = setup().await;
+    let name = "test-dry-run";
+    let students = [student("920004"), student("920005")];
+    let lockers = [locker("M-3")];
+
+    let session = run_session(
+      &pool,
+      name,
+      &students,
+      &lockers,
+      &ZonePolicy::new(),
+      None,
+      true,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(session.assignments.len(), 1);
+    assert_eq!(session.unassigned.len(), 1);
+    assert!(get_session(&pool, name).await.unwrap().is_none());
+    for student in &students {
+      assert!(get_assignment(&pool, &student.id).await.unwrap().is_none());
+    }
+    assert!(!pool.exists(&holder_key("M-3")).await.unwrap());
+  }
+}
@@ -0,0 +1,150 @@
+//! Summary statistics for a match session.
+//!
+//! Everything is computed from the `MatchSession` record alone: it keeps the locker
+//! needs of the students it considered and how many lockers each hallway offered, so
+//! stats can be recomputed for old sessions without the roster of the time.
+
+use crate::locker::MatchSession;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// How full one hallway's offered lockers are after a run
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+pub struct HallwayFill {
+  /// Lockers in the hallway offered to the run
+  pub offered: usize,
+  pub assigned: usize,
+  /// `assigned / offered`, between 0 and 1
+  pub fill_rate: f64,
+}
+
+/// What a match session achieved
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct MatchStats {
+  pub assigned: usize,
+  pub unassigned: usize,
+  /// Students with locker needs (accessible, lower row or wide)
+  pub accommodations_requested: usize,
+  /// Of those, students given a locker that meets every need
+  pub accommodations_satisfied: usize,
+  pub out_of_zone: usize,
+  /// Fill rate of every hallway the run was offered lockers in, keyed by hallway
+  pub hallways: BTreeMap<String, HallwayFill>,
+}
+
+impl MatchSession {
+  /// Compute the session's statistics
+  pub fn stats(&self) -> MatchStats {
+    let mut hallways: BTreeMap<String, HallwayFill> = self
+      .hallway_capacity
+      .iter()
+      .map(|(hallway, offered)| {
+        (
+          hallway.clone(),
+          HallwayFill {
+            offered: *offered,
+            ..HallwayFill::default()
+          },
+        )
+      })
+      .collect();
+    for assignment in &self.assignments {
+      hallways
+        .entry(assignment.locker.hallway.clone())
+        .or_default()
+        .assigned += 1;
+    }
+    for fill in hallways.values_mut() {
+      // Older sessions have no capacity recorded; count what was assigned as offered
+      fill.offered = fill.offered.max(fill.assigned);
+      if fill.offered > 0 {
+        fill.fill_rate = fill.assigned as f64 / fill.offered as f64;
+      }
+    }
+
+    let accommodations_satisfied = self
+      .assignments
+      .iter()
+      .filter(|assignment| {
+        self
+          .needs
+          .get(&assignment.student_id.to_string())
+          .is_some_and(|needs| assignment.locker.satisfies(needs))
+      })
+      .count();
+
+    MatchStats {
+      assigned: self.assignments.len(),
+      unassigned: self.unassigned.len(),
+      accommodations_requested: self.needs.len(),
+      accommodations_satisfied,
+      out_of_zone: self.assignments.iter().filter(|a| a.out_of_zone).count(),
+      hallways,
+    }
+  }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+  use crate::locker::{Assignment, Locker, LockerSize};
+  use crate::student::{AccommodationNeeds, StudentId};
+  use chrono::Utc;
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  fn assignment(student_id: &str, number: &str, ada: bool, out_of_zone: bool) -> Assignment {
+    let hallway = number.split('-').next().unwrap().to_string();
+    let locker = Locker::new(number.to_string(), hallway, 1, LockerSize::Standard, ada).unwrap();
+    Assignment::new(
+      StudentId::new(student_id.to_string()).unwrap(),
+      locker,
+      out_of_zone,
+    )
+  }
+
+  #[test]
+  fn test_stats_for_hand_built_session() {
+    setup();
+    let accessible = AccommodationNeeds {
+      needs_accessible: true,
+      ..AccommodationNeeds::default()
+    };
+    let session = MatchSession {
+      name: "stats".to_string(),
+      assignments: vec![
+        assignment("960001", "A-1", true, false),
+        assignment("960002", "A-2", false, false),
+        assignment("960003", "B-1", false, true),
+      ],
+      unassigned: vec![StudentId::new("960004".to_string()).unwrap()],
+      seed: None,
+      created_at: Utc::now(),
+      hallway_capacity: BTreeMap::from([("A".to_string(), 2), ("B".to_string(), 4)]),
+      needs: BTreeMap::from([
+        ("960001".to_string(), accessible.clone()),
+        ("960004".to_string(), accessible),
+      ]),
+    };
+
+    let stats = session.stats();
+    assert_eq!(stats.assigned, 3);
+    assert_eq!(stats.unassigned, 1);
+    assert_eq!(stats.out_of_zone, 1);
+    assert_eq!(stats.accommodations_requested, 2);
+    assert_eq!(stats.accommodations_satisfied, 1);
+    assert_eq!(
+      stats.hallways["A"],
+      HallwayFill {
+        offered: 2,
+        assigned: 2,
+        fill_rate: 1.0,
+      }
+    );
+    assert_eq!(stats.hallways["B"].fill_rate, 0.25);
+  }
+}
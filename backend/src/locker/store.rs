@@ -0,0 +1,580 @@
+//! Redis persistence for lockers and assignments.
+//!
+//! Key scheme:
+//! - `locker:{number}`: JSON `Locker` record
+//! - `hallway:{name}`: set of locker numbers in the hallway
+//! - `assignment:{student_id}`: JSON `Assignment` record
+//! - `holder:{number}`: id of the student a locker is assigned to
+
+use crate::http::Error;
+use crate::locker::reservation::reservation_key;
+use crate::locker::{Assignment, Locker};
+use crate::redis::{RedisOperations, RedisPool};
+use crate::student::StudentId;
+use log::debug;
+use std::collections::HashSet;
+
+/// Redis key holding the JSON record for a locker
+pub fn locker_key(number: &str) -> String {
+  format!("locker:{}", number)
+}
+
+/// Redis key of the set of locker numbers in a hallway
+pub fn hallway_key(hallway: &str) -> String {
+  format!("hallway:{}", hallway)
+}
+
+/// Redis key holding the JSON assignment for a student
+pub fn assignment_key(student_id: &StudentId) -> String {
+  format!("assignment:{}", student_id.to_string())
+}
+
+/// Redis key holding the id of the student a locker is assigned to
+pub fn holder_key(number: &str) -> String {
+  format!("holder:{}", number)
+}
+
+/// Store a locker record in Redis and add it to its hallway index
+///
+/// Overwriting a locker that moved hallways removes it from the old hallway's index in
+/// the same transaction.
+pub async fn save_locker(pool: &RedisPool, locker: &Locker) -> Result<(), Error> {
+  let key = locker_key(locker.number.as_str());
+  let locker_json = serde_json::to_string(locker)
+    .map_err(|e| Error::RedisParseError(format!("Failed to serialize locker: {}", e)))?;
+
+  pool
+    .transaction::<(), _, _>(&[&key], |mut conn| {
+      let key = pool.prefixed(&key);
+      let number = locker.number.as_str();
+      let locker_json = locker_json.clone();
+
+      async move {
+        let previous: Option<String> = redis::cmd("GET").arg(&key).query_async(&mut conn).await?;
+
+        let mut pipe = redis::pipe();
+        if let Some(previous) = previous.and_then(|json| serde_json::from_str::<Locker>(&json).ok())
+        {
+          if previous.hallway != locker.hallway {
+            pipe
+              .srem(pool.prefixed(&hallway_key(&previous.hallway)), number)
+              .ignore();
+          }
+        }
+        pipe
+          .set(&key, locker_json)
+          .ignore()
+          .sadd(pool.prefixed(&hallway_key(&locker.hallway)), number)
+          .ignore();
+        Ok(pipe)
+      }
+    })
+    .await
+}
+
+/// List every locker indexed under `hallway`
+pub async fn list_lockers_by_hallway(
+  pool: &RedisPool,
+  hallway: &str,
+) -> Result<Vec<Locker>, Error> {
+  let numbers: Vec<String> = pool.smembers(&hallway_key(hallway)).await?;
+
+  let (lockers, missing) = get_lockers(pool, &numbers).await?;
+  if !missing.is_empty() {
+    debug!(
+      "Hallway {} index references missing lockers: {:?}",
+      hallway, missing
+    );
+  }
+
+  Ok(lockers)
+}
+
+/// Store a student's assignment and mark its locker as held by them, in one transaction
+///
+/// This overwrites without checking the locker's current holder; use `claim_locker` to
+/// take a locker that may belong to someone else.
+pub async fn save_assignment(pool: &RedisPool, assignment: &Assignment) -> Result<(), Error> {
+  let assignment_json = serde_json::to_string(assignment)
+    .map_err(|e| Error::RedisParseError(format!("Failed to serialize assignment: {}", e)))?;
+
+  let mut pipe = redis::pipe();
+  pipe
+    .set(
+      pool.prefixed(&assignment_key(&assignment.student_id)),
+      assignment_json,
+    )
+    .ignore()
+    .set(
+      pool.prefixed(&holder_key(assignment.locker.number.as_str())),
+      assignment.student_id.to_string(),
+    )
+    .ignore();
+  pool.execute_pipeline(&pipe).await
+}
+
+/// Load a student's assignment, returning `Ok(None)` if they have none
+pub async fn get_assignment(
+  pool: &RedisPool,
+  student_id: &StudentId,
+) -> Result<Option<Assignment>, Error> {
+  match pool.get_json(&assignment_key(student_id)).await {
+    Ok(assignment) => Ok(Some(assignment)),
+    Err(Error::RedisKeyNotFound(_)) => Ok(None),
+    Err(e) => Err(e),
+  }
+}
+
+/// Atomically assign `locker` to a student and mark it claimed.
+///
+/// The locker's holder, its reservation and the student's assignment are watched, so
+/// of two students racing for the same locker exactly one succeeds. A student who
+/// already holds a different locker gives it up, and a reservation of the locker for
+/// the student is used up.
+///
+/// # Errors
+/// Returns `Error::Conflict` if the locker is held by or reserved for another student.
+pub async fn claim_locker(
+  pool: &RedisPool,
+  student_id: &StudentId,
+  locker: &Locker,
+) -> Result<Assignment, Error> {
+  claim(pool, student_id, locker, false).await
+}
+
+/// Like `claim_locker`, but only if the locker is reserved for the student
+///
+/// # Errors
+/// Returns `Error::NotFound` if the locker has no reservation, and `Error::Conflict` if
+/// it's reserved for or held by another student.
+pub(crate) async fn claim_reserved_locker(
+  pool: &RedisPool,
+  student_id: &StudentId,
+  locker: &Locker,
+) -> Result<Assignment, Error> {
+  claim(pool, student_id, locker, true).await
+}
+
+/// Claim `locker` for a student in one transaction, requiring their reservation of it
+/// if `reserved`
+async fn claim(
+  pool: &RedisPool,
+  student_id: &StudentId,
+  locker: &Locker,
+  reserved: bool,
+) -> Result<Assignment, Error> {
+  let id = student_id.to_string();
+  let holder = holder_key(locker.number.as_str());
+  let reservation = reservation_key(locker.number.as_str());
+  let student_assignment = assignment_key(student_id);
+
+  let mut assignment = Assignment::new(student_id.clone(), locker.clone(), false);
+  assignment.claim();
+  let assignment_json = serde_json::to_string(&assignment)
+    .map_err(|e| Error::RedisParseError(format!("Failed to serialize assignment: {}", e)))?;
+
+  pool
+    .transaction::<(), _, _>(&[&holder, &reservation, &student_assignment], |mut conn| {
+      let id = id.clone();
+      let number = locker.number.clone();
+      let holder = pool.prefixed(&holder);
+      let reservation = pool.prefixed(&reservation);
+      let student_assignment = pool.prefixed(&student_assignment);
+      let assignment_json = assignment_json.clone();
+
+      async move {
+        let reserved_for: Option<String> = redis::cmd("GET")
+          .arg(&reservation)
+          .query_async(&mut conn)
+          .await?;
+        match reserved_for {
+          None if reserved => return Err(Error::NotFound),
+          Some(reserved_for) if reserved_for != id => {
+            return Err(Error::Conflict(format!(
+              "locker {} is reserved for another student",
+              number
+            )));
+          }
+          _ => {}
+        }
+
+        let current: Option<String> = redis::cmd("GET")
+          .arg(&holder)
+          .query_async(&mut conn)
+          .await?;
+        if current.is_some_and(|current| current != id) {
+          return Err(Error::Conflict(format!(
+            "locker {} is already assigned",
+            number
+          )));
+        }
+
+        let mut pipe = redis::pipe();
+
+        // Release the locker the student held before, if any
+        let previous: Option<String> = redis::cmd("GET")
+          .arg(&student_assignment)
+          .query_async(&mut conn)
+          .await?;
+        if let Some(previous) =
+          previous.and_then(|json| serde_json::from_str::<Assignment>(&json).ok())
+        {
+          if previous.locker.number != number {
+            pipe
+              .del(pool.prefixed(&holder_key(previous.locker.number.as_str())))
+              .ignore();
+          }
+        }
+
+        pipe
+          .set(&holder, &id)
+          .ignore()
+          .set(&student_assignment, &assignment_json)
+          .ignore()
+          .del(&reservation)
+          .ignore();
+        Ok(pipe)
+      }
+    })
+    .await?;
+
+  debug!("Student {} claimed locker {}", id, locker.number);
+  Ok(assignment)
+}
+
+/// Atomically trade the lockers of two students
+///
+/// Both assignment keys are watched, so the swap is retried if either changes midway.
+/// The new assignments are claimed with a fresh `assigned_at`, and `out_of_zone` is
+/// cleared since both students agreed to the trade. Returns the new assignments of
+/// `student_a` and `student_b`, in that order.
+///
+/// # Errors
+/// Returns `Error::Conflict` if either student has no assignment.
+pub async fn swap_assignments(
+  pool: &RedisPool,
+  student_a: &StudentId,
+  student_b: &StudentId,
+) -> Result<(Assignment, Assignment), Error> {
+  let key_a = assignment_key(student_a);
+  let key_b = assignment_key(student_b);
+  let swapped = std::sync::Mutex::new(None);
+
+  pool
+    .transaction::<(), _, _>(&[&key_a, &key_b], |mut conn| {
+      let keys = [pool.prefixed(&key_a), pool.prefixed(&key_b)];
+      let ids = [student_a, student_b];
+      let swapped = &swapped;
+
+      async move {
+        let mut current = Vec::with_capacity(2);
+        for (key, id) in keys.iter().zip(ids) {
+          let json: Option<String> = redis::cmd("GET").arg(key).query_async(&mut conn).await?;
+          let assignment = json
+            .and_then(|json| serde_json::from_str::<Assignment>(&json).ok())
+            .ok_or_else(|| {
+              Error::Conflict(format!(
+                "student {} has no locker assignment",
+                id.to_string()
+              ))
+            })?;
+          current.push(assignment);
+        }
+
+        let mut pipe = redis::pipe();
+        let mut new = Vec::with_capacity(2);
+        for (key, (id, other)) in keys.iter().zip(ids.iter().zip(current.iter().rev())) {
+          let mut assignment = Assignment::new((*id).clone(), other.locker.clone(), false);
+          assignment.claim();
+          let assignment_json = serde_json::to_string(&assignment).map_err(|e| {
+            Error::RedisParseError(format!("Failed to serialize assignment: {}", e))
+          })?;
+          pipe
+            .set(key, assignment_json)
+            .ignore()
+            .set(
+              pool.prefixed(&holder_key(other.locker.number.as_str())),
+              id.to_string(),
+            )
+            .ignore();
+          new.push(assignment);
+        }
+
+        let b = new.pop();
+        *swapped.lock().unwrap_or_else(|e| e.into_inner()) = new.pop().zip(b);
+        Ok(pipe)
+      }
+    })
+    .await?;
+
+  let (a, b) = swapped
+    .into_inner()
+    .unwrap_or_else(|e| e.into_inner())
+    .expect("a committed swap records both assignments");
+  debug!(
+    "Swapped lockers {} and {} between students {} and {}",
+    b.locker.number,
+    a.locker.number,
+    student_a.to_string(),
+    student_b.to_string()
+  );
+  Ok((a, b))
+}
+
+/// Delete a student's assignment and free its locker
+pub async fn release_assignment(pool: &RedisPool, assignment: &Assignment) -> Result<(), Error> {
+  let mut pipe = redis::pipe();
+  pipe
+    .del(pool.prefixed(&assignment_key(&assignment.student_id)))
+    .ignore()
+    .del(pool.prefixed(&holder_key(assignment.locker.number.as_str())))
+    .ignore();
+  pool.execute_pipeline(&pipe).await
+}
+
+/// Load every stored assignment
+pub async fn list_assignments(pool: &RedisPool) -> Result<Vec<Assignment>, Error> {
+  let keys = pool.scan_collect("assignment:*").await?;
+  let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+  let values: Vec<Option<String>> = pool.mget(&keys).await?;
+
+  let mut assignments = Vec::with_capacity(keys.len());
+  // Keys removed between SCAN and MGET come back as nil
+  for assignment_json in values.into_iter().flatten() {
+    let assignment: Assignment = serde_json::from_str(&assignment_json)
+      .map_err(|e| Error::RedisParseError(format!("Failed to deserialize assignment: {}", e)))?;
+    assignments.push(assignment);
+  }
+
+  Ok(assignments)
+}
+
+/// Every indexed locker that has no assignment or reservation, ordered by number
+///
+/// Lockers are found through the `hallway:{name}` index sets; numbers indexed without
+/// a record are skipped. A locker reserved with `reserve_locker` isn't free until the
+/// hold is confirmed, cancelled or expires.
+pub async fn list_free_lockers(pool: &RedisPool) -> Result<Vec<Locker>, Error> {
+  let assigned: HashSet<String> = list_assignments(pool)
+    .await?
+    .into_iter()
+    .map(|assignment| assignment.locker.number.to_string())
+    .collect();
+
+  let mut numbers = Vec::new();
+  for key in pool.scan_collect("hallway:*").await? {
+    let members: Vec<String> = pool.smembers(&key).await?;
+    numbers.extend(members.into_iter().filter(|n| !assigned.contains(n)));
+  }
+  numbers.sort();
+  numbers.dedup();
+
+  if !numbers.is_empty() {
+    let keys: Vec<String> = numbers.iter().map(|n| reservation_key(n)).collect();
+    let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+    let holds: Vec<Option<String>> = pool.mget(&keys).await?;
+    let mut holds = holds.into_iter();
+    numbers.retain(|_| holds.next().flatten().is_none());
+  }
+
+  // Sort by `LockerNumber`, so "A-2" comes before "A-10"
+  let (mut lockers, _) = get_lockers(pool, &numbers).await?;
+  lockers.sort_by(|a, b| a.number.cmp(&b.number));
+  Ok(lockers)
+}
+
+/// Fetch many lockers in a single `MGET` round-trip.
+///
+/// Returns the lockers that were found, in request order, and the numbers that have
+/// no stored record.
+pub async fn get_lockers(
+  pool: &RedisPool,
+  numbers: &[String],
+) -> Result<(Vec<Locker>, Vec<String>), Error> {
+  if numbers.is_empty() {
+    return Ok((Vec::new(), Vec::new()));
+  }
+
+  let keys: Vec<String> = numbers.iter().map(|number| locker_key(number)).collect();
+  let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+  let values: Vec<Option<String>> = pool.mget(&keys).await?;
+
+  let mut found = Vec::new();
+  let mut missing = Vec::new();
+  for (number, value) in numbers.iter().zip(values) {
+    match value {
+      Some(locker_json) => {
+        let locker: Locker = serde_json::from_str(&locker_json)
+          .map_err(|e| Error::RedisParseError(format!("Failed to deserialize locker: {}", e)))?;
+        found.push(locker);
+      }
+      None => missing.push(number.clone()),
+    }
+  }
+
+  debug!(
+    "Fetched {} lockers ({} missing)",
+    found.len(),
+    missing.len()
+  );
+
+  Ok((found, missing))
+}
+
+// Tests
+#[cfg(all(test, feature = "redis-tests"))]
+mod tests {
+  use super::*;
+  use crate::locker::LockerSize;
+  use crate::{init_env, init_logging};
+
+  async fn setup() -> RedisPool {
+    let _ = init_logging(); // Ignore error if already initialized
+    init_env().unwrap();
+    RedisPool::init().await.unwrap()
+  }
+
+  fn locker(number: &str, hallway: &str) -> Locker {
+    Locker::new(
+      number.to_string(),
+      hallway.to_string(),
+      1,
+      LockerSize::Standard,
+      false,
+    )
+    .unwrap()
+  }
+
+  #[tokio::test]
+  async fn test_assignment_round_trip() {
+    let pool = setup().await;
+    let student_id = StudentId::new("900001".to_string()).unwrap();
+    let assignment = Assignment::new(student_id.clone(), locker("S-1", "S"), false);
+
+    save_assignment(&pool, &assignment).await.unwrap();
+    let loaded = get_assignment(&pool, &student_id).await.unwrap().unwrap();
+    assert_eq!(loaded.locker, assignment.locker);
+    assert_eq!(loaded.student_id.to_string(), "900001");
+    let holder: Option<String> = pool.get_opt(&holder_key("S-1")).await.unwrap();
+    assert_eq!(holder.as_deref(), Some("900001"));
+
+    release_assignment(&pool, &assignment).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_free_lockers_exclude_assigned_and_reserved() {
+    use crate::locker::reservation::reserve_locker;
+    use crate::redis::RedisConfig;
+    use std::time::Duration;
+
+    let _ = setup().await;
+    // A private namespace, since the listing covers every hallway
+    let pool = RedisPool::new(RedisConfig {
+      key_prefix: Some("test-free-lockers".to_string()),
+      ..RedisConfig::default()
+    })
+    .unwrap();
+    pool.delete_prefix("").await.unwrap();
+    for number in ["F-1", "F-2", "F-3"] {
+      save_locker(&pool, &locker(number, "F")).await.unwrap();
+    }
+    let student_id = StudentId::new("900020".to_string()).unwrap();
+    claim_locker(&pool, &student_id, &locker("F-1", "F"))
+      .await
+      .unwrap();
+    let other = StudentId::new("900021".to_string()).unwrap();
+    reserve_locker(&pool, &other, &locker("F-2", "F"), Duration::from_secs(60))
+      .await
+      .unwrap();
+
+    let free: Vec<String> = list_free_lockers(&pool)
+      .await
+      .unwrap()
+      .iter()
+      .map(|locker| locker.number.to_string())
+      .collect();
+    assert_eq!(free, ["F-3"]);
+
+    // Numbers sort numerically within a prefix, not as text
+    pool.del(&reservation_key("F-2")).await.unwrap();
+    for number in ["F-21", "F-10"] {
+      save_locker(&pool, &locker(number, "F")).await.unwrap();
+    }
+    let free: Vec<String> = list_free_lockers(&pool)
+      .await
+      .unwrap()
+      .iter()
+      .map(|locker| locker.number.to_string())
+      .collect();
+    assert_eq!(free, ["F-2", "F-3", "F-10", "F-21"]);
+
+    pool.delete_prefix("").await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_get_assignment_not_found() {
+    let pool = setup().await;
+    let student_id = StudentId::new("900002".to_string()).unwrap();
+
+    let loaded = get_assignment(&pool, &student_id).await.unwrap();
+    assert!(loaded.is_none());
+  }
+
+  #[tokio::test]
+  async fn test_claim_locker_race_has_one_winner() {
+    let pool = setup().await;
+    let contested = locker("R-1", "R");
+    let first = StudentId::new("900003".to_string()).unwrap();
+    let second = StudentId::new("900004".to_string()).unwrap();
+
+    let (a, b) = tokio::join!(
+      claim_locker(&pool, &first, &contested),
+      claim_locker(&pool, &second, &contested)
+    );
+
+    let results = [&a, &b];
+    assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 1);
+    assert!(results.iter().any(|r| matches!(r, Err(Error::Conflict(_)))));
+
+    let winner = a.or(b).unwrap();
+    let holder: Option<String> = pool.get(&holder_key("R-1")).await.unwrap();
+    assert_eq!(holder, Some(winner.student_id.to_string()));
+
+    release_assignment(&pool, &winner).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_list_lockers_by_hallway() {
+    let pool = setup().await;
+    save_locker(&pool, &locker("H-1", "H")).await.unwrap();
+    save_locker(&pool, &locker("H-2", "H")).await.unwrap();
+    save_locker(&pool, &locker("J-1", "J")).await.unwrap();
+
+    let mut numbers: Vec<String> = list_lockers_by_hallway(&pool, "H")
+      .await
+      .unwrap()
+      .into_iter()
+      .map(|l| l.number.to_string())
+      .collect();
+    numbers.sort();
+    assert_eq!(numbers, vec!["H-1", "H-2"]);
+
+    // Moving a locker to another hallway takes it out of the old index
+    save_locker(&pool, &locker("H-2", "J")).await.unwrap();
+    let members: Vec<String> = pool.smembers(&hallway_key("H")).await.unwrap();
+    assert_eq!(members, vec!["H-1"]);
+    let mut members: Vec<String> = pool.smembers(&hallway_key("J")).await.unwrap();
+    members.sort();
+    assert_eq!(members, vec!["H-2", "J-1"]);
+
+    for key in [
+      locker_key("H-1"),
+      locker_key("H-2"),
+      locker_key("J-1"),
+      hallway_key("H"),
+      hallway_key("J"),
+    ] {
+      pool.del(&key).await.unwrap();
+    }
+  }
+}
@@ -0,0 +1,277 @@
+//! Students waiting for a locker to free up.
+//!
+//! A match session stores the students it couldn't place in `locker:waitlist`, in the
+//! matcher's priority order (accommodation needs first, then seniors down to freshmen).
+//! The list is consumed from the right, so the highest-priority student is next.
+
+use crate::http::Error;
+use crate::locker::reservation::reservation_key;
+use crate::locker::store::{assignment_key, holder_key};
+use crate::locker::{Assignment, Locker};
+use crate::redis::{to_json, RedisOperations, RedisPool};
+use crate::student::StudentId;
+use log::{debug, info, warn};
+
+/// Redis list of waitlisted student ids, highest priority at the right
+pub const WAITLIST_KEY: &str = "locker:waitlist";
+
+/// Queue commands replacing the waitlist with `students`, highest priority first
+pub(crate) fn replace_waitlist(
+  pool: &RedisPool,
+  pipe: &mut redis::Pipeline,
+  students: &[StudentId],
+) {
+  let key = pool.prefixed(WAITLIST_KEY);
+  pipe.del(&key).ignore();
+  if !students.is_empty() {
+    // LPUSH puts each id in front of the previous one, leaving the first at the right
+    let ids: Vec<String> = students.iter().map(StudentId::to_string).collect();
+    pipe.lpush(&key, ids).ignore();
+  }
+}
+
+/// The waitlisted students, highest priority first
+pub async fn waitlist(pool: &RedisPool) -> Result<Vec<StudentId>, Error> {
+  let mut ids: Vec<String> = pool.lrange(WAITLIST_KEY, 0, -1).await?;
+  ids.reverse();
+  Ok(
+    ids
+      .into_iter()
+      .filter_map(|id| match StudentId::from_string(id.clone()) {
+        Ok(id) => Some(id),
+        Err(_) => {
+          warn!("Skipping invalid student id {} on the waitlist", id);
+          None
+        }
+      })
+      .collect(),
+  )
+}
+
+/// Assign `freed_locker` to the highest-priority waitlisted student
+///
+/// Students who got a locker some other way while waiting are dropped from the list.
+/// Zone policy isn't applied, so the assignment is never flagged `out_of_zone`. Returns
+/// `Ok(None)` if nobody is waiting.
+///
+/// The assignment is written in a transaction watching the locker's holder and
+/// reservation, and fails with `Error::Conflict` if someone else took or reserved the
+/// locker first. On any error the popped
+/// student is pushed back, keeping their place at the front of the waitlist.
+pub async fn promote_next_from_waitlist(
+  pool: &RedisPool,
+  freed_locker: &Locker,
+) -> Result<Option<Assignment>, Error> {
+  loop {
+    let Some(id) = pool.rpop::<String>(WAITLIST_KEY).await? else {
+      debug!("Waitlist empty, locker {} stays free", freed_locker.number);
+      return Ok(None);
+    };
+    let Ok(student_id) = StudentId::from_string(id.clone()) else {
+      warn!("Dropping invalid student id {} from the waitlist", id);
+      continue;
+    };
+
+    let assignment = Assignment::new(student_id, freed_locker.clone(), false);
+    match assign_if_free(pool, &assignment).await {
+      Ok(true) => {
+        info!(
+          "Promoted student {} from the waitlist to locker {}",
+          id, freed_locker.number
+        );
+        return Ok(Some(assignment));
+      }
+      Ok(false) => debug!("Waitlisted student {} already has a locker", id),
+      Err(e) => {
+        pool
+          .execute_command::<()>(
+            redis::cmd("RPUSH")
+              .arg(pool.prefixed(WAITLIST_KEY))
+              .arg(&id),
+          )
+          .await?;
+        return Err(e);
+      }
+    }
+  }
+}
+
+/// Write `assignment` if its locker is still free, returning false without writing
+/// anything if the student already has an assignment
+async fn assign_if_free(pool: &RedisPool, assignment: &Assignment) -> Result<bool, Error> {
+  let number = assignment.locker.number.as_str();
+  let holder = holder_key(number);
+  let reservation = reservation_key(number);
+  let student_assignment = assignment_key(&assignment.student_id);
+  let assignment_json = to_json(assignment)?;
+
+  let written: Vec<redis::Value> = pool
+    .transaction(&[&holder, &reservation, &student_assignment], |mut conn| {
+      let holder = pool.prefixed(&holder);
+      let reservation = pool.prefixed(&reservation);
+      let student_assignment = pool.prefixed(&student_assignment);
+      let assignment_json = assignment_json.clone();
+
+      async move {
+        let current: Option<String> = redis::cmd("GET")
+          .arg(&holder)
+          .query_async(&mut conn)
+          .await?;
+        if current.is_some() {
+          return Err(Error::Conflict(format!(
+            "locker {} is already assigned",
+            number
+          )));
+        }
+        let reserved_for: Option<String> = redis::cmd("GET")
+          .arg(&reservation)
+          .query_async(&mut conn)
+          .await?;
+        if reserved_for.is_some_and(|id| id != assignment.student_id.to_string()) {
+          return Err(Error::Conflict(format!(
+            "locker {} is reserved for another student",
+            number
+          )));
+        }
+
+        // An empty transaction leaves the student without a new locker
+        let mut pipe = redis::pipe();
+        let assigned: bool = redis::cmd("EXISTS")
+          .arg(&student_assignment)
+          .query_async(&mut conn)
+          .await?;
+        if !assigned {
+          pipe
+            .set(&student_assignment, assignment_json)
+            .set(&holder, assignment.student_id.to_string());
+        }
+        Ok(pipe)
+      }
+    })
+    .await?;
+
+  Ok(!written.is_empty())
+}
+
+// Tests
+#[cfg(all(test, feature = "redis-tests"))]
+mod tests {
+  use super::*;
+  use crate::locker::store::release_assignment;
+  use crate::locker::{run_session, LockerSize, ZonePolicy};
+  use crate::student::academic_year::DEFAULT_START_MONTH;
+  use crate::student::{AcademicYear, Student};
+  use crate::{init_env, init_logging};
+  use chrono::Utc;
+
+  async fn setup() -> RedisPool {
+    let _ = init_logging(); // Ignore error if already initialized
+    init_env().unwrap();
+    RedisPool::init().await.unwrap()
+  }
+
+  fn student(id: &str, grade: u8) -> Student {
+    Student::new(
+      id.to_string(),
+      "Waiting".to_string(),
+      "Student".to_string(),
+      format!("{}@csxlabs.edu", id),
+      grade,
+      AcademicYear::current(Utc::now(), DEFAULT_START_MONTH).graduation_year_for(grade),
+      None,
+    )
+    .unwrap()
+  }
+
+  #[tokio::test]
+  async fn test_freed_locker_goes_to_top_of_waitlist() {
+    let pool = setup().await;
+    pool.del("match_session:test-waitlist").await.unwrap();
+    let locker = Locker::new(
+      "W-1".to_string(),
+      "W".to_string(),
+      1,
+      LockerSize::Standard,
+      false,
+    )
+    .unwrap();
+    let students = [
+      student("930001", 12),
+      student("930002", 9),
+      student("930003", 11),
+    ];
+
+    let session = run_session(
+      &pool,
+      "test-waitlist",
+      &students,
+      std::slice::from_ref(&locker),
+      &ZonePolicy::new(),
+      None,
+      false,
+    )
+    .await
+    .unwrap();
+    assert_eq!(session.assignments[0].student_id.to_string(), "930001");
+    let waiting: Vec<String> = waitlist(&pool)
+      .await
+      .unwrap()
+      .iter()
+      .map(StudentId::to_string)
+      .collect();
+    assert_eq!(waiting, ["930003", "930002"]);
+
+    release_assignment(&pool, &session.assignments[0])
+      .await
+      .unwrap();
+    let promoted = promote_next_from_waitlist(&pool, &locker)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!(promoted.student_id.to_string(), "930003");
+    assert_eq!(waitlist(&pool).await.unwrap().len(), 1);
+
+    crate::locker::rollback_session(&pool, "test-waitlist")
+      .await
+      .unwrap();
+    release_assignment(&pool, &promoted).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_promotion_onto_taken_locker_keeps_student_waiting() {
+    let _ = setup().await;
+    let pool = RedisPool::new(crate::redis::RedisConfig {
+      key_prefix: Some("test-waitlist-taken".to_string()),
+      ..crate::redis::RedisConfig::default()
+    })
+    .unwrap();
+    pool.delete_prefix("").await.unwrap();
+    let locker = Locker::new(
+      "W-2".to_string(),
+      "W".to_string(),
+      1,
+      LockerSize::Standard,
+      false,
+    )
+    .unwrap();
+    pool.lpush(WAITLIST_KEY, "930011").await.unwrap();
+    pool.set(&holder_key("W-2"), "930010").await.unwrap();
+
+    let result = promote_next_from_waitlist(&pool, &locker).await;
+    assert!(matches!(result, Err(Error::Conflict(_))));
+    let waiting: Vec<String> = waitlist(&pool)
+      .await
+      .unwrap()
+      .iter()
+      .map(StudentId::to_string)
+      .collect();
+    assert_eq!(waiting, ["930011"]);
+    assert!(!pool
+      .exists(&assignment_key(
+        &StudentId::new("930011".to_string()).unwrap()
+      ))
+      .await
+      .unwrap());
+    pool.delete_prefix("").await.unwrap();
+  }
+}
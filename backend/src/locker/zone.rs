@@ -0,0 +1,58 @@
+use crate::student::Grade;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Grade-based hallway zoning used by the matcher.
+///
+/// Maps a grade to the hallways its students may be placed in. Grades without an
+/// entry are unrestricted and may be placed in any hallway.
+///
+/// # Examples
+/// ```
+/// use backend::locker::ZonePolicy;
+///
+/// let policy = ZonePolicy::new()
+///   .with_zone(12, ["A"])
+///   .with_zone(9, ["C", "D"]);
+///
+/// assert!(policy.permits(12, "A"));
+/// assert!(!policy.permits(12, "C"));
+/// assert!(policy.permits(10, "C")); // Sophomores are not zoned
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(transparent)]
+pub struct ZonePolicy {
+  zones: HashMap<Grade, Vec<String>>,
+}
+
+impl ZonePolicy {
+  /// Creates an empty policy that permits every hallway for every grade.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Restricts `grade` to the given hallways, replacing any previous zone for that grade.
+  pub fn with_zone<I, S>(mut self, grade: Grade, hallways: I) -> Self
+  where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+  {
+    self
+      .zones
+      .insert(grade, hallways.into_iter().map(Into::into).collect());
+    self
+  }
+
+  /// Returns the hallways allowed for `grade`, or `None` if the grade is unrestricted.
+  pub fn allowed_hallways(&self, grade: Grade) -> Option<&[String]> {
+    self.zones.get(&grade).map(Vec::as_slice)
+  }
+
+  /// Returns true if a student in `grade` may be placed in `hallway`.
+  pub fn permits(&self, grade: Grade, hallway: &str) -> bool {
+    match self.allowed_hallways(grade) {
+      Some(hallways) => hallways.iter().any(|h| h == hallway),
+      None => true,
+    }
+  }
+}
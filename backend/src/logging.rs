@@ -0,0 +1,76 @@
+//! JSON line encoder for `LOG_FORMAT=json`.
+//!
+//! Each record becomes one JSON object with `timestamp`, `level`, `target`, `message`
+//! and, when logged while handling a request, its `request_id`.
+
+use chrono::Utc;
+use log::Record;
+use log4rs::encode::{Encode, Write};
+use serde_json::json;
+
+/// log4rs encoder writing one JSON object per line
+#[derive(Debug, Default)]
+pub struct JsonLineEncoder;
+
+impl Encode for JsonLineEncoder {
+  fn encode(&self, w: &mut dyn Write, record: &Record) -> anyhow::Result<()> {
+    let mut line = json!({
+      "timestamp": Utc::now().to_rfc3339(),
+      "level": record.level().as_str(),
+      "target": record.target(),
+      "message": record.args().to_string(),
+    });
+    if let Some(id) = crate::http::current_request_id() {
+      line["request_id"] = json!(id);
+    }
+
+    serde_json::to_writer(&mut *w, &line)?;
+    w.write_all(b"\n")?;
+    Ok(())
+  }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use log::Level;
+  use log4rs::encode::writer::simple::SimpleWriter;
+  use serde_json::Value;
+
+  fn encode(message: &str) -> Value {
+    let mut writer = SimpleWriter(Vec::new());
+    JsonLineEncoder
+      .encode(
+        &mut writer,
+        &Record::builder()
+          .args(format_args!("{}", message))
+          .level(Level::Warn)
+          .target("backend::test")
+          .build(),
+      )
+      .unwrap();
+
+    let line = String::from_utf8(writer.0).unwrap();
+    assert!(line.ends_with('\n'));
+    serde_json::from_str(&line).unwrap()
+  }
+
+  #[test]
+  fn test_json_line_has_expected_keys() {
+    let line = encode("locker A-102 claimed");
+
+    assert_eq!(line["level"], "WARN");
+    assert_eq!(line["target"], "backend::test");
+    assert_eq!(line["message"], "locker A-102 claimed");
+    assert!(line["timestamp"].is_string());
+    assert!(line.get("request_id").is_none());
+  }
+
+  #[test]
+  fn test_json_line_includes_request_id() {
+    let line = crate::http::with_request_id("abc-123".to_string(), || encode("handled"));
+
+    assert_eq!(line["request_id"], "abc-123");
+  }
+}
@@ -1,7 +1,46 @@
 use anyhow::Context;
-use backend::{http, init_env, init_logging, redis::RedisPool};
-use log::{error, info, warn};
+use backend::{
+  http::{self, AppState},
+  init_env, init_logging,
+  locker::{expiry, ClaimPolicy},
+  preflight::{preflight, PreflightConfig},
+  redis::RedisPool,
+};
+use log::{debug, error, info, warn};
 use std::sync::Arc;
+use std::time::Duration;
+
+/// How often to retry connecting when Redis was down at startup
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Periodically release assignments that were never claimed
+fn start_sweeper(pool: Arc<RedisPool>) {
+  let policy = ClaimPolicy::default();
+  info!(
+    "Starting unclaimed assignment sweeper (window: {} hours)",
+    policy.window.num_hours()
+  );
+  expiry::spawn_sweeper(pool, policy, Duration::from_secs(300));
+}
+
+/// Keep trying to connect to Redis, handing the pool to every route once it's up
+fn spawn_reconnect(state: AppState) {
+  tokio::spawn(async move {
+    loop {
+      tokio::time::sleep(RECONNECT_INTERVAL).await;
+      match RedisPool::init().await {
+        Ok(pool) => {
+          info!("Redis connection established, enabling Redis-backed routes");
+          let pool = Arc::new(pool);
+          state.set_redis(Some(pool.clone()));
+          start_sweeper(pool);
+          return;
+        }
+        Err(e) => debug!("Redis still unavailable: {}", e),
+      }
+    }
+  });
+}
 
 #[tokio::main]
 async fn main() -> Result<(), anyhow::Error> {
@@ -13,20 +52,44 @@ async fn main() -> Result<(), anyhow::Error> {
   // Load environment variables
   init_env().context("Failed to load environment variables")?;
 
-  // Initialize Redis connection pool
-  let redis_pool = match RedisPool::init().await {
+  // Refuse to start on a configuration we'd otherwise quietly paper over
+  preflight(&PreflightConfig::default())?;
+
+  // Initialize Redis connection pool, giving a slow-starting Redis a few seconds
+  let redis_pool = match RedisPool::init_with_retry(5, Duration::from_millis(500)).await {
     Ok(pool) => {
       info!("Redis connection established");
       Some(Arc::new(pool))
     }
     Err(e) => {
       warn!("Failed to initialize Redis connection: {}", e);
-      warn!("Server will start without Redis support");
+      warn!("Server will start without Redis support and keep retrying");
       None
     }
   };
 
-  match http::serve(redis_pool).await {
+  let state = AppState::new(redis_pool.clone());
+  http::spawn_health_monitor(state.clone(), http::health_interval());
+  #[cfg(feature = "memory-store")]
+  let state = if std::env::var("STUDENT_STORE").is_ok_and(|store| store == "memory") {
+    warn!("Keeping students in memory; they will be lost on restart");
+    state.with_student_store(Arc::new(backend::student::InMemoryStore::new()))
+  } else {
+    state
+  };
+  match redis_pool {
+    Some(pool) => start_sweeper(pool),
+    None => spawn_reconnect(state.clone()),
+  }
+
+  let result = http::serve(state.clone(), http::ServerConfig::default()).await;
+
+  // In-flight requests have finished, so nothing needs Redis any more
+  if let Some(pool) = state.redis() {
+    pool.close().await;
+  }
+
+  match result {
     Ok(_) => {
       info!("Server shutdown gracefully");
       Ok(())
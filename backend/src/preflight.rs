@@ -0,0 +1,199 @@
+//! Startup checks of the server's configuration.
+//!
+//! `preflight` runs before the listener binds, so a bad deploy fails immediately with
+//! a clear message instead of limping along with defaults. Failed checks listed as
+//! fatal stop startup; the rest are logged as warnings.
+
+use anyhow::anyhow;
+use log::{debug, warn};
+use std::collections::HashSet;
+use std::env;
+use std::net::IpAddr;
+
+/// One configuration check run by `preflight`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Check {
+  /// `PORT`, if set, is a port number
+  Port,
+  /// `HOST`, if set, is an IP address
+  Host,
+  /// `REDIS_URL` and `REDIS_REPLICA_URL`, if set, are valid Redis URLs
+  RedisUrl,
+  /// `API_KEYS` has at least one key
+  ApiKeys,
+}
+
+impl Check {
+  pub const ALL: [Check; 4] = [Check::Port, Check::Host, Check::RedisUrl, Check::ApiKeys];
+
+  /// The name used for the check in `PREFLIGHT_FATAL`
+  pub fn name(self) -> &'static str {
+    match self {
+      Check::Port => "port",
+      Check::Host => "host",
+      Check::RedisUrl => "redis_url",
+      Check::ApiKeys => "api_keys",
+    }
+  }
+
+  fn from_name(name: &str) -> Option<Self> {
+    Check::ALL
+      .into_iter()
+      .find(|check| check.name().eq_ignore_ascii_case(name))
+  }
+
+  /// Run the check, reading env vars through `var`
+  fn run(self, var: &dyn Fn(&str) -> Option<String>) -> Result<(), String> {
+    match self {
+      Check::Port => match var("PORT") {
+        Some(port) if port.parse::<u16>().is_err() => {
+          Err(format!("PORT must be a port number, got {:?}", port))
+        }
+        _ => Ok(()),
+      },
+      Check::Host => match var("HOST") {
+        Some(host) if host.parse::<IpAddr>().is_err() => {
+          Err(format!("HOST must be an IP address, got {:?}", host))
+        }
+        _ => Ok(()),
+      },
+      Check::RedisUrl => {
+        for name in ["REDIS_URL", "REDIS_REPLICA_URL"] {
+          if let Some(url) = var(name) {
+            redis::Client::open(url.as_str())
+              .map_err(|e| format!("{} is not a valid Redis URL: {}", name, e))?;
+          }
+        }
+        Ok(())
+      }
+      Check::ApiKeys => match var("API_KEYS") {
+        Some(keys) if keys.split(',').any(|key| !key.trim().is_empty()) => Ok(()),
+        _ => Err("API_KEYS has no keys, so mutating requests will be rejected".to_string()),
+      },
+    }
+  }
+}
+
+/// Which failed checks stop startup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreflightConfig {
+  /// Checks that fail startup; the others only warn
+  pub fatal: HashSet<Check>,
+}
+
+impl Default for PreflightConfig {
+  fn default() -> Self {
+    // Get the fatal checks from environment, or fail on everything but API_KEYS
+    let fatal = match env::var("PREFLIGHT_FATAL") {
+      Ok(names) => names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .filter_map(|name| {
+          let check = Check::from_name(name);
+          if check.is_none() {
+            warn!("Ignoring unknown preflight check {:?}", name);
+          }
+          check
+        })
+        .collect(),
+      Err(_) => HashSet::from([Check::Port, Check::Host, Check::RedisUrl]),
+    };
+
+    Self { fatal }
+  }
+}
+
+/// Check the server's configuration from the environment
+///
+/// Every check runs, so one error lists all the fatal problems at once.
+///
+/// # Errors
+/// Returns an error describing each failed check in `config.fatal`.
+pub fn preflight(config: &PreflightConfig) -> anyhow::Result<()> {
+  run_checks(config, &|name| {
+    env::var(name).ok().filter(|s| !s.is_empty())
+  })
+}
+
+fn run_checks(
+  config: &PreflightConfig,
+  var: &dyn Fn(&str) -> Option<String>,
+) -> anyhow::Result<()> {
+  let mut failures = Vec::new();
+  for check in Check::ALL {
+    match check.run(var) {
+      Ok(()) => debug!("Preflight check {} passed", check.name()),
+      Err(problem) if config.fatal.contains(&check) => failures.push(problem),
+      Err(problem) => warn!("Preflight check {} failed: {}", check.name(), problem),
+    }
+  }
+
+  if failures.is_empty() {
+    Ok(())
+  } else {
+    Err(anyhow!("Preflight checks failed: {}", failures.join("; ")))
+  }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+  use std::collections::HashMap;
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  fn run(config: &PreflightConfig, vars: &[(&str, &str)]) -> anyhow::Result<()> {
+    let vars: HashMap<String, String> = vars
+      .iter()
+      .map(|(name, value)| (name.to_string(), value.to_string()))
+      .collect();
+    run_checks(config, &|name| vars.get(name).cloned())
+  }
+
+  #[test]
+  fn test_invalid_port_fails_preflight() {
+    setup();
+    let config = PreflightConfig {
+      fatal: HashSet::from([Check::Port, Check::Host, Check::RedisUrl]),
+    };
+
+    let error = run(&config, &[("PORT", "80a"), ("HOST", "localhost")]).unwrap_err();
+    let message = error.to_string();
+    assert!(
+      message.contains("PORT must be a port number"),
+      "{}",
+      message
+    );
+    assert!(
+      message.contains("HOST must be an IP address"),
+      "{}",
+      message
+    );
+
+    assert!(run(&config, &[("PORT", "8080"), ("HOST", "127.0.0.1")]).is_ok());
+    // Nothing set means the defaults, which are valid
+    assert!(run(&config, &[]).is_ok());
+  }
+
+  #[test]
+  fn test_only_fatal_checks_fail() {
+    setup();
+    let vars = [("PORT", "70000"), ("REDIS_URL", "not a url")];
+    let lenient = PreflightConfig {
+      fatal: HashSet::from([Check::ApiKeys]),
+    };
+    assert!(run(&lenient, &[("API_KEYS", "key"), vars[0], vars[1]]).is_ok());
+    assert!(run(&lenient, &[]).is_err());
+
+    let strict = PreflightConfig {
+      fatal: HashSet::from([Check::RedisUrl]),
+    };
+    let message = run(&strict, &vars).unwrap_err().to_string();
+    assert!(message.contains("REDIS_URL"), "{}", message);
+  }
+}
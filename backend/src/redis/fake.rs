@@ -0,0 +1,111 @@
+//! A fake Redis server for tests that need a connection without a real Redis.
+
+use crate::redis::{RedisConfig, RedisPool};
+use std::sync::Arc;
+
+/// Read one RESP command from a client, or `None` once it disconnects
+async fn read_command(reader: &mut (impl tokio::io::AsyncBufRead + Unpin)) -> Option<Vec<String>> {
+  use tokio::io::AsyncBufReadExt;
+
+  let mut line = String::new();
+  if reader.read_line(&mut line).await.ok()? == 0 {
+    return None;
+  }
+  let count: usize = line.trim_end().strip_prefix('*')?.parse().ok()?;
+  let mut args = Vec::with_capacity(count);
+  for _ in 0..count {
+    // Skip the `$len` line, then read the argument
+    line.clear();
+    reader.read_line(&mut line).await.ok()?;
+    line.clear();
+    reader.read_line(&mut line).await.ok()?;
+    args.push(line.trim_end().to_string());
+  }
+  Some(args)
+}
+
+/// A fake Redis server, recording the name of every command it receives
+pub(crate) struct FakeRedis {
+  pub(crate) url: String,
+  received: Arc<std::sync::Mutex<Vec<String>>>,
+}
+
+impl FakeRedis {
+  /// Start a server whose first connection drops as soon as it receives `drop_on`
+  ///
+  /// `GET` answers `"ok"`, `EXISTS` 1, `SCAN` an empty final batch and everything
+  /// else `OK`. Commands between `MULTI` and `EXEC` are queued, and `EXEC` answers with
+  /// their replies.
+  pub(crate) async fn start(drop_on: Option<&'static str>) -> Self {
+    use tokio::io::{AsyncWriteExt, BufReader};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("redis://{}", listener.local_addr().unwrap());
+    let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let log = received.clone();
+    tokio::spawn(async move {
+      let mut first = true;
+      while let Ok((socket, _)) = listener.accept().await {
+        let drop_connection = std::mem::take(&mut first);
+        let log = log.clone();
+        tokio::spawn(async move {
+          let (reader, mut writer) = socket.into_split();
+          let mut reader = BufReader::new(reader);
+          let mut queued: Option<Vec<&[u8]>> = None;
+          while let Some(args) = read_command(&mut reader).await {
+            let name = args[0].to_uppercase();
+            log.lock().unwrap().push(name.clone());
+            if drop_connection && Some(name.as_str()) == drop_on {
+              return;
+            }
+            let reply: &[u8] = match name.as_str() {
+              "GET" => b"$2\r\nok\r\n",
+              "EXISTS" => b":1\r\n",
+              "SCAN" => b"*2\r\n$1\r\n0\r\n*0\r\n",
+              _ => b"+OK\r\n",
+            };
+            let reply = match (name.as_str(), queued.as_mut()) {
+              ("MULTI", _) => {
+                queued = Some(Vec::new());
+                reply.to_vec()
+              }
+              ("EXEC", Some(_)) => {
+                let replies = queued.take().unwrap_or_default();
+                let mut reply = format!("*{}\r\n", replies.len()).into_bytes();
+                replies.iter().for_each(|r| reply.extend_from_slice(r));
+                reply
+              }
+              (_, Some(replies)) => {
+                replies.push(reply);
+                b"+QUEUED\r\n".to_vec()
+              }
+              _ => reply.to_vec(),
+            };
+            if writer.write_all(&reply).await.is_err() {
+              return;
+            }
+          }
+        });
+      }
+    });
+    FakeRedis { url, received }
+  }
+
+  /// How many `command`s the server has received
+  pub(crate) fn count(&self, command: &str) -> usize {
+    let received = self.received.lock().unwrap();
+    received.iter().filter(|name| *name == command).count()
+  }
+}
+
+pub(crate) fn fake_pool(url: &str, replica_url: Option<&str>) -> RedisPool {
+  RedisPool::new(RedisConfig {
+    url: url.to_string(),
+    username: None,
+    password: None,
+    key_prefix: None,
+    replica_url: replica_url.map(str::to_string),
+    ..RedisConfig::default()
+  })
+  .unwrap()
+}
@@ -0,0 +1,197 @@
+//! Distributed locks shared by every backend instance.
+//!
+//! A lock is a `lock:{name}` key holding a random token, set with `SET NX PX` so it
+//! expires on its own if the holder dies. Releasing compares the token before deleting,
+//! so an instance never deletes a lock that expired and was taken by someone else.
+
+use log::{debug, warn};
+use rand::Rng;
+use std::time::Duration;
+
+use super::RedisPool;
+use crate::http::Error;
+
+/// Delete the lock only if it still holds our token
+const RELEASE_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+  return redis.call("DEL", KEYS[1])
+else
+  return 0
+end
+"#;
+
+/// Redis key for the lock called `name`
+fn lock_key(name: &str) -> String {
+  format!("lock:{}", name)
+}
+
+/// A held distributed lock, released when dropped
+///
+/// Dropping the guard releases the lock on a background task. Call `release` instead to
+/// wait for the release to finish.
+#[derive(Debug)]
+pub struct LockGuard {
+  pool: RedisPool,
+  key: String,
+  token: String,
+  released: bool,
+}
+
+impl LockGuard {
+  /// Name of the Redis key backing this lock, including any key prefix
+  pub fn key(&self) -> &str {
+    &self.key
+  }
+
+  /// Release the lock now, waiting for Redis to confirm
+  pub async fn release(mut self) -> Result<(), Error> {
+    self.released = true;
+    release(&self.pool, &self.key, &self.token).await
+  }
+}
+
+impl Drop for LockGuard {
+  fn drop(&mut self) {
+    if self.released {
+      return;
+    }
+
+    let pool = self.pool.clone();
+    let key = std::mem::take(&mut self.key);
+    let token = std::mem::take(&mut self.token);
+    match tokio::runtime::Handle::try_current() {
+      Ok(handle) => {
+        handle.spawn(async move {
+          if let Err(e) = release(&pool, &key, &token).await {
+            warn!("Failed to release lock {}: {}", key, e);
+          }
+        });
+      }
+      Err(_) => warn!(
+        "No runtime to release lock {}; it will expire on its own",
+        key
+      ),
+    }
+  }
+}
+
+/// Compare-and-delete the lock at `key` if it holds `token`
+async fn release(pool: &RedisPool, key: &str, token: &str) -> Result<(), Error> {
+  let mut conn = pool.get_connection().await?;
+  let deleted: i64 = redis::Script::new(RELEASE_SCRIPT)
+    .key(key)
+    .arg(token)
+    .invoke_async(&mut conn)
+    .await?;
+
+  if deleted == 0 {
+    debug!("Lock {} had already expired or changed hands", key);
+  } else {
+    debug!("Released lock {}", key);
+  }
+  Ok(())
+}
+
+impl RedisPool {
+  /// Try to take the distributed lock called `name` for at most `ttl`
+  ///
+  /// Returns `None` without waiting if another holder has the lock. The lock expires
+  /// after `ttl` even if the guard is never dropped, so pick a TTL longer than the work
+  /// it protects.
+  pub async fn acquire_lock(&self, name: &str, ttl: Duration) -> Result<Option<LockGuard>, Error> {
+    let key = self.prefixed(&lock_key(name));
+    let token = format!("{:032x}", rand::rng().random::<u128>());
+
+    let acquired: Option<String> = self
+      .execute_command(
+        redis::cmd("SET")
+          .arg(&key)
+          .arg(&token)
+          .arg("NX")
+          .arg("PX")
+          .arg(ttl.as_millis() as u64),
+      )
+      .await?;
+
+    if acquired.is_none() {
+      debug!("Lock {} is held elsewhere", key);
+      return Ok(None);
+    }
+
+    debug!("Acquired lock {} for {:?}", key, ttl);
+    Ok(Some(LockGuard {
+      pool: self.clone(),
+      key,
+      token,
+      released: false,
+    }))
+  }
+}
+
+// Tests
+#[cfg(all(test, feature = "redis-tests"))]
+mod tests {
+  use super::*;
+  use crate::{init_env, init_logging};
+
+  async fn setup() -> RedisPool {
+    let _ = init_logging(); // Ignore error if already initialized
+    init_env().unwrap();
+    RedisPool::init().await.unwrap()
+  }
+
+  #[tokio::test]
+  async fn test_lock_is_exclusive_until_dropped() {
+    let pool = setup().await;
+    let ttl = Duration::from_secs(10);
+
+    let guard = pool.acquire_lock("test-exclusive", ttl).await.unwrap();
+    assert!(guard.is_some());
+    assert!(pool
+      .acquire_lock("test-exclusive", ttl)
+      .await
+      .unwrap()
+      .is_none());
+
+    drop(guard);
+
+    // Dropping releases on a background task, so give it a moment
+    let mut reacquired = None;
+    for _ in 0..20 {
+      reacquired = pool.acquire_lock("test-exclusive", ttl).await.unwrap();
+      if reacquired.is_some() {
+        break;
+      }
+      tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    reacquired.unwrap().release().await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_release_leaves_other_holders_alone() {
+    let pool = setup().await;
+
+    let expired = pool
+      .acquire_lock("test-expired", Duration::from_millis(20))
+      .await
+      .unwrap()
+      .unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let current = pool
+      .acquire_lock("test-expired", Duration::from_secs(10))
+      .await
+      .unwrap()
+      .unwrap();
+
+    // Releasing the stale guard must not delete the new holder's lock
+    expired.release().await.unwrap();
+    assert!(pool
+      .acquire_lock("test-expired", Duration::from_secs(10))
+      .await
+      .unwrap()
+      .is_none());
+
+    current.release().await.unwrap();
+  }
+}
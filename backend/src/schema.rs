@@ -0,0 +1,39 @@
+//! JSON Schema for the types the frontend consumes, available with the `schema` feature.
+
+use crate::http::ErrorBody;
+use crate::student::Student;
+use schemars::{schema_for, Schema};
+
+/// JSON Schema of a `Student` as serialized by the API
+pub fn student_schema() -> Schema {
+  schema_for!(Student)
+}
+
+/// JSON Schema of the body of every error response
+pub fn error_schema() -> Schema {
+  schema_for!(ErrorBody)
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_student_schema_has_grade() {
+    let schema = student_schema().to_value();
+
+    assert!(schema["properties"]["grade"].is_object());
+    assert_eq!(
+      schema["$defs"]["StudentId"]["pattern"],
+      serde_json::json!("^[0-9]{6}$")
+    );
+  }
+
+  #[test]
+  fn test_error_schema_has_code() {
+    let schema = error_schema().to_value();
+
+    assert!(schema["$defs"]["ErrorDetail"]["properties"]["code"].is_object());
+  }
+}
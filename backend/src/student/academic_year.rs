@@ -0,0 +1,130 @@
+//! The school's academic year, which runs across two calendar years.
+//!
+//! Graduation-year validation and the rollover both need to know which academic year
+//! it is. Near the start of the school year the calendar year is the wrong answer: on
+//! August 1st the class that graduated in June is no longer current, even though the
+//! calendar year hasn't changed.
+
+use crate::student::{Grade, ValidationConfig};
+use chrono::{DateTime, Datelike, Utc};
+
+/// Month the academic year starts in when not configured otherwise (August)
+pub const DEFAULT_START_MONTH: u32 = 8;
+
+/// An academic year, named by the calendar year it starts in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AcademicYear {
+  start_year: u16,
+}
+
+impl AcademicYear {
+  /// The academic year starting in `start_year`
+  pub fn new(start_year: u16) -> Self {
+    AcademicYear { start_year }
+  }
+
+  /// The academic year `now` falls in, for a year starting on the first of `start_month`
+  /// (1 to 12; out of range values are clamped)
+  pub fn current(now: DateTime<Utc>, start_month: u32) -> Self {
+    let year = now.year() as u16;
+    if now.month() >= start_month.clamp(1, 12) {
+      AcademicYear::new(year)
+    } else {
+      AcademicYear::new(year - 1)
+    }
+  }
+
+  /// Calendar year the academic year starts in
+  pub fn start_year(self) -> u16 {
+    self.start_year
+  }
+
+  /// Year a student in `grade` this academic year is expected to graduate, at a school
+  /// whose top grade is `top_grade`
+  pub fn graduation_year_in(self, grade: Grade, top_grade: Grade) -> u16 {
+    self.start_year + 1 + (top_grade as u16).saturating_sub(grade as u16)
+  }
+
+  /// `graduation_year_in` for the default `ValidationConfig` grades, 9 through 12
+  pub fn graduation_year_for(self, grade: Grade) -> u16 {
+    self.graduation_year_in(grade, *ValidationConfig::default().grade_range.end())
+  }
+
+  /// True if `graduation_year` is no earlier than this year's top grade's and at most
+  /// `config.grad_year_window` years after it
+  pub fn accepts_graduation_year(self, graduation_year: u16, config: &ValidationConfig) -> bool {
+    let top_grade = *config.grade_range.end();
+    let seniors = self.graduation_year_in(top_grade, top_grade);
+    (seniors..=seniors + config.grad_year_window).contains(&graduation_year)
+  }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+  use chrono::TimeZone;
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  fn date(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(year, month, day, 12, 0, 0).unwrap()
+  }
+
+  #[test]
+  fn test_year_turns_over_on_august_first() {
+    setup();
+    let july = AcademicYear::current(date(2026, 7, 31), DEFAULT_START_MONTH);
+    let august = AcademicYear::current(date(2026, 8, 1), DEFAULT_START_MONTH);
+
+    assert_eq!(july.start_year(), 2025);
+    assert_eq!(august.start_year(), 2026);
+    assert_eq!(july.graduation_year_for(12), 2026);
+    assert_eq!(august.graduation_year_for(12), 2027);
+    assert_eq!(august.graduation_year_for(9), 2030);
+
+    // The class of 2026 can still be entered on July 31st, but not once it's August
+    let config = ValidationConfig::default();
+    assert!(july.accepts_graduation_year(2026, &config));
+    assert!(!august.accepts_graduation_year(2026, &config));
+    assert!(august.accepts_graduation_year(2037, &config));
+    assert!(!august.accepts_graduation_year(2038, &config));
+  }
+
+  #[test]
+  fn test_top_grade_comes_from_the_grade_range() {
+    setup();
+    let august = AcademicYear::current(date(2026, 8, 1), DEFAULT_START_MONTH);
+    let middle_school = ValidationConfig {
+      grade_range: 6..=8,
+      ..ValidationConfig::default()
+    };
+
+    assert_eq!(august.graduation_year_in(8, 8), 2027);
+    assert_eq!(august.graduation_year_in(6, 8), 2029);
+    assert!(august.accepts_graduation_year(2027, &middle_school));
+    assert!(!august.accepts_graduation_year(2026, &middle_school));
+    assert!(august.accepts_graduation_year(2037, &middle_school));
+    assert!(!august.accepts_graduation_year(2038, &middle_school));
+  }
+
+  #[test]
+  fn test_start_month_is_configurable() {
+    setup();
+    assert_eq!(
+      AcademicYear::current(date(2026, 7, 31), 7).start_year(),
+      2026
+    );
+    assert_eq!(
+      AcademicYear::current(date(2026, 1, 1), 1).start_year(),
+      2026
+    );
+    assert_eq!(
+      AcademicYear::current(date(2026, 12, 31), 13).start_year(),
+      2026
+    );
+  }
+}
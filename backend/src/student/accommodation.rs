@@ -0,0 +1,311 @@
+use crate::student::Student;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use utoipa::ToSchema;
+
+/// Words in freeform accommodation text implying an accessible, bottom-tier locker
+pub const ACCESSIBLE_LOCKER_KEYWORDS: &[&str] = &[
+  "wheelchair",
+  "mobility",
+  "lower",
+  "bottom",
+  "crutches",
+  "walker",
+  "accessible",
+  "accessibility",
+];
+
+/// A single locker accommodation a student is entitled to
+///
+/// Students carry a list of these in `accommodations`, which the matcher reads before
+/// the older `accommodation` and `special_accommodations` fields. `NearExit` and `Other`
+/// are recorded for staff but don't constrain placement, since lockers don't record
+/// their distance to an exit.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Accommodation {
+  BottomTier,
+  AdaAccessible,
+  NearExit,
+  LowerHeight,
+  Other(String),
+}
+
+impl Accommodation {
+  /// Interpret legacy freeform accommodation text, for migrating students to the
+  /// structured list.
+  ///
+  /// Recognized phrases become their variants, in declaration order. Text that mentions
+  /// none of them, like "Peanut allergy", is kept as a single `Other`. Blank text yields
+  /// an empty list.
+  ///
+  /// # Examples
+  /// ```
+  /// use backend::student::Accommodation;
+  ///
+  /// assert_eq!(
+  ///   Accommodation::parse_legacy("Bottom row locker for wheelchair access"),
+  ///   [Accommodation::BottomTier, Accommodation::AdaAccessible]
+  /// );
+  /// ```
+  pub fn parse_legacy(text: &str) -> Vec<Accommodation> {
+    let text = text.trim();
+    if text.is_empty() {
+      return Vec::new();
+    }
+
+    let accommodations: Vec<Accommodation> = [
+      (Self::BottomTier, &["bottom"][..]),
+      (
+        Self::AdaAccessible,
+        &["wheelchair", "accessible", "accessibility"],
+      ),
+      (Self::NearExit, &["exit", "entrance"]),
+      (Self::LowerHeight, &["lower", "reach"]),
+    ]
+    .into_iter()
+    .filter_map(|(accommodation, words)| mentions_any(text, words).then_some(accommodation))
+    .collect();
+
+    if accommodations.is_empty() {
+      vec![Self::Other(text.to_string())]
+    } else {
+      accommodations
+    }
+  }
+}
+
+impl fmt::Display for Accommodation {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::BottomTier => f.write_str("Bottom tier locker"),
+      Self::AdaAccessible => f.write_str("ADA accessible locker"),
+      Self::NearExit => f.write_str("Locker near an exit"),
+      Self::LowerHeight => f.write_str("Lower height locker"),
+      Self::Other(text) => f.write_str(text),
+    }
+  }
+}
+
+/// Structured locker accommodation needs for a student.
+///
+/// This is the typed counterpart of the freeform `special_accommodations` text and is
+/// preferred by the matcher when present.
+///
+/// # Examples
+/// ```
+/// use backend::student::AccommodationNeeds;
+///
+/// let needs = AccommodationNeeds {
+///   needs_lower_row: true,
+///   ..Default::default()
+/// };
+/// assert!(needs.has_locker_needs());
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, ToSchema)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AccommodationNeeds {
+  #[serde(default)]
+  pub needs_accessible: bool,
+  #[serde(default)]
+  pub needs_lower_row: bool,
+  #[serde(default)]
+  pub needs_wide: bool,
+  #[serde(default)]
+  pub notes: Option<String>,
+}
+
+impl AccommodationNeeds {
+  /// Best-effort interpretation of legacy freeform accommodation text.
+  ///
+  /// Only used when a student has no structured `accommodation` set.
+  pub fn from_text(text: &str) -> Self {
+    let mentions = |words: &[&str]| mentions_any(text, words);
+
+    AccommodationNeeds {
+      needs_accessible: mentions(&["wheelchair", "accessible", "accessibility"]),
+      needs_lower_row: mentions(&["lower", "bottom"]),
+      needs_wide: mentions(&["wide", "large"]),
+      notes: Some(text.to_string()),
+    }
+  }
+
+  /// The needs implied by a structured accommodations list.
+  ///
+  /// `Other` entries are carried over as `notes`.
+  pub fn from_accommodations(accommodations: &[Accommodation]) -> Self {
+    let has = |wanted: &[Accommodation]| accommodations.iter().any(|a| wanted.contains(a));
+    let notes: Vec<&str> = accommodations
+      .iter()
+      .filter_map(|a| match a {
+        Accommodation::Other(text) => Some(text.as_str()),
+        _ => None,
+      })
+      .collect();
+
+    AccommodationNeeds {
+      needs_accessible: has(&[Accommodation::AdaAccessible]),
+      needs_lower_row: has(&[Accommodation::BottomTier, Accommodation::LowerHeight]),
+      needs_wide: false,
+      notes: (!notes.is_empty()).then(|| notes.join("; ")),
+    }
+  }
+
+  /// Returns true if any of the needs constrain which locker can be assigned.
+  pub fn has_locker_needs(&self) -> bool {
+    self.needs_accessible || self.needs_lower_row || self.needs_wide
+  }
+}
+
+/// Returns true if `text` mentions any of `keywords` as whole words, ignoring case
+///
+/// A keyword of several words must appear as that run of words, so "walking frame"
+/// matches "Uses a walking frame" while "accessible" doesn't match "inaccessible".
+fn mentions_any(text: &str, keywords: &[&str]) -> bool {
+  fn words(text: &str) -> Vec<String> {
+    text
+      .split(|c: char| !c.is_alphanumeric())
+      .filter(|word| !word.is_empty())
+      .map(str::to_lowercase)
+      .collect()
+  }
+
+  let text = words(text);
+  keywords.iter().any(|keyword| {
+    let keyword = words(keyword);
+    !keyword.is_empty() && text.windows(keyword.len()).any(|run| run == keyword)
+  })
+}
+
+impl Student {
+  /// Returns true if the student's `special_accommodations` text implies they need an
+  /// accessible, bottom-tier locker, using `ACCESSIBLE_LOCKER_KEYWORDS`.
+  pub fn needs_accessible_locker(&self) -> bool {
+    self.needs_accessible_locker_with(ACCESSIBLE_LOCKER_KEYWORDS)
+  }
+
+  /// Like `needs_accessible_locker`, matching the text against `keywords` instead.
+  pub fn needs_accessible_locker_with(&self, keywords: &[&str]) -> bool {
+    self
+      .special_accommodations
+      .as_deref()
+      .is_some_and(|text| mentions_any(text, keywords))
+  }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+  use crate::student::academic_year::DEFAULT_START_MONTH;
+  use crate::student::AcademicYear;
+  use chrono::Utc;
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  fn student(accommodations: Option<&str>) -> Student {
+    Student::new(
+      "123456".to_string(),
+      "Jane".to_string(),
+      "Smith".to_string(),
+      "jane.smith@csxlabs.edu".to_string(),
+      12,
+      AcademicYear::current(Utc::now(), DEFAULT_START_MONTH).graduation_year_for(12),
+      accommodations.map(str::to_string),
+    )
+    .unwrap()
+  }
+
+  #[test]
+  fn test_parse_legacy_text_into_variants() {
+    setup();
+    for (text, expected) in [
+      ("Needs a bottom locker", vec![Accommodation::BottomTier]),
+      ("Uses a WHEELCHAIR", vec![Accommodation::AdaAccessible]),
+      ("Close to the exit please", vec![Accommodation::NearExit]),
+      (
+        "Lower locker, limited reach",
+        vec![Accommodation::LowerHeight],
+      ),
+      (
+        "Accessible locker by the main entrance",
+        vec![Accommodation::AdaAccessible, Accommodation::NearExit],
+      ),
+      (
+        "  Peanut allergy ",
+        vec![Accommodation::Other("Peanut allergy".to_string())],
+      ),
+      ("   ", vec![]),
+    ] {
+      assert_eq!(Accommodation::parse_legacy(text), expected, "{}", text);
+    }
+  }
+
+  #[test]
+  fn test_needs_from_accommodations() {
+    setup();
+    let needs = AccommodationNeeds::from_accommodations(&[
+      Accommodation::LowerHeight,
+      Accommodation::NearExit,
+      Accommodation::Other("Peanut allergy".to_string()),
+    ]);
+    assert!(needs.needs_lower_row);
+    assert!(!needs.needs_accessible);
+    assert_eq!(needs.notes.as_deref(), Some("Peanut allergy"));
+    assert!(
+      !AccommodationNeeds::from_accommodations(&[Accommodation::NearExit]).has_locker_needs()
+    );
+  }
+
+  #[test]
+  fn test_accessible_locker_phrasings() {
+    setup();
+    for text in [
+      "Uses a wheelchair",
+      "Limited MOBILITY after surgery",
+      "Needs a bottom locker",
+      "On crutches until March",
+      "Lower row please",
+    ] {
+      assert!(student(Some(text)).needs_accessible_locker(), "{}", text);
+    }
+  }
+
+  #[test]
+  fn test_unrelated_accommodations_do_not_match() {
+    setup();
+    assert!(!student(Some("Peanut allergy")).needs_accessible_locker());
+    assert!(!student(None).needs_accessible_locker());
+  }
+
+  #[test]
+  fn test_keywords_match_whole_words() {
+    setup();
+    let needs = AccommodationNeeds::from_text("Top shelf is inaccessible; label in lowercase");
+    assert!(!needs.needs_accessible);
+    assert!(!needs.needs_lower_row);
+    assert!(!needs.has_locker_needs());
+
+    let needs = AccommodationNeeds::from_text("Wheelchair-accessible, LOWER row");
+    assert!(needs.needs_accessible);
+    assert!(needs.needs_lower_row);
+    assert_eq!(
+      Accommodation::parse_legacy("Sits below the exit sign"),
+      [Accommodation::NearExit]
+    );
+    assert!(!student(Some("Bottomless backpack")).needs_accessible_locker());
+  }
+
+  #[test]
+  fn test_keyword_list_can_be_overridden() {
+    setup();
+    let student = student(Some("Uses a Walking Frame"));
+    assert!(!student.needs_accessible_locker());
+    assert!(student.needs_accessible_locker_with(&["walking frame"]));
+    assert!(!student.needs_accessible_locker_with(&[]));
+  }
+}
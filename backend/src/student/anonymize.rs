@@ -0,0 +1,170 @@
+//! Scrubbing personal data from old student records.
+//!
+//! Once a record is past the retention window its names, email and accommodations are
+//! replaced, but the id, grade and timestamps stay so locker history and per-grade
+//! statistics still add up. The email becomes a hash of the original at
+//! `redacted.invalid`, which keeps it unique for the email index.
+
+use crate::http::Error;
+use crate::redis::RedisPool;
+use crate::student::{store, Student};
+use chrono::{DateTime, Utc};
+use log::info;
+use sha2::{Digest, Sha256};
+
+/// Replacement for a scrubbed first or last name
+pub const REDACTED: &str = "Redacted";
+
+/// Domain of the placeholder emails given to anonymized students
+const REDACTED_EMAIL_DOMAIN: &str = "redacted.invalid";
+
+impl Student {
+  /// Scrub the student's personal data in place
+  ///
+  /// Names become "Redacted", the email becomes a SHA-256 hash of the original, and all
+  /// accommodations and the change log (which holds old values) are cleared. The id,
+  /// grade, graduation year, active flag and timestamps are kept. Anonymizing twice
+  /// changes nothing further.
+  pub fn anonymize(&mut self) {
+    if self.is_anonymized() {
+      return;
+    }
+
+    let digest = Sha256::digest(self.email.as_bytes());
+    let hash: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+    self.first_name = REDACTED.to_string();
+    self.last_name = REDACTED.to_string();
+    self.email = format!("{}@{}", hash, REDACTED_EMAIL_DOMAIN);
+    self.special_accommodations = None;
+    self.accommodation = None;
+    self.accommodations.clear();
+    self.change_log.clear();
+    // `updated_at` is left alone so the record still ages out by its last real change
+    self.version += 1;
+  }
+
+  /// Returns true if `anonymize` has already scrubbed this student
+  pub fn is_anonymized(&self) -> bool {
+    self
+      .email
+      .strip_suffix(REDACTED_EMAIL_DOMAIN)
+      .is_some_and(|rest| rest.ends_with('@'))
+  }
+}
+
+/// Anonymize every stored student last updated before `cutoff`
+///
+/// Each scrubbed student is saved through `store::save`, which releases their old email.
+/// Students already anonymized are skipped, so this is safe to run on a schedule.
+/// Returns how many students were anonymized.
+pub async fn anonymize_older_than(pool: &RedisPool, cutoff: DateTime<Utc>) -> Result<usize, Error> {
+  let mut anonymized = 0;
+  for mut student in store::load_all(pool).await? {
+    if student.updated_at >= cutoff || student.is_anonymized() {
+      continue;
+    }
+    student.anonymize();
+    store::save(pool, &student).await?;
+    anonymized += 1;
+  }
+
+  info!(
+    "Anonymized {} students last updated before {}",
+    anonymized, cutoff
+  );
+  Ok(anonymized)
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+  use crate::student::Accommodation;
+  use chrono::Datelike;
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  fn student(id: &str) -> Student {
+    let mut student = Student::new(
+      id.to_string(),
+      "Jane".to_string(),
+      "Smith".to_string(),
+      format!("{}@csxlabs.edu", id),
+      11,
+      Utc::now().year() as u16 + 1,
+      Some("Bottom row please".to_string()),
+    )
+    .unwrap();
+    student
+      .update_accommodations(vec![Accommodation::BottomTier])
+      .unwrap();
+    student
+  }
+
+  #[test]
+  fn test_anonymize_scrubs_pii_and_keeps_id_and_grade() {
+    setup();
+    let mut student = student("150001");
+    let original = student.clone();
+
+    student.anonymize();
+
+    assert_eq!(student.first_name, REDACTED);
+    assert_eq!(student.last_name, REDACTED);
+    assert!(student.email.ends_with("@redacted.invalid"));
+    assert!(!student.email.contains("csxlabs"));
+    assert_eq!(student.special_accommodations, None);
+    assert_eq!(student.accommodation, None);
+    assert!(student.accommodations.is_empty());
+    assert!(student.change_log().is_empty());
+
+    assert_eq!(student.id.to_string(), "150001");
+    assert_eq!(student.grade, original.grade);
+    assert_eq!(student.created_at, original.created_at);
+    assert_eq!(student.updated_at, original.updated_at);
+    assert!(student.is_anonymized());
+
+    // Same email, same hash; a second pass is a no-op
+    let mut twin = original.clone();
+    twin.anonymize();
+    assert_eq!(twin.email, student.email);
+    let version = student.version;
+    student.anonymize();
+    assert_eq!(student.version, version);
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_anonymize_older_than_uses_updated_at() {
+    use crate::redis::RedisConfig;
+
+    setup();
+    crate::init_env().unwrap();
+    // A private namespace, since anonymizing touches every student
+    let pool = RedisPool::new(RedisConfig {
+      key_prefix: Some("test-anonymize".to_string()),
+      ..RedisConfig::default()
+    })
+    .unwrap();
+    pool.delete_prefix("").await.unwrap();
+    let mut stale = student("150002");
+    stale.updated_at = Utc::now() - chrono::Duration::days(400);
+    let fresh = student("150003");
+    store::save(&pool, &stale).await.unwrap();
+    store::save(&pool, &fresh).await.unwrap();
+
+    let cutoff = Utc::now() - chrono::Duration::days(365);
+    assert_eq!(anonymize_older_than(&pool, cutoff).await.unwrap(), 1);
+    assert_eq!(anonymize_older_than(&pool, cutoff).await.unwrap(), 0);
+
+    let stale = store::load(&pool, &stale.id).await.unwrap().unwrap();
+    assert!(stale.is_anonymized());
+    assert_eq!(stale.grade, 11);
+    let fresh = store::load(&pool, &fresh.id).await.unwrap().unwrap();
+    assert_eq!(fresh.first_name, "Jane");
+    pool.delete_prefix("").await.unwrap();
+  }
+}
@@ -0,0 +1,104 @@
+use crate::student::Student;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Most recent changes kept on a student; older entries are dropped
+pub const MAX_CHANGE_LOG_ENTRIES: usize = 50;
+
+/// One change to a student field, recorded by the `Student::update_*` methods.
+///
+/// Values are stored as text, with `None` for a field that was or became empty.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ChangeLogEntry {
+  pub field: String,
+  pub old: Option<String>,
+  pub new: Option<String>,
+  pub at: DateTime<Utc>,
+}
+
+impl Student {
+  /// Changes made to this student, oldest first
+  pub fn change_log(&self) -> &[ChangeLogEntry] {
+    &self.change_log
+  }
+
+  /// Append a change to the log if the value actually changed
+  pub(crate) fn record_change(&mut self, field: &str, old: Option<String>, new: Option<String>) {
+    if old == new {
+      return;
+    }
+
+    self.change_log.push(ChangeLogEntry {
+      field: field.to_string(),
+      old,
+      new,
+      at: Utc::now(),
+    });
+    if self.change_log.len() > MAX_CHANGE_LOG_ENTRIES {
+      let excess = self.change_log.len() - MAX_CHANGE_LOG_ENTRIES;
+      self.change_log.drain(..excess);
+    }
+  }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+  use chrono::Datelike;
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  fn student() -> Student {
+    Student::new(
+      "123456".to_string(),
+      "John".to_string(),
+      "Doe".to_string(),
+      "john.doe@csxlabs.edu".to_string(),
+      10,
+      Utc::now().year() as u16 + 2,
+      None,
+    )
+    .unwrap()
+  }
+
+  #[test]
+  fn test_updates_are_logged_in_order() {
+    setup();
+    let mut student = student();
+    student.update_grade(11).unwrap();
+    student
+      .update_email("JOHN@csxlabs.edu".to_string())
+      .unwrap();
+    // Failed and no-op updates leave no entry
+    assert!(student.update_grade(13).is_err());
+    student.update_grade(11).unwrap();
+
+    let log = student.change_log();
+    assert_eq!(log.len(), 2);
+    assert_eq!(log[0].field, "grade");
+    assert_eq!(log[0].old.as_deref(), Some("10"));
+    assert_eq!(log[0].new.as_deref(), Some("11"));
+    assert_eq!(log[1].field, "email");
+    assert_eq!(log[1].new.as_deref(), Some("john@csxlabs.edu"));
+    assert!(log[0].at <= log[1].at);
+  }
+
+  #[test]
+  fn test_change_log_is_capped() {
+    setup();
+    let mut student = student();
+    for i in 0..MAX_CHANGE_LOG_ENTRIES + 5 {
+      student.update_grade(9 + (i % 4) as u8).unwrap();
+    }
+
+    let log = student.change_log();
+    assert_eq!(log.len(), MAX_CHANGE_LOG_ENTRIES);
+    assert_eq!(log.last().unwrap().new, Some(student.grade.to_string()));
+  }
+}
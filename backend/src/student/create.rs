@@ -1,7 +1,12 @@
 use crate::http::Error;
-use chrono::{DateTime, Datelike, Utc};
+use crate::student::{
+  AcademicYear, Accommodation, AccommodationNeeds, ChangeLogEntry, ValidationConfig,
+};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use utoipa::openapi::{schema::SchemaType, ObjectBuilder, RefOr, Schema, Type};
+use utoipa::{PartialSchema, ToSchema};
 
 /// A validated student identifier for California High School.
 ///
@@ -62,6 +67,35 @@ impl StudentId {
   }
 }
 
+impl PartialSchema for StudentId {
+  fn schema() -> RefOr<Schema> {
+    ObjectBuilder::new()
+      .schema_type(SchemaType::new(Type::String))
+      .pattern(Some("^[0-9]{6}$"))
+      .examples(["123456"])
+      .into()
+  }
+}
+
+impl ToSchema for StudentId {}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for StudentId {
+  fn schema_name() -> std::borrow::Cow<'static, str> {
+    "StudentId".into()
+  }
+
+  fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+    schemars::json_schema!({
+      "type": "string",
+      "pattern": "^[0-9]{6}$"
+    })
+  }
+}
+
+/// A high school grade level (9=Freshman through 12=Senior).
+pub type Grade = u8;
+
 /// Represents a high school student for locker assignment purposes.
 ///
 /// This struct contains all the information needed to assign lockers to students
@@ -69,53 +103,87 @@ impl StudentId {
 ///
 /// # Field Specifications
 ///
+/// Lengths and ranges below are the `ValidationConfig` defaults.
+///
 /// ## Required Fields
 /// - `id`: 6-digit student identifier (validated)
 /// - `first_name`: Student's first name (1-50 characters, trimmed)
 /// - `last_name`: Student's last name (1-50 characters, trimmed)
 /// - `email`: Valid email address (5-254 characters, normalized to lowercase)
 /// - `grade`: High school grade level (9=Freshman, 10=Sophomore, 11=Junior, 12=Senior)
-/// - `graduation_year`: Expected graduation year (current year to current year + 10)
+/// - `graduation_year`: Expected graduation year (this academic year's seniors' year to
+///   10 years after it)
 ///
 /// ## Optional Fields
 /// - `special_accommodations`: Accessibility needs for locker assignment (max 500 characters)
+/// - `accommodation`: Structured accommodation needs, preferred by the matcher over the text
+/// - `accommodations`: List of `Accommodation`s, preferred by the matcher over both; setting
+///   it rewrites `special_accommodations`, which is kept only for compatibility
+///
+/// ## Status
+/// - `active`: False once the student has left (e.g. transferred); inactive students are
+///   kept for audit but skipped by the matcher. Records without the field load as active.
+///
+/// ## History
+/// - `change_log`: The most recent field changes, see `change_log()`
+/// - `version`: Bumped by every `update_*` call; clients send it back in `If-Match` so a
+///   stale edit is rejected instead of overwriting a newer one
 ///
 /// ## Timestamps
 /// - `created_at`: UTC timestamp when the student record was created
 /// - `updated_at`: UTC timestamp when the student record was last modified
 ///
+/// # Logging
+/// `{:?}` prints the email, accommodations and change log as `<redacted>`; use
+/// `debug_full()` where the full record may be shown.
+///
 /// # Examples
 /// ```
-/// use backend::student::Student;
+/// use backend::student::{academic_year::DEFAULT_START_MONTH, AcademicYear, Student};
+/// use chrono::Utc;
 ///
+/// let year = AcademicYear::current(Utc::now(), DEFAULT_START_MONTH);
 /// let student = Student::new(
 ///   "123456".to_string(),
 ///   "John".to_string(),
 ///   "Doe".to_string(),
 ///   "john.doe@csxlabs.edu".to_string(),
 ///   11, // Junior
-///   2026,
+///   year.graduation_year_for(11),
 ///   Some("Lower locker for wheelchair access".to_string()),
 /// ).unwrap();
 ///
 /// assert_eq!(student.grade_level(), "Junior");
 /// assert_eq!(student.full_name(), "John Doe");
 /// ```
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Student {
   pub id: StudentId,
   pub first_name: String,
   pub last_name: String,
   pub email: String,
-  pub grade: u8,                              // 9-12 for high school grades
-  pub graduation_year: u16,                   // e.g., 2025, 2026, etc.
-  pub special_accommodations: Option<String>, // Any special needs for locker assignment
+  pub grade: Grade,         // 9-12 for high school grades
+  pub graduation_year: u16, // e.g., 2025, 2026, etc.
+  /// Deprecated: kept in sync with `accommodations` by `update_accommodations`, for
+  /// clients that still read the freeform text
+  pub special_accommodations: Option<String>,
+  #[serde(default)]
+  pub accommodation: Option<AccommodationNeeds>, // Structured needs, preferred over the text
+  #[serde(default)]
+  pub accommodations: Vec<Accommodation>, // Preferred over both fields above when non-empty
+  #[serde(default = "active_by_default")]
+  pub active: bool,
+  #[serde(default)]
+  pub(super) change_log: Vec<ChangeLogEntry>,
+  #[serde(default)]
+  pub version: u64,
   pub created_at: DateTime<Utc>,
   pub updated_at: DateTime<Utc>,
 }
 
 impl Student {
-  /// Creates a new Student with validation.
+  /// Creates a new Student with validation, using the default `ValidationConfig`.
   ///
   /// All fields are validated according to the rules specified in the struct documentation.
   /// Names are automatically trimmed and emails are normalized to lowercase.
@@ -126,7 +194,8 @@ impl Student {
   /// * `last_name` - Student's last name (1-50 characters after trimming)
   /// * `email` - Valid email address (5-254 characters)
   /// * `grade` - Grade level (9-12 for Freshman through Senior)
-  /// * `graduation_year` - Expected graduation year (current year to current year + 10)
+  /// * `graduation_year` - Expected graduation year (this academic year's seniors' year to
+  ///   10 years after it)
   /// * `special_accommodations` - Optional accessibility requirements (max 500 characters)
   ///
   /// # Returns
@@ -135,7 +204,10 @@ impl Student {
   ///
   /// # Examples
   /// ```
-  /// use backend::student::Student;
+  /// use backend::student::{academic_year::DEFAULT_START_MONTH, AcademicYear, Student};
+  /// use chrono::Utc;
+  ///
+  /// let year = AcademicYear::current(Utc::now(), DEFAULT_START_MONTH);
   ///
   /// // Valid student
   /// let student = Student::new(
@@ -144,7 +216,7 @@ impl Student {
   ///   "Smith".to_string(),
   ///   "jane.smith@csxlabs.edu".to_string(),
   ///   9, // Freshman
-  ///   2028,
+  ///   year.graduation_year_for(9),
   ///   None,
   /// ).unwrap();
   ///
@@ -155,7 +227,7 @@ impl Student {
   ///   "Johnson".to_string(),
   ///   "alex.johnson@csxlabs.edu".to_string(),
   ///   12, // Senior
-  ///   2025,
+  ///   year.graduation_year_for(12),
   ///   Some("Bottom row locker for mobility aid access".to_string()),
   /// ).unwrap();
   ///
@@ -170,6 +242,54 @@ impl Student {
     grade: u8,
     graduation_year: u16,
     special_accommodations: Option<String>,
+  ) -> Result<Self, Error> {
+    Self::new_with_config(
+      id,
+      first_name,
+      last_name,
+      email,
+      grade,
+      graduation_year,
+      special_accommodations,
+      &ValidationConfig::default(),
+    )
+  }
+
+  /// Creates a new Student, validated against the limits in `config`.
+  ///
+  /// # Examples
+  /// ```
+  /// use backend::student::{Student, ValidationConfig};
+  ///
+  /// let config = ValidationConfig {
+  ///   name_max: 10,
+  ///   ..ValidationConfig::default()
+  /// };
+  /// let student = Student::new_with_config(
+  ///   "123456".to_string(),
+  ///   "Maximiliana".to_string(),
+  ///   "Smith".to_string(),
+  ///   "max.smith@csxlabs.edu".to_string(),
+  ///   9,
+  ///   2030,
+  ///   None,
+  ///   &config,
+  /// );
+  /// assert!(student.is_err());
+  /// ```
+  ///
+  /// # Errors
+  /// Returns `Error::UnprocessableEntity` listing every field that is out of bounds.
+  #[allow(clippy::too_many_arguments)]
+  pub fn new_with_config(
+    id: String,
+    first_name: String,
+    last_name: String,
+    email: String,
+    grade: u8,
+    graduation_year: u16,
+    special_accommodations: Option<String>,
+    config: &ValidationConfig,
   ) -> Result<Self, Error> {
     let mut errors = HashMap::new();
 
@@ -189,11 +309,11 @@ impl Student {
         .entry("first_name".into())
         .or_insert_with(Vec::new)
         .push("cannot be empty".into());
-    } else if first_name.len() > 50 {
+    } else if first_name.len() > config.name_max {
       errors
         .entry("first_name".into())
         .or_insert_with(Vec::new)
-        .push("cannot be longer than 50 characters".into());
+        .push(format!("cannot be longer than {} characters", config.name_max).into());
     }
 
     // Validate last name
@@ -202,11 +322,11 @@ impl Student {
         .entry("last_name".into())
         .or_insert_with(Vec::new)
         .push("cannot be empty".into());
-    } else if last_name.len() > 50 {
+    } else if last_name.len() > config.name_max {
       errors
         .entry("last_name".into())
         .or_insert_with(Vec::new)
-        .push("cannot be longer than 50 characters".into());
+        .push(format!("cannot be longer than {} characters", config.name_max).into());
     }
 
     // Validate email
@@ -218,16 +338,20 @@ impl Student {
     }
 
     // Validate grade
-    if !(9..=12).contains(&grade) {
-      errors
-        .entry("grade".into())
-        .or_insert_with(Vec::new)
-        .push("must be between 9 and 12".into());
+    if !config.grade_range.contains(&grade) {
+      errors.entry("grade".into()).or_insert_with(Vec::new).push(
+        format!(
+          "must be between {} and {}",
+          config.grade_range.start(),
+          config.grade_range.end()
+        )
+        .into(),
+      );
     }
 
     // Validate graduation year (reasonable range)
-    let current_year = chrono::Utc::now().year() as u16;
-    if graduation_year < current_year || graduation_year > current_year + 10 {
+    let academic_year = AcademicYear::current(Utc::now(), config.academic_year_start_month);
+    if !academic_year.accepts_graduation_year(graduation_year, config) {
       errors
         .entry("graduation_year".into())
         .or_insert_with(Vec::new)
@@ -236,11 +360,17 @@ impl Student {
 
     // Validate special accommodations if provided
     if let Some(ref accommodations_str) = special_accommodations {
-      if accommodations_str.len() > 500 {
+      if accommodations_str.len() > config.accommodation_max {
         errors
           .entry("special_accommodations".into())
           .or_insert_with(Vec::new)
-          .push("cannot be longer than 500 characters".into());
+          .push(
+            format!(
+              "cannot be longer than {} characters",
+              config.accommodation_max
+            )
+            .into(),
+          );
       }
     }
 
@@ -258,6 +388,11 @@ impl Student {
       grade,
       graduation_year,
       special_accommodations,
+      accommodation: None,
+      accommodations: Vec::new(),
+      active: true,
+      change_log: Vec::new(),
+      version: 0,
       created_at: now,
       updated_at: now,
     })
@@ -284,64 +419,264 @@ impl Student {
         "must be a valid email address",
       )]));
     }
-    self.email = new_email.trim().to_lowercase();
-    self.updated_at = Utc::now();
+    let new_email = new_email.trim().to_lowercase();
+    self.record_change("email", Some(self.email.clone()), Some(new_email.clone()));
+    self.email = new_email;
+    self.touch();
     Ok(())
   }
 
+  /// Move the student to `new_grade`, validated against the default `ValidationConfig`
   pub fn update_grade(&mut self, new_grade: u8) -> Result<(), Error> {
-    if !(9..=12).contains(&new_grade) {
+    self.update_grade_with_config(new_grade, &ValidationConfig::default())
+  }
+
+  /// Move the student to `new_grade`, which must be in `config.grade_range`
+  pub fn update_grade_with_config(
+    &mut self,
+    new_grade: u8,
+    config: &ValidationConfig,
+  ) -> Result<(), Error> {
+    if !config.grade_range.contains(&new_grade) {
       return Err(Error::unprocessable_entity([(
         "grade",
-        "must be between 9 and 12",
+        format!(
+          "must be between {} and {}",
+          config.grade_range.start(),
+          config.grade_range.end()
+        ),
       )]));
     }
+    self.record_change(
+      "grade",
+      Some(self.grade.to_string()),
+      Some(new_grade.to_string()),
+    );
     self.grade = new_grade;
-    self.updated_at = Utc::now();
+    self.touch();
     Ok(())
   }
 
+  /// Change the graduation year, validated against the default `ValidationConfig`
   pub fn update_graduation_year(&mut self, new_graduation_year: u16) -> Result<(), Error> {
-    let current_year = chrono::Utc::now().year() as u16;
-    if new_graduation_year < current_year || new_graduation_year > current_year + 10 {
+    self.update_graduation_year_with_config(new_graduation_year, &ValidationConfig::default())
+  }
+
+  /// Change the graduation year, which must fall within `config.grad_year_window`
+  pub fn update_graduation_year_with_config(
+    &mut self,
+    new_graduation_year: u16,
+    config: &ValidationConfig,
+  ) -> Result<(), Error> {
+    let academic_year = AcademicYear::current(Utc::now(), config.academic_year_start_month);
+    if !academic_year.accepts_graduation_year(new_graduation_year, config) {
       return Err(Error::unprocessable_entity([(
         "graduation_year",
         "must be within a reasonable range",
       )]));
     }
+    self.record_change(
+      "graduation_year",
+      Some(self.graduation_year.to_string()),
+      Some(new_graduation_year.to_string()),
+    );
     self.graduation_year = new_graduation_year;
-    self.updated_at = Utc::now();
+    self.touch();
     Ok(())
   }
 
+  /// Replace the free-text accommodations, validated against the default
+  /// `ValidationConfig`
   pub fn update_special_accommodations(
     &mut self,
     new_accommodations: Option<String>,
+  ) -> Result<(), Error> {
+    self.update_special_accommodations_with_config(new_accommodations, &ValidationConfig::default())
+  }
+
+  /// Replace the free-text accommodations, at most `config.accommodation_max` bytes
+  pub fn update_special_accommodations_with_config(
+    &mut self,
+    new_accommodations: Option<String>,
+    config: &ValidationConfig,
   ) -> Result<(), Error> {
     if let Some(ref accommodations_str) = new_accommodations {
-      if accommodations_str.len() > 500 {
+      if accommodations_str.len() > config.accommodation_max {
         return Err(Error::unprocessable_entity([(
           "special_accommodations",
-          "cannot be longer than 500 characters",
+          format!(
+            "cannot be longer than {} characters",
+            config.accommodation_max
+          ),
         )]));
       }
     }
+    self.record_change(
+      "special_accommodations",
+      self.special_accommodations.clone(),
+      new_accommodations.clone(),
+    );
     self.special_accommodations = new_accommodations;
-    self.updated_at = Utc::now();
+    self.touch();
     Ok(())
   }
 
+  /// Replace the structured accommodation needs, validated against the default
+  /// `ValidationConfig`
+  pub fn update_accommodation(
+    &mut self,
+    new_accommodation: Option<AccommodationNeeds>,
+  ) -> Result<(), Error> {
+    self.update_accommodation_with_config(new_accommodation, &ValidationConfig::default())
+  }
+
+  /// Replace the structured accommodation needs, whose notes may be at most
+  /// `config.accommodation_max` bytes
+  pub fn update_accommodation_with_config(
+    &mut self,
+    new_accommodation: Option<AccommodationNeeds>,
+    config: &ValidationConfig,
+  ) -> Result<(), Error> {
+    if let Some(notes) = new_accommodation.as_ref().and_then(|a| a.notes.as_ref()) {
+      if notes.len() > config.accommodation_max {
+        return Err(Error::unprocessable_entity([(
+          "accommodation.notes",
+          format!(
+            "cannot be longer than {} characters",
+            config.accommodation_max
+          ),
+        )]));
+      }
+    }
+    let as_json = |needs: &Option<AccommodationNeeds>| {
+      needs
+        .as_ref()
+        .and_then(|needs| serde_json::to_string(needs).ok())
+    };
+    self.record_change(
+      "accommodation",
+      as_json(&self.accommodation),
+      as_json(&new_accommodation),
+    );
+    self.accommodation = new_accommodation;
+    self.touch();
+    Ok(())
+  }
+
+  /// Replace the structured accommodations list, rewriting `special_accommodations` to
+  /// describe it (or clearing it when the list is empty).
+  pub fn update_accommodations(
+    &mut self,
+    new_accommodations: Vec<Accommodation>,
+  ) -> Result<(), Error> {
+    self.update_accommodations_with_config(new_accommodations, &ValidationConfig::default())
+  }
+
+  /// Like `update_accommodations`, limiting the text to `config.accommodation_max` bytes
+  pub fn update_accommodations_with_config(
+    &mut self,
+    new_accommodations: Vec<Accommodation>,
+    config: &ValidationConfig,
+  ) -> Result<(), Error> {
+    let text = (!new_accommodations.is_empty()).then(|| {
+      new_accommodations
+        .iter()
+        .map(Accommodation::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+    });
+    if text
+      .as_ref()
+      .is_some_and(|text| text.len() > config.accommodation_max)
+    {
+      return Err(Error::unprocessable_entity([(
+        "accommodations",
+        format!(
+          "cannot be longer than {} characters in total",
+          config.accommodation_max
+        ),
+      )]));
+    }
+    let as_json = |accommodations: &[Accommodation]| serde_json::to_string(accommodations).ok();
+    self.record_change(
+      "accommodations",
+      as_json(&self.accommodations),
+      as_json(&new_accommodations),
+    );
+    self.accommodations = new_accommodations;
+    if text != self.special_accommodations {
+      self.record_change(
+        "special_accommodations",
+        self.special_accommodations.clone(),
+        text.clone(),
+      );
+      self.special_accommodations = text;
+    }
+    self.touch();
+    Ok(())
+  }
+
+  /// Mark the student as no longer enrolled, keeping the record
+  pub fn deactivate(&mut self) {
+    self.record_change(
+      "active",
+      Some(self.active.to_string()),
+      Some("false".to_string()),
+    );
+    self.active = false;
+    self.touch();
+  }
+
+  /// Mark a deactivated student as enrolled again
+  pub fn reactivate(&mut self) {
+    self.record_change(
+      "active",
+      Some(self.active.to_string()),
+      Some("true".to_string()),
+    );
+    self.active = true;
+    self.touch();
+  }
+
+  /// Returns the student's accommodation needs for matching.
+  ///
+  /// Uses the `accommodations` list when it isn't empty, then the structured
+  /// `accommodation`, and otherwise falls back to interpreting the legacy
+  /// `special_accommodations` text.
+  pub fn accommodation_needs(&self) -> AccommodationNeeds {
+    if !self.accommodations.is_empty() {
+      return AccommodationNeeds::from_accommodations(&self.accommodations);
+    }
+    match (&self.accommodation, &self.special_accommodations) {
+      (Some(needs), _) => needs.clone(),
+      (None, Some(text)) => AccommodationNeeds::from_text(text),
+      (None, None) => AccommodationNeeds::default(),
+    }
+  }
+
+  /// Record a modification: bump `version` and refresh `updated_at`
+  pub(super) fn touch(&mut self) {
+    self.version += 1;
+    self.updated_at = Utc::now();
+  }
+
   // Simple email validation - you might want to use a proper email validation crate
   fn is_valid_email(email: &str) -> bool {
     email.contains('@') && email.len() >= 5 && email.len() <= 254
   }
 }
 
+fn active_by_default() -> bool {
+  true
+}
+
 // Tests
 #[cfg(test)]
 mod tests {
   use super::*;
   use crate::init_logging;
+  use crate::student::academic_year::DEFAULT_START_MONTH;
+  use chrono::Datelike;
   use log::debug;
 
   fn setup() {
@@ -367,13 +702,15 @@ mod tests {
   #[test]
   fn test_student_new_valid() {
     setup();
+    let graduation_year =
+      AcademicYear::current(Utc::now(), DEFAULT_START_MONTH).graduation_year_for(11);
     let student = Student::new(
       "123456".to_string(),
       "John".to_string(),
       "Doe".to_string(),
       "john.doe@csxlabs.edu".to_string(),
       11, // Junior
-      2026,
+      graduation_year,
       None,
     );
 
@@ -384,7 +721,7 @@ mod tests {
     assert_eq!(student.last_name, "Doe");
     assert_eq!(student.email, "john.doe@csxlabs.edu");
     assert_eq!(student.grade, 11);
-    assert_eq!(student.graduation_year, 2026);
+    assert_eq!(student.graduation_year, graduation_year);
     assert_eq!(student.special_accommodations, None);
     assert_eq!(student.full_name(), "John Doe");
     assert_eq!(student.grade_level(), "Junior");
@@ -436,6 +773,36 @@ mod tests {
     }
   }
 
+  #[test]
+  fn test_new_with_config_applies_name_max() {
+    setup();
+    let config = ValidationConfig {
+      name_max: 10,
+      ..ValidationConfig::default()
+    };
+    let build = |first_name: &str| {
+      Student::new_with_config(
+        "123456".to_string(),
+        first_name.to_string(),
+        "Doe".to_string(),
+        "john.doe@csxlabs.edu".to_string(),
+        10,
+        Utc::now().year() as u16 + 2,
+        None,
+        &config,
+      )
+    };
+
+    assert!(build("Alexandria").is_ok());
+    let Err(Error::UnprocessableEntity { errors }) = build("Maximiliana") else {
+      panic!("expected an 11-character name to be rejected");
+    };
+    assert_eq!(
+      errors["first_name"],
+      vec!["cannot be longer than 10 characters"]
+    );
+  }
+
   #[test]
   fn test_student_update_grade() {
     setup();
@@ -456,6 +823,42 @@ mod tests {
     assert_eq!(student.grade_level(), "Junior");
   }
 
+  #[test]
+  fn test_updates_apply_validation_config() {
+    setup();
+    let year = AcademicYear::current(Utc::now(), DEFAULT_START_MONTH);
+    let mut student = Student::new(
+      "123456".to_string(),
+      "John".to_string(),
+      "Doe".to_string(),
+      "john.doe@csxlabs.edu".to_string(),
+      10,
+      year.graduation_year_for(10),
+      None,
+    )
+    .unwrap();
+    let config = ValidationConfig {
+      grade_range: 6..=8,
+      accommodation_max: 10,
+      ..ValidationConfig::default()
+    };
+
+    student.update_grade_with_config(7, &config).unwrap();
+    assert_eq!(student.grade, 7);
+    assert!(student.update_grade_with_config(9, &config).is_err());
+
+    let long = Some("Bottom row locker".to_string());
+    assert!(student
+      .update_special_accommodations_with_config(long.clone(), &config)
+      .is_err());
+    student
+      .update_special_accommodations_with_config(long, &ValidationConfig::default())
+      .unwrap();
+    assert!(student
+      .update_accommodations_with_config(vec![Accommodation::BottomTier], &config)
+      .is_err());
+  }
+
   #[test]
   fn test_student_update_special_accommodations() {
     setup();
@@ -483,4 +886,83 @@ mod tests {
     assert!(result.is_ok());
     assert_eq!(student.special_accommodations, None);
   }
+
+  #[test]
+  fn test_update_accommodations_syncs_text() {
+    setup();
+    let mut student = Student::new(
+      "123456".to_string(),
+      "John".to_string(),
+      "Doe".to_string(),
+      "john.doe@csxlabs.edu".to_string(),
+      10,
+      AcademicYear::current(Utc::now(), DEFAULT_START_MONTH).graduation_year_for(10),
+      Some("Bottom row please".to_string()),
+    )
+    .unwrap();
+
+    student
+      .update_accommodations(vec![
+        Accommodation::AdaAccessible,
+        Accommodation::Other("Peanut allergy".to_string()),
+      ])
+      .unwrap();
+    assert_eq!(
+      student.special_accommodations.as_deref(),
+      Some("ADA accessible locker; Peanut allergy")
+    );
+    // The list wins over the text it replaced
+    let needs = student.accommodation_needs();
+    assert!(needs.needs_accessible);
+    assert!(!needs.needs_lower_row);
+
+    student.update_accommodations(Vec::new()).unwrap();
+    assert_eq!(student.special_accommodations, None);
+  }
+
+  #[test]
+  fn test_student_deactivate_and_reactivate() {
+    setup();
+    let mut student = Student::new(
+      "123456".to_string(),
+      "John".to_string(),
+      "Doe".to_string(),
+      "john.doe@csxlabs.edu".to_string(),
+      10,
+      AcademicYear::current(Utc::now(), DEFAULT_START_MONTH).graduation_year_for(10),
+      None,
+    )
+    .unwrap();
+    assert!(student.active);
+
+    let before = student.updated_at;
+    student.deactivate();
+    assert!(!student.active);
+    assert!(student.updated_at >= before);
+
+    student.reactivate();
+    assert!(student.active);
+  }
+
+  #[test]
+  fn test_student_without_active_field_loads_as_active() {
+    setup();
+    let mut json = serde_json::to_value(
+      Student::new(
+        "123456".to_string(),
+        "John".to_string(),
+        "Doe".to_string(),
+        "john.doe@csxlabs.edu".to_string(),
+        10,
+        AcademicYear::current(Utc::now(), DEFAULT_START_MONTH).graduation_year_for(10),
+        None,
+      )
+      .unwrap(),
+    )
+    .unwrap();
+    json.as_object_mut().unwrap().remove("active");
+
+    let student: Student = serde_json::from_value(json).unwrap();
+    assert!(student.active);
+  }
 }
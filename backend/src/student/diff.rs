@@ -0,0 +1,95 @@
+//! Field-level comparison of student records.
+//!
+//! Two students are equal when every field a client can set matches; `updated_at`,
+//! `version` and the change log are bookkeeping and are ignored.
+
+use crate::student::Student;
+
+impl Student {
+  /// Returns the names of the fields that differ between `self` and `other`
+  ///
+  /// `updated_at`, `version` and the change log are not compared, so an update that sets every
+  /// field to its current value produces an empty diff.
+  pub fn diff(&self, other: &Student) -> Vec<&'static str> {
+    [
+      ("id", self.id.to_string() != other.id.to_string()),
+      ("first_name", self.first_name != other.first_name),
+      ("last_name", self.last_name != other.last_name),
+      ("email", self.email != other.email),
+      ("grade", self.grade != other.grade),
+      (
+        "graduation_year",
+        self.graduation_year != other.graduation_year,
+      ),
+      (
+        "special_accommodations",
+        self.special_accommodations != other.special_accommodations,
+      ),
+      ("accommodation", self.accommodation != other.accommodation),
+      (
+        "accommodations",
+        self.accommodations != other.accommodations,
+      ),
+      ("active", self.active != other.active),
+      ("created_at", self.created_at != other.created_at),
+    ]
+    .into_iter()
+    .filter_map(|(field, differs)| differs.then_some(field))
+    .collect()
+  }
+}
+
+impl PartialEq for Student {
+  fn eq(&self, other: &Self) -> bool {
+    self.diff(other).is_empty()
+  }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+  use chrono::{Datelike, Utc};
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  fn student() -> Student {
+    Student::new(
+      "123456".to_string(),
+      "John".to_string(),
+      "Doe".to_string(),
+      "john.doe@csxlabs.edu".to_string(),
+      10,
+      Utc::now().year() as u16 + 2,
+      None,
+    )
+    .unwrap()
+  }
+
+  #[test]
+  fn test_identical_students_have_no_diff() {
+    setup();
+    let original = student();
+    let mut updated = original.clone();
+    updated.update_grade(original.grade).unwrap();
+
+    assert!(updated.diff(&original).is_empty());
+    assert_eq!(updated, original);
+  }
+
+  #[test]
+  fn test_one_changed_field_is_reported() {
+    setup();
+    let original = student();
+    let mut updated = original.clone();
+    updated
+      .update_email("jdoe@csxlabs.edu".to_string())
+      .unwrap();
+
+    assert_eq!(updated.diff(&original), ["email"]);
+    assert_ne!(updated, original);
+  }
+}
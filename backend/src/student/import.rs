@@ -0,0 +1,158 @@
+use crate::http::Error;
+use crate::student::Student;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+/// One row of a roster CSV, before validation.
+///
+/// Columns: `id,first_name,last_name,email,grade,graduation_year,accommodations`
+#[derive(Debug, Deserialize)]
+struct CsvRow {
+  id: String,
+  first_name: String,
+  last_name: String,
+  email: String,
+  grade: String,
+  graduation_year: String,
+  #[serde(default)]
+  accommodations: Option<String>,
+}
+
+/// The outcome of importing one CSV row
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ImportRow {
+  /// The row produced a valid student
+  Created { row: usize, id: String },
+  /// The row failed validation; `errors` is keyed by field like `UnprocessableEntity`
+  Invalid {
+    row: usize,
+    #[schema(value_type = HashMap<String, Vec<String>>)]
+    errors: HashMap<Cow<'static, str>, Vec<Cow<'static, str>>>,
+  },
+}
+
+/// Parse and validate a roster CSV with a header row.
+///
+/// Each data row (numbered from 1) is validated independently through `Student::new`,
+/// so one bad row never prevents the others from importing. Returns the students to
+/// store alongside a per-row result in input order.
+pub fn parse_csv(csv: &str) -> (Vec<Student>, Vec<ImportRow>) {
+  let mut reader = csv::ReaderBuilder::new()
+    .trim(csv::Trim::All)
+    .from_reader(csv.as_bytes());
+
+  let mut students = Vec::new();
+  let mut rows = Vec::new();
+
+  for (index, record) in reader.deserialize::<CsvRow>().enumerate() {
+    let row = index + 1;
+    let result = record
+      .map_err(|e| Error::unprocessable_entity([("row", e.to_string())]))
+      .and_then(validate_row);
+
+    match result {
+      Ok(student) => {
+        rows.push(ImportRow::Created {
+          row,
+          id: student.id.to_string(),
+        });
+        students.push(student);
+      }
+      Err(Error::UnprocessableEntity { errors }) => rows.push(ImportRow::Invalid { row, errors }),
+      Err(e) => rows.push(ImportRow::Invalid {
+        row,
+        errors: HashMap::from([("row".into(), vec![e.to_string().into()])]),
+      }),
+    }
+  }
+
+  (students, rows)
+}
+
+/// Parse the numeric columns and build a `Student`, collecting every field error
+fn validate_row(row: CsvRow) -> Result<Student, Error> {
+  let grade = row.grade.parse::<u8>();
+  let graduation_year = row.graduation_year.parse::<u16>();
+
+  let mut errors = Vec::new();
+  if grade.is_err() {
+    errors.push(("grade", "must be a number"));
+  }
+  if graduation_year.is_err() {
+    errors.push(("graduation_year", "must be a number"));
+  }
+  if !errors.is_empty() {
+    return Err(Error::unprocessable_entity(errors));
+  }
+
+  Student::new(
+    row.id,
+    row.first_name,
+    row.last_name,
+    row.email,
+    grade.unwrap(),
+    graduation_year.unwrap(),
+    row.accommodations.filter(|a| !a.is_empty()),
+  )
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+  use chrono::{Datelike, Utc};
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  #[test]
+  fn test_parse_csv_keeps_valid_rows_around_invalid_one() {
+    setup();
+    let year = Utc::now().year() + 2;
+    let csv = format!(
+      "id,first_name,last_name,email,grade,graduation_year,accommodations\n\
+       100001,Ada,Lovelace,ada@csxlabs.edu,10,{year},\n\
+       100002,Alan,Turing,alan@csxlabs.edu,14,{year},\n\
+       100003,Grace,Hopper,grace@csxlabs.edu,10,{year},Bottom row locker\n"
+    );
+
+    let (students, rows) = parse_csv(&csv);
+
+    assert_eq!(students.len(), 2);
+    assert_eq!(
+      students[1].special_accommodations.as_deref(),
+      Some("Bottom row locker")
+    );
+    assert!(students[0].special_accommodations.is_none());
+
+    assert_eq!(rows.len(), 3);
+    assert!(matches!(&rows[0], ImportRow::Created { row: 1, id } if id == "100001"));
+    match &rows[1] {
+      ImportRow::Invalid { row, errors } => {
+        assert_eq!(*row, 2);
+        assert!(errors.contains_key("grade"));
+      }
+      other => panic!("expected row 2 to be invalid, got {:?}", other),
+    }
+    assert!(matches!(&rows[2], ImportRow::Created { row: 3, .. }));
+  }
+
+  #[test]
+  fn test_parse_csv_reports_malformed_rows() {
+    setup();
+    let csv = "id,first_name,last_name,email,grade,graduation_year,accommodations\n\
+               100004,Only,Three\n\
+               100005,Linus,Torvalds,linus@csxlabs.edu,ten,2030,\n";
+
+    let (students, rows) = parse_csv(csv);
+
+    assert!(students.is_empty());
+    assert!(matches!(&rows[0], ImportRow::Invalid { errors, .. } if errors.contains_key("row")));
+    assert!(matches!(&rows[1], ImportRow::Invalid { errors, .. } if errors.contains_key("grade")));
+  }
+}
@@ -0,0 +1,130 @@
+use crate::http::Error;
+use crate::student::{Accommodation, AccommodationNeeds, Grade, Student};
+use serde::Deserialize;
+use std::borrow::Cow;
+use utoipa::ToSchema;
+
+/// Body of a create request: a student's fields, before validation.
+///
+/// Every field is optional so that a missing field is reported alongside the other
+/// validation errors by `Student::try_from`, rather than failing deserialization.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct CreateStudentRequest {
+  pub id: Option<String>,
+  pub first_name: Option<String>,
+  pub last_name: Option<String>,
+  pub email: Option<String>,
+  pub grade: Option<Grade>,
+  pub graduation_year: Option<u16>,
+  #[serde(default)]
+  pub special_accommodations: Option<String>,
+  #[serde(default)]
+  pub accommodation: Option<AccommodationNeeds>,
+  #[serde(default)]
+  pub accommodations: Option<Vec<Accommodation>>,
+}
+
+impl TryFrom<CreateStudentRequest> for Student {
+  type Error = Error;
+
+  /// Validate the request into a `Student`.
+  ///
+  /// # Errors
+  /// Returns `Error::UnprocessableEntity` with every invalid field, where missing
+  /// required fields are reported as "is required".
+  fn try_from(request: CreateStudentRequest) -> Result<Self, Error> {
+    let missing: Vec<&'static str> = [
+      ("id", request.id.is_none()),
+      ("first_name", request.first_name.is_none()),
+      ("last_name", request.last_name.is_none()),
+      ("email", request.email.is_none()),
+      ("grade", request.grade.is_none()),
+      ("graduation_year", request.graduation_year.is_none()),
+    ]
+    .into_iter()
+    .filter_map(|(field, is_missing)| is_missing.then_some(field))
+    .collect();
+
+    // Missing fields get placeholders that always fail validation, and their errors
+    // are replaced below so the present fields are still checked
+    let result = Student::new(
+      request.id.unwrap_or_default(),
+      request.first_name.unwrap_or_default(),
+      request.last_name.unwrap_or_default(),
+      request.email.unwrap_or_default(),
+      request.grade.unwrap_or_default(),
+      request.graduation_year.unwrap_or_default(),
+      request.special_accommodations,
+    )
+    .and_then(|mut student| {
+      student.update_accommodation(request.accommodation)?;
+      if let Some(accommodations) = request.accommodations {
+        student.update_accommodations(accommodations)?;
+      }
+      Ok(student)
+    });
+
+    match result {
+      Err(Error::UnprocessableEntity { mut errors }) => {
+        for field in missing {
+          errors.insert(Cow::Borrowed(field), vec!["is required".into()]);
+        }
+        Err(Error::UnprocessableEntity { errors })
+      }
+      result => result,
+    }
+  }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+  use chrono::{Datelike, Utc};
+  use serde_json::json;
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  #[test]
+  fn test_invalid_request_reports_every_field() {
+    setup();
+    let request: CreateStudentRequest = serde_json::from_value(json!({
+      "id": "123456",
+      "first_name": "Casey",
+      "email": "not-an-email",
+      "grade": 10,
+    }))
+    .unwrap();
+
+    let Err(Error::UnprocessableEntity { errors }) = Student::try_from(request) else {
+      panic!("expected validation errors");
+    };
+    assert_eq!(errors["last_name"], vec!["is required"]);
+    assert_eq!(errors["graduation_year"], vec!["is required"]);
+    assert_eq!(errors["email"], vec!["must be a valid email address"]);
+    assert!(!errors.contains_key("id"));
+    assert!(!errors.contains_key("grade"));
+  }
+
+  #[test]
+  fn test_valid_request_builds_student() {
+    setup();
+    let request: CreateStudentRequest = serde_json::from_value(json!({
+      "id": "123456",
+      "first_name": "Casey",
+      "last_name": "Jones",
+      "email": "casey@csxlabs.edu",
+      "grade": 10,
+      "graduation_year": Utc::now().year() + 2,
+      "accommodation": { "needs_wide": true },
+    }))
+    .unwrap();
+
+    let student = Student::try_from(request).unwrap();
+    assert_eq!(student.full_name(), "Casey Jones");
+    assert!(student.accommodation_needs().needs_wide);
+  }
+}
@@ -0,0 +1,282 @@
+//! A `StudentStore` kept in process memory.
+//!
+//! Used by unit tests and, with the `memory-store` feature, local development without
+//! Redis. It keeps the same email ownership rule as the Redis store but none of its
+//! change or tombstone indexes, and everything is lost on restart.
+
+use crate::http::Error;
+use crate::student::search::rank_matches;
+use crate::student::store::{watermark_at, ChangedSince, StudentPage, StudentStore};
+use crate::student::{Grade, PublicStudent, Student, StudentId};
+use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Students keyed by id, behind a mutex
+#[derive(Debug, Default)]
+pub struct InMemoryStore {
+  students: Mutex<HashMap<String, Student>>,
+}
+
+impl InMemoryStore {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  fn students(&self) -> std::sync::MutexGuard<'_, HashMap<String, Student>> {
+    // A panic while holding the lock can't leave the map half-updated
+    self
+      .students
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+  }
+}
+
+#[async_trait::async_trait]
+impl StudentStore for InMemoryStore {
+  async fn save(&self, student: &Student) -> Result<(), Error> {
+    insert(&mut self.students(), student)
+  }
+
+  async fn create(&self, student: &Student) -> Result<(), Error> {
+    let mut students = self.students();
+    if students.contains_key(&student.id.to_string()) {
+      return Err(Error::Conflict(format!(
+        "student {} already exists",
+        student.id.to_string()
+      )));
+    }
+    insert(&mut students, student)
+  }
+
+  async fn save_if_version(&self, student: &Student, expected: u64) -> Result<(), Error> {
+    let mut students = self.students();
+    let stored = students
+      .get(&student.id.to_string())
+      .map(|stored| stored.version);
+    if stored != Some(expected) {
+      return Err(Error::Conflict(format!(
+        "student {} is no longer at version {}",
+        student.id.to_string(),
+        expected
+      )));
+    }
+    insert(&mut students, student)
+  }
+
+  async fn load(&self, id: &StudentId) -> Result<Option<Student>, Error> {
+    Ok(self.students().get(&id.to_string()).cloned())
+  }
+
+  async fn delete(&self, id: &StudentId) -> Result<(), Error> {
+    self.students().remove(&id.to_string());
+    Ok(())
+  }
+
+  async fn exists(&self, id: &StudentId) -> Result<bool, Error> {
+    Ok(self.students().contains_key(&id.to_string()))
+  }
+
+  async fn list_by_grade(&self, grade: Grade) -> Result<Vec<StudentId>, Error> {
+    Ok(
+      self
+        .students()
+        .values()
+        .filter(|student| student.grade == grade)
+        .map(|student| student.id.clone())
+        .collect(),
+    )
+  }
+
+  async fn list_page(
+    &self,
+    cursor: Option<&StudentId>,
+    limit: usize,
+    grade: Option<Grade>,
+  ) -> Result<StudentPage, Error> {
+    let students = self.students();
+    let mut ids: Vec<&String> = students
+      .iter()
+      .filter(|(_, student)| grade.is_none_or(|grade| student.grade == grade))
+      .map(|(id, _)| id)
+      .collect();
+    ids.sort();
+    let total_estimate = grade.is_none().then_some(ids.len());
+
+    let start = match cursor {
+      Some(cursor) => ids.partition_point(|id| **id <= cursor.to_string()),
+      None => 0,
+    };
+    let page: Vec<Student> = ids[start..]
+      .iter()
+      .take(limit)
+      .map(|id| students[*id].clone())
+      .collect();
+    let next_cursor = (start + page.len() < ids.len() && !page.is_empty())
+      .then(|| ids[start + page.len() - 1].clone());
+
+    Ok(StudentPage {
+      students: page,
+      next_cursor,
+      total_estimate,
+    })
+  }
+
+  async fn search(&self, query: &str) -> Result<Vec<PublicStudent>, Error> {
+    let students = self.students().values().cloned().collect();
+    Ok(
+      rank_matches(students, query)
+        .iter()
+        .map(Student::to_public)
+        .collect(),
+    )
+  }
+
+  /// Students updated after `since`; deletions aren't tracked, so `deleted` is empty
+  async fn changed_since(&self, since: i64) -> Result<ChangedSince, Error> {
+    let watermark = watermark_at(Utc::now().timestamp_millis(), since);
+    let mut students: Vec<Student> = self
+      .students()
+      .values()
+      .filter(|student| student.updated_at.timestamp_millis() > since)
+      .cloned()
+      .collect();
+    students.sort_by_key(|student| student.updated_at);
+
+    Ok(ChangedSince {
+      students,
+      deleted: Vec::new(),
+      watermark,
+    })
+  }
+
+  async fn save_many(&self, students: &[Student]) -> Result<Vec<Option<String>>, Error> {
+    let mut stored = self.students();
+    Ok(
+      students
+        .iter()
+        .map(|student| match insert(&mut stored, student) {
+          Err(Error::Conflict(reason)) => Some(reason),
+          _ => None,
+        })
+        .collect(),
+    )
+  }
+}
+
+/// Store `student` unless its email belongs to another student
+fn insert(students: &mut HashMap<String, Student>, student: &Student) -> Result<(), Error> {
+  let id = student.id.to_string();
+  if let Some(owner) = students
+    .values()
+    .find(|other| other.email == student.email && other.id.to_string() != id)
+  {
+    return Err(Error::Conflict(format!(
+      "email {} is already used by student {}",
+      student.email,
+      owner.id.to_string()
+    )));
+  }
+
+  students.insert(id, student.clone());
+  Ok(())
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+  use crate::student::store::WATERMARK_SKEW_MS;
+  use chrono::{Datelike, Utc};
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  fn student(id: &str, email: &str, grade: Grade) -> Student {
+    Student::new(
+      id.to_string(),
+      "Memory".to_string(),
+      "Store".to_string(),
+      email.to_string(),
+      grade,
+      Utc::now().year() as u16 + (12 - grade as u16) + 1,
+      None,
+    )
+    .unwrap()
+  }
+
+  #[tokio::test]
+  async fn test_round_trip_without_redis() {
+    setup();
+    let store = InMemoryStore::new();
+    let casey = student("980001", "casey@csxlabs.edu", 10);
+    let id = casey.id.clone();
+
+    assert!(!store.exists(&id).await.unwrap());
+    store.save(&casey).await.unwrap();
+    assert!(store.exists(&id).await.unwrap());
+    assert_eq!(
+      store.load(&id).await.unwrap().unwrap().email,
+      "casey@csxlabs.edu"
+    );
+
+    store
+      .save(&student("980002", "jo@csxlabs.edu", 11))
+      .await
+      .unwrap();
+    let tenth: Vec<String> = store
+      .list_by_grade(10)
+      .await
+      .unwrap()
+      .iter()
+      .map(StudentId::to_string)
+      .collect();
+    assert_eq!(tenth, ["980001"]);
+
+    store.delete(&id).await.unwrap();
+    assert!(store.load(&id).await.unwrap().is_none());
+    assert!(store.list_by_grade(10).await.unwrap().is_empty());
+  }
+
+  #[tokio::test]
+  async fn test_changed_since_watermark_trails_the_read() {
+    setup();
+    let store = InMemoryStore::new();
+    store
+      .save(&student("980040", "lee@csxlabs.edu", 9))
+      .await
+      .unwrap();
+
+    let changes = store.changed_since(0).await.unwrap();
+    assert_eq!(changes.students.len(), 1);
+    assert!(changes.watermark <= Utc::now().timestamp_millis() - WATERMARK_SKEW_MS);
+
+    // A poll from the watermark sees the just-saved student again
+    let repeat = store.changed_since(changes.watermark).await.unwrap();
+    assert_eq!(repeat.students.len(), 1);
+
+    // A watermark ahead of the clock is never moved backwards
+    let ahead = Utc::now().timestamp_millis() + 60_000;
+    assert_eq!(store.changed_since(ahead).await.unwrap().watermark, ahead);
+  }
+
+  #[tokio::test]
+  async fn test_email_belongs_to_one_student() {
+    setup();
+    let store = InMemoryStore::new();
+    store
+      .save(&student("980003", "sam@csxlabs.edu", 9))
+      .await
+      .unwrap();
+
+    let taken = store.save(&student("980004", "sam@csxlabs.edu", 9)).await;
+    assert!(matches!(taken, Err(Error::Conflict(_))));
+    // Saving the owner again is fine
+    store
+      .save(&student("980003", "sam@csxlabs.edu", 10))
+      .await
+      .unwrap();
+  }
+}
@@ -0,0 +1,193 @@
+//! One-off migration from freeform accommodation text to the structured list.
+//!
+//! Each stored student with `special_accommodations` text and an empty
+//! `accommodations` list gets the list derived by `Accommodation::parse_legacy`. The
+//! original text is left in place, so nothing the parser missed is lost. Students who
+//! already have a list are skipped, which makes the migration safe to run again.
+
+use crate::http::Error;
+use crate::redis::RedisPool;
+use crate::student::{store, Accommodation, Student};
+use log::info;
+use serde::Serialize;
+
+/// What an accommodations migration did
+#[derive(Debug, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AccommodationMigration {
+  /// Students given a structured list
+  pub migrated: usize,
+  /// Of those, students whose text matched no known accommodation and was kept as `Other`
+  pub unparseable: usize,
+}
+
+/// What happens to one student in the migration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+  Migrated,
+  Unparseable,
+  Skipped,
+}
+
+/// Derive `student`'s accommodations list from their freeform text, if they need one
+fn migrate(student: &mut Student) -> Outcome {
+  if !student.accommodations.is_empty() {
+    return Outcome::Skipped;
+  }
+  let Some(text) = student.special_accommodations.as_deref() else {
+    return Outcome::Skipped;
+  };
+  let accommodations = Accommodation::parse_legacy(text);
+  if accommodations.is_empty() {
+    return Outcome::Skipped;
+  }
+
+  let unparseable = accommodations
+    .iter()
+    .all(|a| matches!(a, Accommodation::Other(_)));
+  student.record_change(
+    "accommodations",
+    None,
+    serde_json::to_string(&accommodations).ok(),
+  );
+  student.accommodations = accommodations;
+  student.touch();
+
+  if unparseable {
+    Outcome::Unparseable
+  } else {
+    Outcome::Migrated
+  }
+}
+
+/// Give every stored student with freeform accommodation text a structured list
+///
+/// Each migrated student is saved through `store::save`. Students who already have a
+/// list, or have no text, are left untouched, so running this again only picks up
+/// students added or edited through the legacy field since.
+pub async fn migrate_accommodations(pool: &RedisPool) -> Result<AccommodationMigration, Error> {
+  let mut summary = AccommodationMigration::default();
+  for mut student in store::load_all(pool).await? {
+    match migrate(&mut student) {
+      Outcome::Migrated => summary.migrated += 1,
+      Outcome::Unparseable => {
+        summary.migrated += 1;
+        summary.unparseable += 1;
+      }
+      Outcome::Skipped => continue,
+    }
+    store::save(pool, &student).await?;
+  }
+
+  info!(
+    "Migrated accommodations for {} students, {} unparseable",
+    summary.migrated, summary.unparseable
+  );
+  Ok(summary)
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+  use chrono::{Datelike, Utc};
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  fn student(id: &str, accommodations: Option<&str>) -> Student {
+    Student::new(
+      id.to_string(),
+      "Legacy".to_string(),
+      "Text".to_string(),
+      format!("{}@csxlabs.edu", id),
+      10,
+      Utc::now().year() as u16 + 2,
+      accommodations.map(str::to_string),
+    )
+    .unwrap()
+  }
+
+  #[test]
+  fn test_varied_phrasings_migrate_once() {
+    setup();
+    let mut roster = [
+      student("130001", Some("Bottom row locker for wheelchair access")),
+      student("130002", Some("Seat near the EXIT")),
+      student("130003", Some("Peanut allergy")),
+      student("130004", None),
+    ];
+
+    let outcomes: Vec<Outcome> = roster.iter_mut().map(migrate).collect();
+    assert_eq!(
+      outcomes,
+      [
+        Outcome::Migrated,
+        Outcome::Migrated,
+        Outcome::Unparseable,
+        Outcome::Skipped,
+      ]
+    );
+    assert_eq!(
+      roster[0].accommodations,
+      [Accommodation::BottomTier, Accommodation::AdaAccessible]
+    );
+    assert_eq!(roster[1].accommodations, [Accommodation::NearExit]);
+    assert_eq!(
+      roster[2].accommodations,
+      [Accommodation::Other("Peanut allergy".to_string())]
+    );
+    // The original text is kept
+    assert_eq!(
+      roster[0].special_accommodations.as_deref(),
+      Some("Bottom row locker for wheelchair access")
+    );
+
+    // A second run changes nothing
+    assert!(roster
+      .iter_mut()
+      .all(|student| migrate(student) == Outcome::Skipped));
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_migrate_accommodations_saves_students() {
+    use crate::redis::RedisConfig;
+
+    setup();
+    crate::init_env().unwrap();
+    // A private namespace, since the migration touches every student
+    let pool = RedisPool::new(RedisConfig {
+      key_prefix: Some("test-migrate-accommodations".to_string()),
+      ..RedisConfig::default()
+    })
+    .unwrap();
+    pool.delete_prefix("").await.unwrap();
+    let roster = [
+      student("140001", Some("Lower locker, limited reach")),
+      student("140002", Some("Needs extra time")),
+      student("140003", None),
+    ];
+    for student in &roster {
+      store::save(&pool, student).await.unwrap();
+    }
+
+    let summary = migrate_accommodations(&pool).await.unwrap();
+    assert_eq!(
+      summary,
+      AccommodationMigration {
+        migrated: 2,
+        unparseable: 1,
+      }
+    );
+    let migrated = store::load(&pool, &roster[0].id).await.unwrap().unwrap();
+    assert_eq!(migrated.accommodations, [Accommodation::LowerHeight]);
+
+    assert_eq!(
+      migrate_accommodations(&pool).await.unwrap(),
+      AccommodationMigration::default()
+    );
+    pool.delete_prefix("").await.unwrap();
+  }
+}
@@ -1,4 +1,34 @@
+pub mod academic_year;
+pub mod accommodation;
+pub mod anonymize;
+pub mod change_log;
 pub mod create;
+pub mod diff;
+pub mod import;
+pub mod input;
+#[cfg(any(test, feature = "memory-store"))]
+pub mod memory;
+pub mod migrate;
+pub mod public;
+pub mod redact;
+pub mod rollover;
+pub mod search;
+pub mod store;
+pub mod validation;
 
 // Re-export the main types for easier access
-pub use create::{Student, StudentId};
+pub use academic_year::AcademicYear;
+pub use accommodation::{Accommodation, AccommodationNeeds};
+pub use anonymize::anonymize_older_than;
+pub use change_log::ChangeLogEntry;
+pub use create::{Grade, Student, StudentId};
+pub use import::{parse_csv, ImportRow};
+pub use input::CreateStudentRequest;
+#[cfg(any(test, feature = "memory-store"))]
+pub use memory::InMemoryStore;
+pub use migrate::{migrate_accommodations, AccommodationMigration};
+pub use public::PublicStudent;
+pub use rollover::{rollover, Rollover};
+pub use search::search;
+pub use store::{load_many, StorePolicy, StudentStore};
+pub use validation::ValidationConfig;
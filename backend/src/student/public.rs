@@ -0,0 +1,68 @@
+use crate::student::{Grade, Student, StudentId};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// The parts of a `Student` that are safe to show without an API key.
+///
+/// Leaves out the email, timestamps and, above all, accommodations, which are only
+/// available through authenticated requests.
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct PublicStudent {
+  pub id: StudentId,
+  pub full_name: String,
+  pub grade: Grade,
+  pub grade_level: String,
+}
+
+impl Student {
+  /// The public view of this student
+  pub fn to_public(&self) -> PublicStudent {
+    PublicStudent {
+      id: self.id.clone(),
+      full_name: self.full_name(),
+      grade: self.grade,
+      grade_level: self.grade_level(),
+    }
+  }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use crate::init_logging;
+  use crate::student::Student;
+  use chrono::{Datelike, Utc};
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  #[test]
+  fn test_public_student_omits_accommodations() {
+    setup();
+    let student = Student::new(
+      "123456".to_string(),
+      "Casey".to_string(),
+      "Jones".to_string(),
+      "casey@csxlabs.edu".to_string(),
+      11,
+      Utc::now().year() as u16 + 1,
+      Some("Bottom row locker for wheelchair access".to_string()),
+    )
+    .unwrap();
+
+    let public = serde_json::to_value(student.to_public()).unwrap();
+    assert_eq!(public["id"], "123456");
+    assert_eq!(public["full_name"], "Casey Jones");
+    assert_eq!(public["grade_level"], "Junior");
+    for key in [
+      "special_accommodations",
+      "accommodation",
+      "accommodations",
+      "email",
+      "created_at",
+    ] {
+      assert!(public.get(key).is_none(), "{} leaked", key);
+    }
+  }
+}
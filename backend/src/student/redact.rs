@@ -0,0 +1,108 @@
+//! Log-safe formatting of student records.
+//!
+//! `Student`'s `Debug` output goes to logs, so the email, accommodations and change log
+//! (which holds old values of both) are printed as `<redacted>`. Diagnostics that are
+//! allowed to see everything use `Student::debug_full`.
+
+use crate::student::Student;
+use std::fmt;
+
+/// Placeholder printed instead of sensitive fields
+const REDACTED: &str = "<redacted>";
+
+impl fmt::Debug for Student {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let redacted = format_args!("{}", REDACTED);
+    f.debug_struct("Student")
+      .field("id", &self.id)
+      .field("first_name", &self.first_name)
+      .field("last_name", &self.last_name)
+      .field("email", &redacted)
+      .field("grade", &self.grade)
+      .field("graduation_year", &self.graduation_year)
+      .field("special_accommodations", &redacted)
+      .field("accommodation", &redacted)
+      .field("accommodations", &redacted)
+      .field("active", &self.active)
+      .field("change_log", &redacted)
+      .field("version", &self.version)
+      .field("created_at", &self.created_at)
+      .field("updated_at", &self.updated_at)
+      .finish()
+  }
+}
+
+/// Unredacted `Debug` view of a student, see `Student::debug_full`
+pub struct FullDebug<'a>(&'a Student);
+
+impl fmt::Debug for FullDebug<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let student = self.0;
+    f.debug_struct("Student")
+      .field("id", &student.id)
+      .field("first_name", &student.first_name)
+      .field("last_name", &student.last_name)
+      .field("email", &student.email)
+      .field("grade", &student.grade)
+      .field("graduation_year", &student.graduation_year)
+      .field("special_accommodations", &student.special_accommodations)
+      .field("accommodation", &student.accommodation)
+      .field("accommodations", &student.accommodations)
+      .field("active", &student.active)
+      .field("change_log", &student.change_log)
+      .field("version", &student.version)
+      .field("created_at", &student.created_at)
+      .field("updated_at", &student.updated_at)
+      .finish()
+  }
+}
+
+impl Student {
+  /// Every field, including the ones the default `Debug` output redacts
+  ///
+  /// Only for authorized diagnostics; never pass this to a log that leaves the server.
+  pub fn debug_full(&self) -> FullDebug<'_> {
+    FullDebug(self)
+  }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+  use chrono::{Datelike, Utc};
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  #[test]
+  fn test_debug_redacts_email_and_accommodations() {
+    setup();
+    let mut student = Student::new(
+      "123456".to_string(),
+      "Jane".to_string(),
+      "Smith".to_string(),
+      "jane.smith@csxlabs.edu".to_string(),
+      10,
+      Utc::now().year() as u16 + 2,
+      None,
+    )
+    .unwrap();
+    // Also leaves the text in the change log
+    student
+      .update_special_accommodations(Some("Uses a wheelchair".to_string()))
+      .unwrap();
+
+    let redacted = format!("{:?}", student);
+    assert!(redacted.contains("<redacted>"));
+    assert!(redacted.contains("Jane"));
+    assert!(!redacted.contains("wheelchair"));
+    assert!(!redacted.contains("jane.smith@csxlabs.edu"));
+
+    let full = format!("{:?}", student.debug_full());
+    assert!(full.contains("wheelchair"));
+    assert!(full.contains("jane.smith@csxlabs.edu"));
+  }
+}
@@ -0,0 +1,371 @@
+//! Year-end roster rollover.
+//!
+//! Every active student moves up a grade; seniors whose graduation year has passed are
+//! deactivated rather than deleted, so their records stay available for audit. The
+//! last academic year rolled over to is kept in `students:rollover_year`, which makes
+//! running the rollover again for the same year a no-op.
+//!
+//! While a rollover runs, each student it changes is added to `students:rolled:{year}`
+//! in the same transaction as their record, so a rerun after a failure partway through
+//! skips them instead of promoting them twice.
+
+use crate::http::Error;
+use crate::redis::{RedisOperations, RedisPool};
+use crate::student::{store, AcademicYear, Student, ValidationConfig};
+use log::{debug, info};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// Redis key holding the academic year the roster was last rolled over to
+pub const ROLLOVER_YEAR_KEY: &str = "students:rollover_year";
+
+/// Redis key of the set of students already rolled over into the year starting `start_year`
+pub fn rolled_key(start_year: u16) -> String {
+  format!("students:rolled:{}", start_year)
+}
+
+/// Longest a rollover may hold its lock
+const ROLLOVER_LOCK_TTL: Duration = Duration::from_secs(300);
+
+/// What a rollover did
+#[derive(Debug, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Rollover {
+  /// Students moved up one grade
+  pub promoted: usize,
+  /// Seniors deactivated because they graduated
+  pub graduated: usize,
+  /// True if the roster had already been rolled over to this year, so nothing changed
+  pub already_done: bool,
+}
+
+/// What happens to one student in a rollover
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+  Promoted,
+  Graduated,
+  Unchanged,
+}
+
+/// Move `student` into `new_academic_year`, at a school with `config.grade_range`
+///
+/// A student in the top grade graduates once `graduation_year` is earlier than the new
+/// year's top grade's; one with a later graduation year is held back and stays put.
+fn roll(
+  student: &mut Student,
+  new_academic_year: AcademicYear,
+  config: &ValidationConfig,
+) -> Result<Outcome, Error> {
+  let top_grade = *config.grade_range.end();
+  if !student.active {
+    return Ok(Outcome::Unchanged);
+  }
+  if student.grade < top_grade {
+    student.update_grade_with_config(student.grade + 1, config)?;
+    return Ok(Outcome::Promoted);
+  }
+  if student.graduation_year < new_academic_year.graduation_year_in(top_grade, top_grade) {
+    student.deactivate();
+    return Ok(Outcome::Graduated);
+  }
+  Ok(Outcome::Unchanged)
+}
+
+/// Advance the roster into academic year `new_academic_year`, for the default grades
+pub async fn rollover(
+  pool: &RedisPool,
+  new_academic_year: AcademicYear,
+) -> Result<Rollover, Error> {
+  rollover_with_config(pool, new_academic_year, &ValidationConfig::default()).await
+}
+
+/// Advance the roster into academic year `new_academic_year`
+///
+/// Every grade below the top of `config.grade_range` moves up one grade and graduated
+/// students in the top grade are deactivated; each change is saved through
+/// `store::save_marking`, which moves the student between the grade index sets and
+/// records them in `students:rolled:{year}`. Running it again for the same (or an
+/// earlier) year changes nothing, and rerunning after a failure only rolls the students
+/// that weren't saved yet. Use `AcademicYear::current` for the year that has just
+/// started.
+///
+/// # Errors
+/// Returns `Error::Conflict` if another rollover is already running.
+pub async fn rollover_with_config(
+  pool: &RedisPool,
+  new_academic_year: AcademicYear,
+  config: &ValidationConfig,
+) -> Result<Rollover, Error> {
+  let Some(lock) = pool.acquire_lock("rollover", ROLLOVER_LOCK_TTL).await? else {
+    return Err(Error::Conflict("a rollover is already running".to_string()));
+  };
+
+  let last_year: Option<u16> = pool.get_opt(ROLLOVER_YEAR_KEY).await?;
+  if last_year.is_some_and(|last_year| last_year >= new_academic_year.start_year()) {
+    debug!(
+      "Roster already rolled over to {}, skipping",
+      last_year.unwrap_or_default()
+    );
+    lock.release().await?;
+    return Ok(Rollover {
+      already_done: true,
+      ..Rollover::default()
+    });
+  }
+
+  let progress_key = rolled_key(new_academic_year.start_year());
+  let rolled: HashSet<String> = pool.smembers(&progress_key).await?.into_iter().collect();
+  if !rolled.is_empty() {
+    info!(
+      "Resuming rollover to {}, {} students already done",
+      new_academic_year.start_year(),
+      rolled.len()
+    );
+  }
+
+  let mut summary = Rollover::default();
+  for mut student in store::load_all(pool).await? {
+    if rolled.contains(&student.id.to_string()) {
+      continue;
+    }
+    match roll(&mut student, new_academic_year, config)? {
+      Outcome::Promoted => summary.promoted += 1,
+      Outcome::Graduated => summary.graduated += 1,
+      Outcome::Unchanged => continue,
+    }
+    store::save_marking(pool, &student, &progress_key).await?;
+  }
+
+  let mut pipe = redis::pipe();
+  pipe
+    .set(
+      pool.prefixed(ROLLOVER_YEAR_KEY),
+      new_academic_year.start_year(),
+    )
+    .ignore()
+    .del(pool.prefixed(&progress_key))
+    .ignore();
+  pool.execute_pipeline::<()>(&pipe).await?;
+  lock.release().await?;
+
+  info!(
+    "Rolled roster over to {}: {} promoted, {} graduated",
+    new_academic_year.start_year(),
+    summary.promoted,
+    summary.graduated
+  );
+  Ok(summary)
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+  use crate::student::academic_year::DEFAULT_START_MONTH;
+  use chrono::Utc;
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  /// The academic year students are created in, and the one the roster rolls into
+  fn years() -> (AcademicYear, AcademicYear) {
+    let current = AcademicYear::current(Utc::now(), DEFAULT_START_MONTH);
+    (current, AcademicYear::new(current.start_year() + 1))
+  }
+
+  fn student(id: &str, grade: u8, graduation_year: u16) -> Student {
+    Student::new(
+      id.to_string(),
+      "Roll".to_string(),
+      "Over".to_string(),
+      format!("{}@csxlabs.edu", id),
+      grade,
+      graduation_year,
+      None,
+    )
+    .unwrap()
+  }
+
+  #[test]
+  fn test_each_cohort_rolls_to_the_right_place() {
+    setup();
+    let (current, next) = years();
+    let year = current.graduation_year_for(12);
+    let mut roster = [
+      student("110009", 9, year + 3),
+      student("110010", 10, year + 2),
+      student("110011", 11, year + 1),
+      student("110012", 12, year),
+      student("110013", 12, year + 1),
+    ];
+
+    let outcomes: Vec<Outcome> = roster
+      .iter_mut()
+      .map(|student| roll(student, next, &ValidationConfig::default()).unwrap())
+      .collect();
+
+    assert_eq!(
+      outcomes,
+      [
+        Outcome::Promoted,
+        Outcome::Promoted,
+        Outcome::Promoted,
+        Outcome::Graduated,
+        Outcome::Unchanged,
+      ]
+    );
+    let grades: Vec<u8> = roster.iter().map(|s| s.grade).collect();
+    assert_eq!(grades, [10, 11, 12, 12, 12]);
+    assert!(!roster[3].active);
+    assert!(roster[4].active);
+  }
+
+  #[test]
+  fn test_top_grade_comes_from_the_config() {
+    setup();
+    let (current, next) = years();
+    let config = ValidationConfig {
+      grade_range: 6..=8,
+      ..ValidationConfig::default()
+    };
+    let year = current.graduation_year_in(8, 8);
+    let mut roster = [
+      Student::new_with_config(
+        "110107".to_string(),
+        "Roll".to_string(),
+        "Over".to_string(),
+        "110107@csxlabs.edu".to_string(),
+        7,
+        year + 1,
+        None,
+        &config,
+      )
+      .unwrap(),
+      Student::new_with_config(
+        "110108".to_string(),
+        "Roll".to_string(),
+        "Over".to_string(),
+        "110108@csxlabs.edu".to_string(),
+        8,
+        year,
+        None,
+        &config,
+      )
+      .unwrap(),
+    ];
+
+    let outcomes: Vec<Outcome> = roster
+      .iter_mut()
+      .map(|student| roll(student, next, &config).unwrap())
+      .collect();
+
+    assert_eq!(outcomes, [Outcome::Promoted, Outcome::Graduated]);
+    assert_eq!(roster[0].grade, 8);
+    assert!(!roster[1].active);
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_rollover_moves_grade_indexes_once() {
+    use crate::redis::RedisConfig;
+    use crate::student::store::{grade_key, list_by_grade};
+    use crate::student::StudentId;
+
+    setup();
+    crate::init_env().unwrap();
+    // A private namespace, since rollover touches every student
+    let pool = RedisPool::new(RedisConfig {
+      key_prefix: Some("test-rollover".to_string()),
+      ..RedisConfig::default()
+    })
+    .unwrap();
+    let (current, next) = years();
+    let year = current.graduation_year_for(12);
+    let roster = [
+      student("120009", 9, year + 3),
+      student("120011", 11, year + 1),
+      student("120012", 12, year),
+    ];
+    for student in &roster {
+      store::save(&pool, student).await.unwrap();
+    }
+    pool.del(ROLLOVER_YEAR_KEY).await.unwrap();
+
+    let summary = rollover(&pool, next).await.unwrap();
+    assert_eq!((summary.promoted, summary.graduated), (2, 1));
+    assert!(rollover(&pool, next).await.unwrap().already_done);
+
+    for (grade, expected) in [
+      (9, vec![]),
+      (10, vec!["120009"]),
+      (12, vec!["120011", "120012"]),
+    ] {
+      let mut ids: Vec<String> = list_by_grade(&pool, grade)
+        .await
+        .unwrap()
+        .iter()
+        .map(StudentId::to_string)
+        .collect();
+      ids.sort();
+      assert_eq!(ids, expected, "grade {}", grade);
+    }
+    let graduate = store::load(&pool, &roster[2].id).await.unwrap().unwrap();
+    assert!(!graduate.active);
+    assert!(!pool.exists(&rolled_key(next.start_year())).await.unwrap());
+
+    for student in &roster {
+      store::delete(&pool, &student.id).await.unwrap();
+    }
+    for key in [
+      ROLLOVER_YEAR_KEY.to_string(),
+      grade_key(10),
+      grade_key(12),
+      store::TOMBSTONE_KEY.to_string(),
+    ] {
+      pool.del(&key).await.unwrap();
+    }
+  }
+
+  #[cfg(feature = "redis-tests")]
+  #[tokio::test]
+  async fn test_rollover_resumes_after_partial_failure() {
+    use crate::redis::RedisConfig;
+
+    setup();
+    crate::init_env().unwrap();
+    let pool = RedisPool::new(RedisConfig {
+      key_prefix: Some("test-rollover-resume".to_string()),
+      ..RedisConfig::default()
+    })
+    .unwrap();
+    pool.delete_prefix("").await.unwrap();
+    let (current, next) = years();
+    let year = current.graduation_year_for(12);
+    let mut done = student("120109", 9, year + 3);
+    let pending = student("120110", 10, year + 2);
+    store::save(&pool, &pending).await.unwrap();
+
+    // An earlier run promoted one student, then failed before finishing
+    assert_eq!(
+      roll(&mut done, next, &ValidationConfig::default()).unwrap(),
+      Outcome::Promoted
+    );
+    store::save_marking(&pool, &done, &rolled_key(next.start_year()))
+      .await
+      .unwrap();
+
+    let summary = rollover(&pool, next).await.unwrap();
+    assert_eq!((summary.promoted, summary.graduated), (1, 0));
+    let grade = |id| {
+      let pool = &pool;
+      async move { store::load(pool, id).await.unwrap().unwrap().grade }
+    };
+    assert_eq!(grade(&done.id).await, 10);
+    assert_eq!(grade(&pending.id).await, 11);
+    assert!(!pool.exists(&rolled_key(next.start_year())).await.unwrap());
+
+    pool.delete_prefix("").await.unwrap();
+  }
+}
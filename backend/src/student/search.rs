@@ -0,0 +1,133 @@
+//! Partial-name lookup over the roster.
+//!
+//! Matching is case-insensitive. A student whose first name (or full name) starts with
+//! the query ranks above one whose last name does, and both rank above a match in the
+//! middle of a name.
+
+use crate::http::Error;
+use crate::redis::RedisPool;
+use crate::student::{store, PublicStudent, Student};
+use log::debug;
+
+/// How well a student's name matches a query, best first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Rank {
+  FirstNamePrefix,
+  LastNamePrefix,
+  Substring,
+}
+
+/// Rank `student` against an already lowercased `query`, or `None` if it doesn't match
+fn rank(student: &Student, query: &str) -> Option<Rank> {
+  let first_name = student.first_name.to_lowercase();
+  let last_name = student.last_name.to_lowercase();
+  let full_name = student.full_name().to_lowercase();
+
+  if first_name.starts_with(query) || full_name.starts_with(query) {
+    Some(Rank::FirstNamePrefix)
+  } else if last_name.starts_with(query) {
+    Some(Rank::LastNamePrefix)
+  } else if full_name.contains(query) {
+    Some(Rank::Substring)
+  } else {
+    None
+  }
+}
+
+/// The students matching `query`, best match first, then by last and first name
+pub(crate) fn rank_matches(students: Vec<Student>, query: &str) -> Vec<Student> {
+  let query = query.trim().to_lowercase();
+  if query.is_empty() {
+    return Vec::new();
+  }
+
+  let mut matches: Vec<(Rank, Student)> = students
+    .into_iter()
+    .filter_map(|student| rank(&student, &query).map(|rank| (rank, student)))
+    .collect();
+  matches.sort_by_cached_key(|(rank, student)| {
+    (
+      *rank,
+      student.last_name.to_lowercase(),
+      student.first_name.to_lowercase(),
+      student.id.to_string(),
+    )
+  });
+  matches.into_iter().map(|(_, student)| student).collect()
+}
+
+/// Find students whose first or last name contains `query`, ignoring case
+///
+/// Scans the whole roster, so it's meant for interactive lookups rather than bulk use.
+/// An empty query matches nobody.
+pub async fn search(pool: &RedisPool, query: &str) -> Result<Vec<PublicStudent>, Error> {
+  let students = store::load_all(pool).await?;
+  let matches = rank_matches(students, query);
+  debug!("Search for {:?} matched {} students", query, matches.len());
+
+  Ok(matches.iter().map(Student::to_public).collect())
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::init_logging;
+  use chrono::{Datelike, Utc};
+
+  fn setup() {
+    let _ = init_logging(); // Ignore error if already initialized
+  }
+
+  fn student(id: &str, first_name: &str, last_name: &str) -> Student {
+    Student::new(
+      id.to_string(),
+      first_name.to_string(),
+      last_name.to_string(),
+      format!("{}@csxlabs.edu", id),
+      10,
+      Utc::now().year() as u16 + 2,
+      None,
+    )
+    .unwrap()
+  }
+
+  fn names(students: &[Student]) -> Vec<String> {
+    students.iter().map(Student::full_name).collect()
+  }
+
+  #[test]
+  fn test_first_name_prefix_ranks_above_last_name_prefix() {
+    setup();
+    let roster = vec![
+      student("100001", "Casey", "Jones"),
+      student("100002", "John", "Smith"),
+      student("100003", "Ada", "Lovelace"),
+    ];
+
+    assert_eq!(
+      names(&rank_matches(roster, "jo")),
+      ["John Smith", "Casey Jones"]
+    );
+  }
+
+  #[test]
+  fn test_substring_matches_rank_last() {
+    setup();
+    let roster = vec![
+      student("100004", "Marjorie", "Diaz"),
+      student("100005", "Jordan", "Lee"),
+      student("100006", "Sam", "Jordan"),
+    ];
+
+    assert_eq!(
+      names(&rank_matches(roster.clone(), "JOR")),
+      ["Jordan Lee", "Sam Jordan", "Marjorie Diaz"]
+    );
+    assert_eq!(
+      names(&rank_matches(roster.clone(), "jordan l")),
+      ["Jordan Lee"]
+    );
+    assert!(rank_matches(roster, "  ").is_empty());
+  }
+}
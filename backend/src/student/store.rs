@@ -0,0 +1,935 @@
+//! Redis persistence for students.
+//!
+//! Key scheme:
+//! - `student:{id}`: JSON `Student` record
+//! - `students:by_updated`: sorted set of student ids scored by `updated_at` epoch millis
+//! - `students:deleted`: sorted set of tombstoned student ids scored by deletion epoch millis
+//! - `students:grade:{n}`: set of the ids of students currently in grade `n`
+//! - `email:{email}`: id of the student using an email address
+
+use crate::http::Error;
+use crate::redis::{RedisOperations, RedisPool};
+use crate::student::{search, Grade, PublicStudent, Student, StudentId};
+use chrono::Utc;
+use log::{debug, warn};
+use serde::Serialize;
+use std::ops::RangeInclusive;
+use std::time::Duration;
+use utoipa::ToSchema;
+
+/// Sorted set of student ids scored by `updated_at` epoch millis
+pub const UPDATED_INDEX_KEY: &str = "students:by_updated";
+
+/// Sorted set of deleted student ids scored by deletion epoch millis
+pub const TOMBSTONE_KEY: &str = "students:deleted";
+
+/// How far the `changed_since` watermark trails the read time, so a write stamped just
+/// before the read but committed after it is still returned by the next poll
+pub const WATERMARK_SKEW_MS: i64 = 5_000;
+
+/// Grades with a `students:grade:{n}` index set
+const INDEXED_GRADES: RangeInclusive<Grade> = 9..=12;
+
+/// Students fetched per `MGET` when loading the whole roster
+const LOAD_BATCH_SIZE: usize = 200;
+
+/// Prefix of the per-student record keys
+const STUDENT_KEY_PREFIX: &str = "student:";
+
+/// The student operations the HTTP handlers need
+///
+/// Implemented by `RedisPool` with the functions in this module, and by
+/// `InMemoryStore` for tests and local development without Redis.
+#[async_trait::async_trait]
+pub trait StudentStore: Send + Sync {
+  /// Store a student, failing with `Error::Conflict` if its email belongs to another
+  async fn save(&self, student: &Student) -> Result<(), Error>;
+  /// Store a new student, failing with `Error::Conflict` if one with its id exists
+  ///
+  /// The existence check and the write are atomic, so of two concurrent creations of
+  /// the same id exactly one succeeds.
+  async fn create(&self, student: &Student) -> Result<(), Error>;
+  /// Store a student only if the stored record is still at version `expected`
+  ///
+  /// The compare and the write are atomic, failing with `Error::Conflict` if another
+  /// update got there first.
+  async fn save_if_version(&self, student: &Student, expected: u64) -> Result<(), Error>;
+  /// Load a student, returning `Ok(None)` if there is none
+  async fn load(&self, id: &StudentId) -> Result<Option<Student>, Error>;
+  /// Delete a student and release its email; deleting a missing student is a no-op
+  async fn delete(&self, id: &StudentId) -> Result<(), Error>;
+  /// Returns true if a record is stored for the student
+  async fn exists(&self, id: &StudentId) -> Result<bool, Error>;
+  /// Ids of the students in `grade`, in no particular order
+  async fn list_by_grade(&self, grade: Grade) -> Result<Vec<StudentId>, Error>;
+  /// Up to `limit` students with ids after `cursor`, optionally only those in `grade`
+  async fn list_page(
+    &self,
+    cursor: Option<&StudentId>,
+    limit: usize,
+    grade: Option<Grade>,
+  ) -> Result<StudentPage, Error>;
+  /// Students whose name matches `query`, best match first
+  async fn search(&self, query: &str) -> Result<Vec<PublicStudent>, Error>;
+  /// Students updated or deleted strictly after `since` (epoch millis)
+  async fn changed_since(&self, since: i64) -> Result<ChangedSince, Error>;
+  /// Store several students, one outcome per student in order
+  ///
+  /// A taken email fails only that student, as `Some(reason)`; any other error aborts
+  /// the batch.
+  async fn save_many(&self, students: &[Student]) -> Result<Vec<Option<String>>, Error>;
+}
+
+#[async_trait::async_trait]
+impl StudentStore for RedisPool {
+  async fn save(&self, student: &Student) -> Result<(), Error> {
+    save(self, student).await
+  }
+
+  async fn create(&self, student: &Student) -> Result<(), Error> {
+    create(self, student).await
+  }
+
+  async fn save_if_version(&self, student: &Student, expected: u64) -> Result<(), Error> {
+    save_if_version(self, student, expected).await
+  }
+
+  async fn load(&self, id: &StudentId) -> Result<Option<Student>, Error> {
+    load(self, id).await
+  }
+
+  async fn delete(&self, id: &StudentId) -> Result<(), Error> {
+    delete(self, id).await
+  }
+
+  async fn exists(&self, id: &StudentId) -> Result<bool, Error> {
+    exists(self, id).await
+  }
+
+  async fn list_by_grade(&self, grade: Grade) -> Result<Vec<StudentId>, Error> {
+    list_by_grade(self, grade).await
+  }
+
+  async fn list_page(
+    &self,
+    cursor: Option<&StudentId>,
+    limit: usize,
+    grade: Option<Grade>,
+  ) -> Result<StudentPage, Error> {
+    list_page(self, cursor, limit, grade).await
+  }
+
+  async fn search(&self, query: &str) -> Result<Vec<PublicStudent>, Error> {
+    search::search(self, query).await
+  }
+
+  async fn changed_since(&self, since: i64) -> Result<ChangedSince, Error> {
+    changed_since(self, since).await
+  }
+
+  async fn save_many(&self, students: &[Student]) -> Result<Vec<Option<String>>, Error> {
+    let mut outcomes = Vec::with_capacity(students.len());
+    for student in students {
+      match save(self, student).await {
+        Ok(()) => outcomes.push(None),
+        Err(Error::Conflict(reason)) => outcomes.push(Some(reason)),
+        Err(e) => return Err(e),
+      }
+    }
+    Ok(outcomes)
+  }
+}
+
+/// Redis key holding the JSON record for a student
+pub fn student_key(id: &StudentId) -> String {
+  key_for(&id.to_string())
+}
+
+/// Redis key of the set of student ids in `grade`
+pub fn grade_key(grade: Grade) -> String {
+  format!("students:grade:{}", grade)
+}
+
+/// Redis key mapping an email address to the student using it
+pub fn email_key(email: &str) -> String {
+  format!("email:{}", email)
+}
+
+/// Record key for an id read back from an index
+fn key_for(id: &str) -> String {
+  format!("{}{}", STUDENT_KEY_PREFIX, id)
+}
+
+/// Students changed after a watermark, for incremental sync clients.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChangedSince {
+  /// Students updated after the requested timestamp, oldest first
+  pub students: Vec<Student>,
+  /// Ids of students deleted after the requested timestamp
+  pub deleted: Vec<String>,
+  /// Epoch millis to pass as `ts` on the next poll
+  ///
+  /// Trails the read time by `WATERMARK_SKEW_MS`, so the next poll may repeat the most
+  /// recent changes.
+  pub watermark: i64,
+}
+
+/// One page of the student roster, ordered by id.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StudentPage {
+  pub students: Vec<Student>,
+  /// Id to pass as `cursor` for the next page, or `None` on the last page
+  pub next_cursor: Option<String>,
+  /// Number of students on the roster; omitted when filtering makes it expensive
+  pub total_estimate: Option<usize>,
+}
+
+/// How long stored student records live
+///
+/// This covers the `student:{id}` records and their `email:{email}` claims; short-lived
+/// cache keys set their own expiry. The grade and change indexes can't expire per
+/// member, so reads that find an index entry without a record prune it (see
+/// `prune_expired`). The default keeps students until they're deleted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StorePolicy {
+  /// Expiry applied on every save, or `None` to keep records indefinitely
+  pub student_ttl: Option<Duration>,
+}
+
+/// Store a student record, claim its email and update the change and grade indexes
+///
+/// Everything is written in one transaction. Returns `Error::Conflict` if the email
+/// already belongs to a different student; when a student's email changes, the old
+/// address is released in the same transaction. Records never expire; see
+/// `save_with_policy`.
+pub async fn save(pool: &RedisPool, student: &Student) -> Result<(), Error> {
+  save_with_policy(pool, student, &StorePolicy::default()).await
+}
+
+/// Like `save`, but only for a new student
+///
+/// Whether the id is taken is checked inside the transaction, so this returns
+/// `Error::Conflict` instead of overwriting a student created concurrently.
+pub async fn create(pool: &RedisPool, student: &Student) -> Result<(), Error> {
+  let options = SaveOptions {
+    create: true,
+    ..SaveOptions::default()
+  };
+  save_checked(pool, student, &options).await
+}
+
+/// Like `save`, but only if the stored record is still at version `expected`
+///
+/// The version is compared inside the transaction, so a concurrent update between the
+/// caller's load and this save fails with `Error::Conflict` instead of being overwritten.
+pub async fn save_if_version(
+  pool: &RedisPool,
+  student: &Student,
+  expected: u64,
+) -> Result<(), Error> {
+  let options = SaveOptions {
+    expected_version: Some(expected),
+    ..SaveOptions::default()
+  };
+  save_checked(pool, student, &options).await
+}
+
+/// Like `save`, also adding the student's id to the set `progress_key` in the same
+/// transaction
+///
+/// Batch jobs use this to record which students they've finished, so a rerun after a
+/// failure can skip them without risking a student saved but not marked.
+pub async fn save_marking(
+  pool: &RedisPool,
+  student: &Student,
+  progress_key: &str,
+) -> Result<(), Error> {
+  let options = SaveOptions {
+    progress_key: Some(progress_key),
+    ..SaveOptions::default()
+  };
+  save_checked(pool, student, &options).await
+}
+
+/// Like `save`, writing the record with `policy.student_ttl` as its expiry
+pub async fn save_with_policy(
+  pool: &RedisPool,
+  student: &Student,
+  policy: &StorePolicy,
+) -> Result<(), Error> {
+  let options = SaveOptions {
+    policy: *policy,
+    ..SaveOptions::default()
+  };
+  save_checked(pool, student, &options).await
+}
+
+/// How `save_checked` writes a student, beyond the record and its indexes
+#[derive(Debug, Default)]
+struct SaveOptions<'a> {
+  policy: StorePolicy,
+  /// Fail with `Error::Conflict` if a record with the student's id already exists
+  create: bool,
+  /// Fail with `Error::Conflict` unless the stored record is at this version
+  expected_version: Option<u64>,
+  /// Set to add the student's id to in the same transaction
+  progress_key: Option<&'a str>,
+}
+
+/// Save a student as described by `options`
+async fn save_checked(
+  pool: &RedisPool,
+  student: &Student,
+  options: &SaveOptions<'_>,
+) -> Result<(), Error> {
+  let id = student.id.to_string();
+  let record_key = student_key(&student.id);
+  let owner_key = email_key(&student.email);
+  let json = serde_json::to_string(student)
+    .map_err(|e| Error::RedisParseError(format!("Failed to serialize student: {}", e)))?;
+
+  pool
+    .transaction::<(), _, _>(&[&record_key, &owner_key], |mut conn| {
+      let id = id.clone();
+      let email = student.email.clone();
+      let record_key = pool.prefixed(&record_key);
+      let owner_key = pool.prefixed(&owner_key);
+      let json = json.clone();
+
+      async move {
+        let owner: Option<String> = redis::cmd("GET")
+          .arg(&owner_key)
+          .query_async(&mut conn)
+          .await?;
+        if let Some(owner) = owner.filter(|owner| *owner != id) {
+          return Err(Error::Conflict(format!(
+            "email {} is already used by student {}",
+            email, owner
+          )));
+        }
+
+        let mut pipe = redis::pipe();
+
+        // Release the address the student had before, if it changed
+        let previous: Option<String> = redis::cmd("GET")
+          .arg(&record_key)
+          .query_async(&mut conn)
+          .await?;
+        if options.create && previous.is_some() {
+          return Err(Error::Conflict(format!("student {} already exists", id)));
+        }
+        let previous = previous.and_then(|json| serde_json::from_str::<Student>(&json).ok());
+        if let Some(expected) = options.expected_version {
+          let stored = previous.as_ref().map(|previous| previous.version);
+          if stored != Some(expected) {
+            return Err(Error::Conflict(format!(
+              "student {} is no longer at version {}",
+              id, expected
+            )));
+          }
+        }
+        if let Some(previous) = previous {
+          if previous.email != email {
+            pipe
+              .del(pool.prefixed(&email_key(&previous.email)))
+              .ignore();
+          }
+        }
+
+        // The email claim expires with the record, so it can't outlive the student
+        match options.policy.student_ttl {
+          Some(ttl) => pipe
+            .set_ex(&record_key, &json, ttl.as_secs().max(1))
+            .ignore()
+            .set_ex(&owner_key, &id, ttl.as_secs().max(1)),
+          None => pipe.set(&record_key, &json).ignore().set(&owner_key, &id),
+        };
+        pipe
+          .ignore()
+          .zadd(
+            pool.prefixed(UPDATED_INDEX_KEY),
+            &id,
+            student.updated_at.timestamp_millis(),
+          )
+          .ignore()
+          // A re-created student is no longer deleted
+          .zrem(pool.prefixed(TOMBSTONE_KEY), &id)
+          .ignore();
+
+        // Move the id into its grade's set, out of whichever set held it before
+        for grade in INDEXED_GRADES.filter(|grade| *grade != student.grade) {
+          pipe.srem(pool.prefixed(&grade_key(grade)), &id).ignore();
+        }
+        pipe
+          .sadd(pool.prefixed(&grade_key(student.grade)), &id)
+          .ignore();
+        if let Some(progress_key) = options.progress_key {
+          pipe.sadd(pool.prefixed(progress_key), &id).ignore();
+        }
+        Ok(pipe)
+      }
+    })
+    .await
+}
+
+/// Ids of the students in `grade`, in no particular order
+pub async fn list_by_grade(pool: &RedisPool, grade: Grade) -> Result<Vec<StudentId>, Error> {
+  let ids: Vec<String> = pool.smembers(&grade_key(grade)).await?;
+  Ok(
+    ids
+      .into_iter()
+      .filter_map(|id| match StudentId::from_string(id.clone()) {
+        Ok(id) => Some(id),
+        Err(_) => {
+          warn!(
+            "Skipping invalid student id {} in grade {} index",
+            id, grade
+          );
+          None
+        }
+      })
+      .collect(),
+  )
+}
+
+/// Load a student record, returning `Ok(None)` if there is none
+pub async fn load(pool: &RedisPool, id: &StudentId) -> Result<Option<Student>, Error> {
+  match pool.get_json(&student_key(id)).await {
+    Ok(student) => Ok(Some(student)),
+    Err(Error::RedisKeyNotFound(_)) => Ok(None),
+    Err(e) => Err(e),
+  }
+}
+
+/// Load many student records in one `MGET` round-trip, in request order
+///
+/// Ids with no record are skipped, as are records that fail to parse, which are logged.
+pub async fn load_many(pool: &RedisPool, ids: &[StudentId]) -> Result<Vec<Student>, Error> {
+  if ids.is_empty() {
+    return Ok(Vec::new());
+  }
+
+  let keys: Vec<String> = ids.iter().map(student_key).collect();
+  let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+  let values: Vec<Option<String>> = pool.mget(&keys).await?;
+  Ok(parse_records(&keys, values))
+}
+
+/// Deserialize the `MGET` results for `keys`, skipping missing and unparseable records
+fn parse_records(keys: &[&str], values: Vec<Option<String>>) -> Vec<Student> {
+  let mut students = Vec::with_capacity(keys.len());
+  for (key, json) in keys.iter().zip(values) {
+    match json.map(|json| serde_json::from_str::<Student>(&json)) {
+      Some(Ok(student)) => students.push(student),
+      Some(Err(e)) => warn!("Skipping unparseable student {}: {}", key, e),
+      None => {}
+    }
+  }
+  students
+}
+
+/// Returns true if a record is stored for the student
+pub async fn exists(pool: &RedisPool, id: &StudentId) -> Result<bool, Error> {
+  RedisOperations::exists(pool, &student_key(id)).await
+}
+
+/// List up to `limit` students with ids after `cursor`, optionally only those in `grade`
+///
+/// With a `grade`, only the students in that grade's index set are fetched.
+pub async fn list_page(
+  pool: &RedisPool,
+  cursor: Option<&StudentId>,
+  limit: usize,
+  grade: Option<Grade>,
+) -> Result<StudentPage, Error> {
+  let mut ids: Vec<String> = match grade {
+    Some(grade) => list_by_grade(pool, grade)
+      .await?
+      .iter()
+      .map(StudentId::to_string)
+      .collect(),
+    None => pool
+      .scan_collect(&format!("{}*", STUDENT_KEY_PREFIX))
+      .await?
+      .into_iter()
+      .filter_map(|key| key.strip_prefix(STUDENT_KEY_PREFIX).map(str::to_string))
+      .collect(),
+  };
+  ids.sort();
+  let total_estimate = grade.is_none().then_some(ids.len());
+
+  let start = match cursor {
+    Some(cursor) => ids.partition_point(|id| *id <= cursor.to_string()),
+    None => 0,
+  };
+  let remaining = &ids[start..];
+
+  let mut students = Vec::with_capacity(limit);
+  let mut missing = Vec::new();
+  let mut consumed = 0;
+  // Fetch in page-sized batches until the page is full, since filtering may drop some
+  for batch in remaining.chunks(limit.max(1)) {
+    let keys: Vec<String> = batch.iter().map(|id| key_for(id)).collect();
+    let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+    let values: Vec<Option<String>> = pool.mget(&keys).await?;
+
+    for (id, value) in batch.iter().zip(values) {
+      if students.len() == limit {
+        break;
+      }
+      consumed += 1;
+      match value.map(|json| serde_json::from_str::<Student>(&json)) {
+        Some(Ok(student)) if grade.is_none_or(|g| student.grade == g) => students.push(student),
+        Some(Ok(_)) => {}
+        Some(Err(e)) => warn!("Skipping unparseable student {}: {}", id, e),
+        None => missing.push(id.clone()),
+      }
+    }
+
+    if students.len() == limit {
+      break;
+    }
+  }
+  prune_expired(pool, &missing).await?;
+
+  let next_cursor =
+    (consumed < remaining.len() && consumed > 0).then(|| remaining[consumed - 1].clone());
+
+  Ok(StudentPage {
+    students,
+    next_cursor,
+    total_estimate,
+  })
+}
+
+/// Load every stored student, in no particular order
+pub async fn load_all(pool: &RedisPool) -> Result<Vec<Student>, Error> {
+  let keys = pool
+    .scan_collect(&format!("{}*", STUDENT_KEY_PREFIX))
+    .await?;
+
+  let mut students = Vec::with_capacity(keys.len());
+  for batch in keys.chunks(LOAD_BATCH_SIZE) {
+    let batch: Vec<&str> = batch.iter().map(String::as_str).collect();
+    let values: Vec<Option<String>> = pool.mget(&batch).await?;
+    // Keys removed between SCAN and MGET come back as nil
+    students.extend(parse_records(&batch, values));
+  }
+
+  Ok(students)
+}
+
+/// Delete a student record, leaving a tombstone so sync clients can remove it
+///
+/// The student's email is released so another student can use it.
+pub async fn delete(pool: &RedisPool, id: &StudentId) -> Result<(), Error> {
+  let id_str = id.to_string();
+  let existing = load(pool, id).await?;
+
+  pool.del(&student_key(id)).await?;
+  pool
+    .execute_command::<()>(
+      redis::cmd("ZREM")
+        .arg(pool.prefixed(UPDATED_INDEX_KEY))
+        .arg(&id_str),
+    )
+    .await?;
+  pool
+    .execute_command::<()>(
+      redis::cmd("ZADD")
+        .arg(pool.prefixed(TOMBSTONE_KEY))
+        .arg(Utc::now().timestamp_millis())
+        .arg(&id_str),
+    )
+    .await?;
+
+  let mut pipe = redis::pipe();
+  for grade in INDEXED_GRADES {
+    pipe
+      .srem(pool.prefixed(&grade_key(grade)), &id_str)
+      .ignore();
+  }
+  if let Some(existing) = existing {
+    pipe
+      .del(pool.prefixed(&email_key(&existing.email)))
+      .ignore();
+  }
+  pool.execute_pipeline::<()>(&pipe).await
+}
+
+/// Remove index entries for `ids` whose records have expired or been deleted
+///
+/// Records with a `StorePolicy::student_ttl` expire on their own, leaving their ids in
+/// the grade and change indexes, so reads that come across one call this. The records
+/// are watched, so a student re-created meanwhile keeps their entries.
+async fn prune_expired(pool: &RedisPool, ids: &[String]) -> Result<(), Error> {
+  if ids.is_empty() {
+    return Ok(());
+  }
+
+  let keys: Vec<String> = ids.iter().map(|id| key_for(id)).collect();
+  let watched: Vec<&str> = keys.iter().map(String::as_str).collect();
+  pool
+    .transaction::<(), _, _>(&watched, |mut conn| {
+      let keys = &keys;
+      async move {
+        let mut pipe = redis::pipe();
+        for (id, key) in ids.iter().zip(keys) {
+          let exists: bool = redis::cmd("EXISTS")
+            .arg(pool.prefixed(key))
+            .query_async(&mut conn)
+            .await?;
+          if exists {
+            continue;
+          }
+          debug!("Pruning index entries of missing student {}", id);
+          pipe.zrem(pool.prefixed(UPDATED_INDEX_KEY), id).ignore();
+          for grade in INDEXED_GRADES {
+            pipe.srem(pool.prefixed(&grade_key(grade)), id).ignore();
+          }
+        }
+        Ok(pipe)
+      }
+    })
+    .await
+}
+
+/// Return every student updated or deleted strictly after `since` (epoch millis)
+pub async fn changed_since(pool: &RedisPool, since: i64) -> Result<ChangedSince, Error> {
+  let min = format!("({}", since);
+  let watermark = watermark_at(Utc::now().timestamp_millis(), since);
+
+  let updated: Vec<(String, i64)> = pool
+    .execute_command(
+      redis::cmd("ZRANGEBYSCORE")
+        .arg(pool.prefixed(UPDATED_INDEX_KEY))
+        .arg(&min)
+        .arg("+inf")
+        .arg("WITHSCORES"),
+    )
+    .await?;
+  let deleted: Vec<(String, i64)> = pool
+    .execute_command(
+      redis::cmd("ZRANGEBYSCORE")
+        .arg(pool.prefixed(TOMBSTONE_KEY))
+        .arg(&min)
+        .arg("+inf")
+        .arg("WITHSCORES"),
+    )
+    .await?;
+
+  let mut students = Vec::with_capacity(updated.len());
+  let mut missing = Vec::new();
+  if !updated.is_empty() {
+    let keys: Vec<String> = updated.iter().map(|(id, _)| key_for(id)).collect();
+    let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+    let values: Vec<Option<String>> = pool.mget(&keys).await?;
+
+    for ((id, _), value) in updated.iter().zip(values) {
+      match value.map(|json| serde_json::from_str::<Student>(&json)) {
+        Some(Ok(student)) => students.push(student),
+        Some(Err(e)) => warn!("Skipping unparseable student {}: {}", id, e),
+        None => missing.push(id.clone()),
+      }
+    }
+  }
+  prune_expired(pool, &missing).await?;
+
+  Ok(ChangedSince {
+    students,
+    deleted: deleted.into_iter().map(|(id, _)| id).collect(),
+    watermark,
+  })
+}
+
+/// The watermark for a `changed_since` read at `now_ms`, never behind `since`
+pub(crate) fn watermark_at(now_ms: i64, since: i64) -> i64 {
+  (now_ms - WATERMARK_SKEW_MS).max(since)
+}
+
+// Tests
+#[cfg(all(test, feature = "redis-tests"))]
+mod tests {
+  use super::*;
+  use crate::{init_env, init_logging};
+  use chrono::Datelike;
+
+  async fn setup() -> RedisPool {
+    let _ = init_logging(); // Ignore error if already initialized
+    init_env().unwrap();
+    RedisPool::init().await.unwrap()
+  }
+
+  fn student(id: &str) -> Student {
+    Student::new(
+      id.to_string(),
+      "Sync".to_string(),
+      "Student".to_string(),
+      format!("{}@csxlabs.edu", id),
+      10,
+      Utc::now().year() as u16 + 2,
+      None,
+    )
+    .unwrap()
+  }
+
+  #[tokio::test]
+  async fn test_save_load_round_trip() {
+    let pool = setup().await;
+    let saved = student("910010");
+    save(&pool, &saved).await.unwrap();
+
+    assert!(exists(&pool, &saved.id).await.unwrap());
+    let loaded = load(&pool, &saved.id).await.unwrap().unwrap();
+    assert_eq!(loaded.id.to_string(), saved.id.to_string());
+    assert_eq!(loaded.email, saved.email);
+
+    delete(&pool, &saved.id).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_save_ttl_follows_store_policy() {
+    let pool = setup().await;
+    let saved = student("910030");
+
+    save(&pool, &saved).await.unwrap();
+    assert_eq!(pool.ttl(&student_key(&saved.id)).await.unwrap(), -1);
+
+    let policy = StorePolicy {
+      student_ttl: Some(Duration::from_secs(60)),
+    };
+    save_with_policy(&pool, &saved, &policy).await.unwrap();
+    for key in [student_key(&saved.id), email_key(&saved.email)] {
+      let ttl = pool.ttl(&key).await.unwrap();
+      assert!((1..=60).contains(&ttl), "{}: {}", key, ttl);
+    }
+
+    // A later plain save clears the expiry again
+    save(&pool, &saved).await.unwrap();
+    for key in [student_key(&saved.id), email_key(&saved.email)] {
+      assert_eq!(pool.ttl(&key).await.unwrap(), -1, "{}", key);
+    }
+    delete(&pool, &saved.id).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_reads_prune_index_entries_of_expired_students() {
+    let pool = setup().await;
+    let expired = student("910031");
+    save(&pool, &expired).await.unwrap();
+    // As if the record's TTL ran out
+    pool.del(&student_key(&expired.id)).await.unwrap();
+
+    let page = list_page(&pool, None, 100, Some(10)).await.unwrap();
+    assert!(page.students.iter().all(|s| s.id.to_string() != "910031"));
+    let in_grade: Vec<String> = pool.smembers(&grade_key(10)).await.unwrap();
+    assert!(!in_grade.contains(&"910031".to_string()));
+    let score: Option<i64> = pool
+      .execute_command(
+        redis::cmd("ZSCORE")
+          .arg(pool.prefixed(UPDATED_INDEX_KEY))
+          .arg("910031"),
+      )
+      .await
+      .unwrap();
+    assert_eq!(score, None);
+
+    pool.del(&email_key(&expired.email)).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_create_refuses_existing_id() {
+    let pool = setup().await;
+    let created = student("910032");
+    delete(&pool, &created.id).await.unwrap();
+
+    // Of two racing creations, one wins and the other conflicts
+    let (first, second) = tokio::join!(create(&pool, &created), create(&pool, &created));
+    let conflicts = [&first, &second]
+      .iter()
+      .filter(|result| matches!(result, Err(Error::Conflict(_))))
+      .count();
+    assert!(first.is_ok() || second.is_ok());
+    assert_eq!(conflicts, 1);
+
+    delete(&pool, &created.id).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_load_missing_student_is_none() {
+    let pool = setup().await;
+    let missing = student("910011");
+    delete(&pool, &missing.id).await.unwrap();
+
+    assert!(!exists(&pool, &missing.id).await.unwrap());
+    assert!(load(&pool, &missing.id).await.unwrap().is_none());
+  }
+
+  #[tokio::test]
+  async fn test_load_many_in_one_round_trip() {
+    let pool = setup().await;
+    let seeded = [student("910020"), student("910021"), student("910022")];
+    for student in &seeded {
+      save(&pool, student).await.unwrap();
+    }
+    let missing = student("910023");
+    delete(&pool, &missing.id).await.unwrap();
+    pool
+      .set(&student_key(&seeded[2].id), "not json")
+      .await
+      .unwrap();
+
+    let before = pool.metrics().commands_executed;
+    let ids = [
+      seeded[1].id.clone(),
+      missing.id.clone(),
+      seeded[0].id.clone(),
+      seeded[2].id.clone(),
+    ];
+    let loaded = load_many(&pool, &ids).await.unwrap();
+    assert_eq!(pool.metrics().commands_executed - before, 1);
+
+    // Missing and unparseable records are skipped, the rest keep request order
+    let loaded: Vec<String> = loaded.iter().map(|s| s.id.to_string()).collect();
+    assert_eq!(loaded, ["910021", "910020"]);
+
+    // Restore the corrupted record so it can be deleted normally
+    save(&pool, &seeded[2]).await.unwrap();
+    for student in &seeded {
+      delete(&pool, &student.id).await.unwrap();
+    }
+  }
+
+  #[tokio::test]
+  async fn test_grade_change_moves_student_between_sets() {
+    let pool = setup().await;
+    let mut moved = student("910012");
+    save(&pool, &moved).await.unwrap();
+    let in_grade = |ids: Vec<StudentId>| ids.iter().any(|id| id.to_string() == "910012");
+    assert!(in_grade(list_by_grade(&pool, 10).await.unwrap()));
+
+    moved.update_grade(11).unwrap();
+    save(&pool, &moved).await.unwrap();
+    assert!(!in_grade(list_by_grade(&pool, 10).await.unwrap()));
+    assert!(in_grade(list_by_grade(&pool, 11).await.unwrap()));
+
+    delete(&pool, &moved.id).await.unwrap();
+    assert!(!in_grade(list_by_grade(&pool, 11).await.unwrap()));
+  }
+
+  #[tokio::test]
+  async fn test_taken_email_is_a_conflict() {
+    let pool = setup().await;
+    let first = student("910013");
+    let mut second = student("910014");
+    second.update_email(first.email.clone()).unwrap();
+    save(&pool, &first).await.unwrap();
+
+    let result = save(&pool, &second).await;
+    assert!(matches!(result, Err(Error::Conflict(_))), "{:?}", result);
+    assert!(!exists(&pool, &second.id).await.unwrap());
+
+    // Changing the first student's email frees the old address
+    let mut first = first;
+    first
+      .update_email("910013.new@csxlabs.edu".to_string())
+      .unwrap();
+    save(&pool, &first).await.unwrap();
+    save(&pool, &second).await.unwrap();
+
+    delete(&pool, &first.id).await.unwrap();
+    delete(&pool, &second.id).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_changed_since_returns_only_recent_updates() {
+    let pool = setup().await;
+    let unchanged = student("910001");
+    save(&pool, &unchanged).await.unwrap();
+
+    let watermark = Utc::now().timestamp_millis();
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+
+    let mut updated = student("910002");
+    updated.update_grade(11).unwrap();
+    save(&pool, &updated).await.unwrap();
+
+    let changes = changed_since(&pool, watermark).await.unwrap();
+    let ids: Vec<String> = changes.students.iter().map(|s| s.id.to_string()).collect();
+    assert!(ids.contains(&"910002".to_string()));
+    assert!(!ids.contains(&"910001".to_string()));
+    assert!(changes.watermark >= watermark);
+
+    // The watermark trails the read, so the next poll repeats the recent update
+    let repeat = changed_since(&pool, changes.watermark).await.unwrap();
+    assert!(repeat.students.iter().any(|s| s.id.to_string() == "910002"));
+
+    delete(&pool, &unchanged.id).await.unwrap();
+    delete(&pool, &updated.id).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_list_page_filters_by_grade() {
+    let pool = setup().await;
+    let ids = ["910101", "910102", "910103", "910104"];
+    for (i, id) in ids.iter().enumerate() {
+      let mut student = student(id);
+      student
+        .update_grade(if i % 2 == 0 { 9 } else { 10 })
+        .unwrap();
+      save(&pool, &student).await.unwrap();
+    }
+
+    let mut seen = Vec::new();
+    let mut cursor = None;
+    loop {
+      let page = list_page(&pool, cursor.as_ref(), 1, Some(9)).await.unwrap();
+      assert!(page.students.len() <= 1);
+      assert!(page.total_estimate.is_none());
+      seen.extend(page.students.into_iter().map(|s| {
+        assert_eq!(s.grade, 9);
+        s.id.to_string()
+      }));
+      match page.next_cursor {
+        Some(next) => cursor = Some(StudentId::new(next).unwrap()),
+        None => break,
+      }
+    }
+    assert!(seen.contains(&"910101".to_string()));
+    assert!(seen.contains(&"910103".to_string()));
+    assert!(!seen.contains(&"910102".to_string()));
+
+    for id in ids {
+      delete(&pool, &StudentId::new(id.to_string()).unwrap())
+        .await
+        .unwrap();
+    }
+  }
+
+  #[tokio::test]
+  async fn test_changed_since_reports_tombstones() {
+    let pool = setup().await;
+    let student = student("910003");
+    save(&pool, &student).await.unwrap();
+
+    let watermark = Utc::now().timestamp_millis();
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    delete(&pool, &student.id).await.unwrap();
+
+    let changes = changed_since(&pool, watermark).await.unwrap();
+    assert!(changes.deleted.contains(&"910003".to_string()));
+    assert!(changes
+      .students
+      .iter()
+      .all(|s| s.id.to_string() != "910003"));
+
+    pool
+      .execute_command::<()>(
+        redis::cmd("ZREM")
+          .arg(pool.prefixed(TOMBSTONE_KEY))
+          .arg("910003"),
+      )
+      .await
+      .unwrap();
+  }
+}
@@ -0,0 +1,37 @@
+//! Limits applied when validating student records.
+//!
+//! The defaults match California High School's policy; other districts can build a
+//! `ValidationConfig` of their own and pass it to `Student::new_with_config` and the
+//! `Student::update_*_with_config` methods.
+
+use crate::student::academic_year::DEFAULT_START_MONTH;
+use crate::student::Grade;
+use std::ops::RangeInclusive;
+
+/// Field limits checked by `Student::new_with_config` and the `update_*_with_config` methods
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationConfig {
+  /// Longest first or last name, in bytes (default: 50)
+  pub name_max: usize,
+  /// Longest `special_accommodations` text or accommodation notes, in bytes (default: 500)
+  pub accommodation_max: usize,
+  /// Grades the school has (default: 9 through 12)
+  pub grade_range: RangeInclusive<Grade>,
+  /// How many years past this academic year's seniors a graduation year may be
+  /// (default: 10)
+  pub grad_year_window: u16,
+  /// Month the academic year starts in, 1 to 12 (default: 8, August)
+  pub academic_year_start_month: u32,
+}
+
+impl Default for ValidationConfig {
+  fn default() -> Self {
+    ValidationConfig {
+      name_max: 50,
+      accommodation_max: 500,
+      grade_range: 9..=12,
+      grad_year_window: 10,
+      academic_year_start_month: DEFAULT_START_MONTH,
+    }
+  }
+}
@@ -0,0 +1,57 @@
+//! Runs in its own test binary so it can install a capturing logger in place of log4rs.
+
+use axum::{body::Body, http::Request};
+use backend::http::{self, ACCESS_LOG_TARGET};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::sync::Mutex;
+use tower::ServiceExt;
+
+/// Logger that keeps every access log line in memory
+struct CaptureLogger {
+  lines: Mutex<Vec<String>>,
+}
+
+impl Log for CaptureLogger {
+  fn enabled(&self, metadata: &Metadata) -> bool {
+    metadata.target() == ACCESS_LOG_TARGET
+  }
+
+  fn log(&self, record: &Record) {
+    if self.enabled(record.metadata()) && record.level() == Level::Info {
+      self.lines.lock().unwrap().push(record.args().to_string());
+    }
+  }
+
+  fn flush(&self) {}
+}
+
+static LOGGER: CaptureLogger = CaptureLogger {
+  lines: Mutex::new(Vec::new()),
+};
+
+#[tokio::test]
+async fn test_request_is_logged_with_path_and_status() {
+  log::set_logger(&LOGGER).unwrap();
+  log::set_max_level(LevelFilter::Info);
+
+  let app = http::router(http::AppState::default());
+  app
+    .clone()
+    .oneshot(Request::get("/health_check").body(Body::empty()).unwrap())
+    .await
+    .unwrap();
+  app
+    .oneshot(Request::get("/nope").body(Body::empty()).unwrap())
+    .await
+    .unwrap();
+
+  let lines = LOGGER.lines.lock().unwrap();
+  assert!(
+    lines
+      .iter()
+      .any(|line| line.starts_with("GET /health_check 200 ")),
+    "no access log line in {:?}",
+    lines
+  );
+  assert!(lines.iter().any(|line| line.starts_with("GET /nope 404 ")));
+}
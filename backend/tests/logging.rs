@@ -0,0 +1,18 @@
+//! Runs in its own test binary since it changes the working directory and installs the
+//! global logger.
+
+use backend::{init_logging, logging_initialized};
+
+#[test]
+fn test_init_logging_without_yaml() {
+  let dir = std::env::temp_dir().join(format!("backend-logging-{}", std::process::id()));
+  std::fs::create_dir_all(&dir).unwrap();
+  std::env::set_current_dir(&dir).unwrap();
+  assert!(!dir.join("log4rs.yaml").exists());
+
+  init_logging().unwrap();
+  assert!(logging_initialized());
+  log::info!("Logging initialized without log4rs.yaml");
+
+  std::fs::remove_dir_all(&dir).unwrap();
+}